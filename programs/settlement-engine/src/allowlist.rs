@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use solana_program::hash::hashv;
+
+// Taker allowlists are committed to as a Merkle root over sha256 leaves.
+// Leaves and inner nodes carry distinct prefixes so a node cannot pass as a leaf,
+// and each pair is hashed in sorted order so a proof is just the sibling hashes.
+const LEAF_PREFIX: &[u8] = &[0x00];
+const NODE_PREFIX: &[u8] = &[0x01];
+
+pub fn leaf(taker: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, taker.as_ref()]).to_bytes()
+}
+
+fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, lo, hi]).to_bytes()
+}
+
+// Whether `proof` links the taker's leaf to `root`.
+pub fn verify(root: &[u8; 32], taker: &Pubkey, proof: &[[u8; 32]]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf(taker), |acc, sibling| node(&acc, sibling));
+    computed == *root
+}
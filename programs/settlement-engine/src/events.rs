@@ -0,0 +1,292 @@
+use anchor_lang::prelude::*;
+
+use crate::state::config::{GuardKeyType, PendingConfigUpdate, TransferFeePolicy};
+use crate::state::rfq::{PricingMode, RfqSide, RfqState, SizeMode};
+
+// Lifecycle events, emitted through `emit_cpi!` so indexers can read them from
+// the inner instructions instead of diffing account snapshots.
+
+/// init_config / update_config / execute_config_update / close_config
+#[event]
+pub struct ConfigUpdated {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub treasury_wallet: Pubkey,
+    pub liquidity_guard: Pubkey,
+    pub liquidity_guard_key_type: GuardKeyType,
+    pub facilitator_fee_bps: u16,
+    pub transfer_fee_policy: TransferFeePolicy,
+    pub keeper_bounty_bps: u16,
+    pub timelock_secs: u32,
+    pub closed: bool,
+    pub timestamp: i64,
+}
+
+/// force_close_config: the config was closed while RFQs were still live
+#[event]
+pub struct ConfigForceClosed {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub live_rfq_count: u64,
+    pub timestamp: i64,
+}
+
+/// add_guard_key / revoke_guard_key / remove_guard_key (removed = true)
+#[event]
+pub struct GuardKeyUpdated {
+    pub guard_key: Pubkey,
+    pub key: Pubkey,
+    pub key_type: GuardKeyType,
+    pub valid_from: i64,
+    pub valid_until: Option<i64>,
+    pub removed: bool,
+    pub timestamp: i64,
+}
+
+/// queue_config_update
+#[event]
+pub struct ConfigUpdateQueued {
+    pub config: Pubkey,
+    pub update: PendingConfigUpdate,
+}
+
+/// cancel_config_update
+#[event]
+pub struct ConfigUpdateCancelled {
+    pub config: Pubkey,
+    pub update: PendingConfigUpdate,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// propose_admin (pending_admin = None cancels a pending transfer)
+#[event]
+pub struct AdminProposed {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// accept_admin
+#[event]
+pub struct AdminTransferred {
+    pub config: Pubkey,
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// set_pause (paused holds the Config::PAUSE_* bitflags now in effect)
+#[event]
+pub struct PauseUpdated {
+    pub config: Pubkey,
+    pub pauser: Pubkey,
+    pub previous: u8,
+    pub paused: u8,
+    pub timestamp: i64,
+}
+
+/// set_rfq_pause
+#[event]
+pub struct RfqPauseUpdated {
+    pub rfq: Pubkey,
+    pub pauser: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+/// init_rfq (private RFQs only) / update_rfq: exposes the root, never the member list
+#[event]
+pub struct RfqAllowlistUpdated {
+    pub rfq: Pubkey,
+    pub maker: Pubkey,
+    pub taker_allowlist_root: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+/// init_config / set_config_roles
+#[event]
+pub struct ConfigRolesUpdated {
+    pub config: Pubkey,
+    pub fee_manager: Pubkey,
+    pub guard_manager: Pubkey,
+    pub pauser: Pubkey,
+    pub timestamp: i64,
+}
+
+/// init_rfq
+#[event]
+pub struct RfqInitialized {
+    pub rfq: Pubkey,
+    pub maker: Pubkey,
+    pub uuid: [u8; 16],
+    pub side: RfqSide,
+    pub size_mode: SizeMode,
+    pub pricing_mode: PricingMode,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub bond_amount: u64,
+    pub base_amount: u64,
+    pub min_quote_amount: u64,
+    pub max_quote_amount: u64,
+    pub quote_size: u64,
+    pub taker_fee_bps: u16,
+    pub facilitator: Option<Pubkey>,
+    pub created_at: i64,
+}
+
+/// update_rfq (draft edits)
+#[event]
+pub struct RfqUpdated {
+    pub rfq: Pubkey,
+    pub maker: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub bond_amount: u64,
+    pub base_amount: u64,
+    pub min_quote_amount: u64,
+    pub max_quote_amount: u64,
+    pub quote_size: u64,
+    pub taker_fee_bps: u16,
+    pub facilitator: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// set_rfq_facilitator (quote: None) / set_quote_facilitator
+#[event]
+pub struct FacilitatorUpdated {
+    pub rfq: Pubkey,
+    pub quote: Option<Pubkey>,
+    pub facilitator: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// open_rfq
+#[event]
+pub struct RfqOpened {
+    pub rfq: Pubkey,
+    pub maker: Pubkey,
+    pub bonds_escrow: Pubkey,
+    pub bond_amount: u64,
+    pub prefunded_base_amount: u64,
+    pub opened_at: i64,
+    pub commit_deadline: i64,
+    pub reveal_deadline: i64,
+}
+
+/// commit_quote
+#[event]
+pub struct QuoteCommitted {
+    pub rfq: Pubkey,
+    pub quote: Pubkey,
+    pub taker: Pubkey,
+    pub commit_guard: Pubkey,
+    pub bond_amount: u64,
+    pub facilitator: Option<Pubkey>,
+    pub reveal_delegate: Option<Pubkey>,
+    pub max_notional: u64,
+    pub committed_at: i64,
+}
+
+/// reveal_quote
+#[event]
+pub struct QuoteRevealed {
+    pub rfq: Pubkey,
+    pub quote: Pubkey,
+    pub taker: Pubkey,
+    pub revealer: Pubkey, // the taker or its reveal delegate
+    pub quote_amount: u64,
+    pub base_amount: Option<u64>, // quote-size RFQs only
+    pub revealed_at: i64,
+}
+
+/// select_quote / select_quotes (one per winner) / finalize_auction
+#[event]
+pub struct QuoteSelected {
+    pub rfq: Pubkey,
+    pub quote: Pubkey,
+    pub taker: Pubkey,
+    pub settlement: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub funding_deadline: i64,
+    pub selected_at: i64,
+}
+
+/// complete_settlement
+#[event]
+pub struct SettlementCompleted {
+    pub rfq: Pubkey,
+    pub quote: Pubkey,
+    pub settlement: Pubkey,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub treasury_fee: u64,
+    pub facilitator_fee: u64,
+    pub completed_at: i64,
+}
+
+/// Bonds seized to the treasury (settlement: the no-show winner, if any).
+/// keeper_bounty is the part of `amount` paid to the keeper instead.
+#[event]
+pub struct BondsSlashed {
+    pub rfq: Pubkey,
+    pub settlement: Option<Pubkey>,
+    pub treasury_wallet: Pubkey,
+    pub amount: u64,
+    pub keeper_bounty: u64,
+    pub seized_at: i64,
+}
+
+/// Bond returned to a taker (quote: Some) or to the maker (quote: None)
+#[event]
+pub struct BondsRefunded {
+    pub rfq: Pubkey,
+    pub quote: Option<Pubkey>,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub refunded_at: i64,
+}
+
+/// withdraw_reward
+#[event]
+pub struct RewardWithdrawn {
+    pub rfq: Pubkey,
+    pub quote: Pubkey,
+    pub facilitator: Pubkey,
+    pub amount: u64,
+    pub withdrawn_at: i64,
+}
+
+/// close_quote (refunded quotes are closed by refund_quote_bonds / complete_settlement)
+#[event]
+pub struct QuoteClosed {
+    pub rfq: Pubkey,
+    pub quote: Pubkey,
+    pub taker: Pubkey,
+    pub slashed: bool,
+    pub closed_at: i64,
+}
+
+/// RFQ reached a terminal state (or was cancelled as a draft)
+#[event]
+pub struct RfqClosed {
+    pub rfq: Pubkey,
+    pub maker: Pubkey,
+    pub state: RfqState,
+    pub closed_at: i64,
+}
+
+/// close_rfq: the RFQ and its escrows, trackers and settlements were closed
+#[event]
+pub struct RfqReclaimed {
+    pub rfq: Pubkey,
+    pub maker: Pubkey,
+    pub state: RfqState,
+    pub settlements_closed: u16,
+    pub reclaimed_at: i64,
+}
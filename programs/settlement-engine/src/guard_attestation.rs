@@ -0,0 +1,99 @@
+use crate::rfq_errors::RfqError;
+use anchor_lang::prelude::*;
+use solana_program::hash::hash;
+
+// Domain separator and version prefixed to every commit-hash preimage.
+pub const COMMIT_HASH_DOMAIN: &[u8] = b"settlement-engine:commit";
+pub const COMMIT_HASH_VERSION: u8 = 1;
+
+// Commit hash (version 1) the liquidity guard computes and reveal_quote recomputes:
+// sha256(domain || version || salt || rfq || taker || mint || amount (u64 LE)
+//        || bond_amount (u64 LE) || taker_fee_bps (u16 LE) || rfq.open_nonce (u64 LE)).
+// `mint` / `amount` are the quote mint and amount, or the base mint and amount for
+// quote-size RFQs. The open nonce is never reused, so a commit made against a closed
+// RFQ can never be revealed on one re-created at the same PDA.
+#[allow(clippy::too_many_arguments)]
+pub fn commit_hash(
+    salt: &[u8; 64],
+    rfq: &Pubkey,
+    taker: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    bond_amount: u64,
+    taker_fee_bps: u16,
+    open_nonce: u64,
+) -> [u8; 32] {
+    let mut buf: Vec<u8> =
+        Vec::with_capacity(COMMIT_HASH_DOMAIN.len() + 1 + 64 + 32 * 3 + 8 + 8 + 2 + 8);
+    buf.extend_from_slice(COMMIT_HASH_DOMAIN);
+    buf.push(COMMIT_HASH_VERSION);
+    buf.extend_from_slice(salt);
+    buf.extend_from_slice(rfq.as_ref());
+    buf.extend_from_slice(taker.as_ref());
+    buf.extend_from_slice(mint.as_ref());
+    buf.extend_from_slice(&amount.to_le_bytes());
+    buf.extend_from_slice(&bond_amount.to_le_bytes());
+    buf.extend_from_slice(&taker_fee_bps.to_le_bytes());
+    buf.extend_from_slice(&open_nonce.to_le_bytes());
+    hash(&buf).to_bytes()
+}
+
+// Domain separator prefixed to every liquidity-guard attestation.
+pub const ATTESTATION_DOMAIN: &[u8] = b"settlement-engine:liquidity-guard";
+pub const ATTESTATION_VERSION: u8 = 1;
+
+// domain || version || program_id || rfq || taker || commit_hash || max_notional (u64 LE) || valid_until (i64 LE)
+pub const ATTESTATION_LEN: usize = ATTESTATION_DOMAIN.len() + 1 + 32 * 4 + 8 + 8;
+
+// Message signed by the liquidity guard for one (rfq, taker, commit_hash);
+// commit_hash is laid out as in `commit_hash` above.
+pub struct GuardAttestation {
+    pub program_id: Pubkey,
+    pub rfq: Pubkey,
+    pub taker: Pubkey,
+    pub commit_hash: [u8; 32],
+    pub max_notional: u64,
+    pub valid_until: i64,
+}
+
+impl GuardAttestation {
+    // Parses an attestation, rejecting unknown domains, versions and lengths.
+    pub fn parse(msg: &[u8]) -> Result<Self> {
+        require!(msg.len() == ATTESTATION_LEN, RfqError::InvalidMessageSize);
+        let (domain, rest) = msg.split_at(ATTESTATION_DOMAIN.len());
+        require!(domain == ATTESTATION_DOMAIN, RfqError::InvalidAttestation);
+        let (version, rest) = rest.split_at(1);
+        require!(
+            version[0] == ATTESTATION_VERSION,
+            RfqError::InvalidAttestation
+        );
+
+        let (program_id, rest) = rest.split_at(32);
+        let (rfq, rest) = rest.split_at(32);
+        let (taker, rest) = rest.split_at(32);
+        let (commit_hash, rest) = rest.split_at(32);
+        let (max_notional, valid_until) = rest.split_at(8);
+
+        // Lengths are fixed above, so the conversions cannot fail
+        let to_array = |b: &[u8]| -> Result<[u8; 32]> {
+            b.try_into()
+                .map_err(|_| error!(RfqError::InvalidAttestation))
+        };
+        Ok(Self {
+            program_id: Pubkey::new_from_array(to_array(program_id)?),
+            rfq: Pubkey::new_from_array(to_array(rfq)?),
+            taker: Pubkey::new_from_array(to_array(taker)?),
+            commit_hash: to_array(commit_hash)?,
+            max_notional: u64::from_le_bytes(
+                max_notional
+                    .try_into()
+                    .map_err(|_| error!(RfqError::InvalidAttestation))?,
+            ),
+            valid_until: i64::from_le_bytes(
+                valid_until
+                    .try_into()
+                    .map_err(|_| error!(RfqError::InvalidAttestation))?,
+            ),
+        })
+    }
+}
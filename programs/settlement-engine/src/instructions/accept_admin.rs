@@ -0,0 +1,35 @@
+use crate::events::AdminTransferred;
+use crate::state::config::Config;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Second step of an admin transfer, signed by the proposed admin.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(new_admin.key()) @ RfqError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn accept_admin_handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let cfg = &mut ctx.accounts.config;
+    let previous_admin = cfg.admin;
+    cfg.admin = ctx.accounts.new_admin.key();
+    cfg.pending_admin = None;
+
+    emit_cpi!(AdminTransferred {
+        config: cfg.key(),
+        previous_admin,
+        new_admin: cfg.admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::events::GuardKeyUpdated;
+use crate::state::{Config, GuardKey, GuardKeyType};
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Registers an extra liquidity guard key. Like a queued config change, it only
+/// becomes valid once the config timelock has elapsed.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(key: Pubkey)]
+pub struct AddGuardKey<'info> {
+    #[account(mut)]
+    pub guard_manager: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        has_one = guard_manager @ RfqError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = guard_manager,
+        space = 8 + GuardKey::INIT_SPACE,
+        seeds = [GuardKey::SEED_PREFIX, key.as_ref()],
+        bump,
+    )]
+    pub guard_key: Account<'info, GuardKey>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_guard_key_handler(
+    ctx: Context<AddGuardKey>,
+    key: Pubkey,
+    valid_from: i64,
+    valid_until: Option<i64>,
+    key_type: Option<GuardKeyType>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let earliest = now
+        .checked_add(i64::from(ctx.accounts.config.timelock_secs))
+        .ok_or(RfqError::ArithmeticOverflow)?;
+    require!(valid_from >= earliest, RfqError::InvalidGuardKeyWindow);
+    require!(
+        valid_until.is_none_or(|until| until > valid_from),
+        RfqError::InvalidGuardKeyWindow
+    );
+
+    let guard_key = &mut ctx.accounts.guard_key;
+    guard_key.key = key;
+    guard_key.key_type = key_type.unwrap_or(GuardKeyType::Ed25519);
+    guard_key.valid_from = valid_from;
+    guard_key.valid_until = valid_until;
+    guard_key.rent_payer = ctx.accounts.guard_manager.key();
+    guard_key.bump = ctx.bumps.guard_key;
+
+    emit_cpi!(GuardKeyUpdated {
+        guard_key: guard_key.key(),
+        key,
+        key_type: guard_key.key_type,
+        valid_from,
+        valid_until,
+        removed: false,
+        timestamp: now,
+    });
+
+    Ok(())
+}
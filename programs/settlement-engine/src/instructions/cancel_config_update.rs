@@ -0,0 +1,37 @@
+use crate::events::ConfigUpdateCancelled;
+use crate::state::config::Config;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Drops the queued change; signed by the admin or whoever queued it.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelConfigUpdate<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn cancel_config_update_handler(ctx: Context<CancelConfigUpdate>) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let cfg = &mut ctx.accounts.config;
+    let pending = cfg.pending_update.take().ok_or(RfqError::NoPendingConfigUpdate)?;
+    require!(
+        authority == cfg.admin || authority == pending.queued_by,
+        RfqError::Unauthorized
+    );
+
+    emit_cpi!(ConfigUpdateCancelled {
+        config: cfg.key(),
+        update: pending,
+        cancelled_by: authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 
+use crate::events::ConfigUpdated;
 use crate::state::config::Config;
 use crate::RfqError;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseConfig<'info> {
     #[account(mut)]
@@ -14,10 +16,29 @@ pub struct CloseConfig<'info> {
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
         has_one = admin @ RfqError::Unauthorized,
+        constraint = config.live_rfq_count == 0 @ RfqError::LiveRfqsRemaining,
+        // its SelectionPause record would otherwise never end
+        constraint = !config.is_paused(Config::PAUSE_SELECT_QUOTE) @ RfqError::SelectionPaused,
     )]
     pub config: Account<'info, Config>,
 }
 
-pub fn close_config_handler(_ctx: Context<CloseConfig>) -> Result<()> {
+pub fn close_config_handler(ctx: Context<CloseConfig>) -> Result<()> {
+    let cfg = &ctx.accounts.config;
+    emit_cpi!(ConfigUpdated {
+        config: cfg.key(),
+        admin: cfg.admin,
+        usdc_mint: cfg.usdc_mint,
+        treasury_wallet: cfg.treasury_wallet,
+        liquidity_guard: cfg.liquidity_guard,
+        liquidity_guard_key_type: cfg.liquidity_guard_key_type,
+        facilitator_fee_bps: cfg.facilitator_fee_bps,
+        transfer_fee_policy: cfg.transfer_fee_policy,
+        keeper_bounty_bps: cfg.keeper_bounty_bps,
+        timelock_secs: cfg.timelock_secs,
+        closed: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -0,0 +1,61 @@
+use crate::events::ConfigUpdated;
+use crate::state::config::Config;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Applies the queued change once its `eta` is reached. Anyone may call it.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteConfigUpdate<'info> {
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn execute_config_update_handler(ctx: Context<ExecuteConfigUpdate>) -> Result<()> {
+    let cfg = &mut ctx.accounts.config;
+    let pending = cfg.pending_update.take().ok_or(RfqError::NoPendingConfigUpdate)?;
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= pending.eta, RfqError::TimelockNotElapsed);
+
+    if let Some(v) = pending.usdc_mint {
+        cfg.usdc_mint = v;
+    }
+    if let Some(v) = pending.treasury_wallet {
+        cfg.treasury_wallet = v;
+    }
+    if let Some(v) = pending.liquidity_guard {
+        cfg.liquidity_guard = v;
+    }
+    if let Some(v) = pending.liquidity_guard_key_type {
+        cfg.liquidity_guard_key_type = v;
+    }
+    if let Some(v) = pending.facilitator_fee_bps {
+        cfg.facilitator_fee_bps = v;
+    }
+    if let Some(v) = pending.timelock_secs {
+        cfg.timelock_secs = v;
+    }
+
+    emit_cpi!(ConfigUpdated {
+        config: cfg.key(),
+        admin: cfg.admin,
+        usdc_mint: cfg.usdc_mint,
+        treasury_wallet: cfg.treasury_wallet,
+        liquidity_guard: cfg.liquidity_guard,
+        liquidity_guard_key_type: cfg.liquidity_guard_key_type,
+        facilitator_fee_bps: cfg.facilitator_fee_bps,
+        transfer_fee_policy: cfg.transfer_fee_policy,
+        keeper_bounty_bps: cfg.keeper_bounty_bps,
+        timelock_secs: cfg.timelock_secs,
+        closed: false,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::events::{ConfigForceClosed, ConfigUpdated};
+use crate::state::config::Config;
+use crate::RfqError;
+
+/// Admin escape hatch for close_config: closes the config even while RFQs are
+/// live. Those RFQs are stranded until a config is initialized again.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ForceCloseConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        has_one = admin @ RfqError::Unauthorized,
+        // its SelectionPause record would otherwise never end
+        constraint = !config.is_paused(Config::PAUSE_SELECT_QUOTE) @ RfqError::SelectionPaused,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn force_close_config_handler(ctx: Context<ForceCloseConfig>) -> Result<()> {
+    let cfg = &ctx.accounts.config;
+    let now = Clock::get()?.unix_timestamp;
+    emit_cpi!(ConfigForceClosed {
+        config: cfg.key(),
+        admin: cfg.admin,
+        live_rfq_count: cfg.live_rfq_count,
+        timestamp: now,
+    });
+    emit_cpi!(ConfigUpdated {
+        config: cfg.key(),
+        admin: cfg.admin,
+        usdc_mint: cfg.usdc_mint,
+        treasury_wallet: cfg.treasury_wallet,
+        liquidity_guard: cfg.liquidity_guard,
+        liquidity_guard_key_type: cfg.liquidity_guard_key_type,
+        facilitator_fee_bps: cfg.facilitator_fee_bps,
+        transfer_fee_policy: cfg.transfer_fee_policy,
+        keeper_bounty_bps: cfg.keeper_bounty_bps,
+        timelock_secs: cfg.timelock_secs,
+        closed: true,
+        timestamp: now,
+    });
+
+    Ok(())
+}
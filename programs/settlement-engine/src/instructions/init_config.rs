@@ -1,7 +1,9 @@
-use crate::state::config::Config;
+use crate::events::{ConfigRolesUpdated, ConfigUpdated};
+use crate::state::config::{Config, ConfigGeneration, GuardKeyType, TransferFeePolicy};
 use crate::RfqError;
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitConfig<'info> {
     /// Admin is also the payer. This simplifies auth & testing.
@@ -18,28 +20,89 @@ pub struct InitConfig<'info> {
     )]
     pub config: Account<'info, Config>,
 
+    /// Never closed, so the generation keeps counting across close_config
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + ConfigGeneration::INIT_SPACE,
+        seeds = [ConfigGeneration::SEED_PREFIX],
+        bump,
+    )]
+    pub config_generation: Account<'info, ConfigGeneration>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn init_config_handler(
     ctx: Context<InitConfig>,
     usdc_mint: Pubkey,
     treasury_wallet: Pubkey,
     liquidity_guard: Pubkey,
     facilitator_fee_bps: Option<u16>,
+    transfer_fee_policy: Option<TransferFeePolicy>,
+    keeper_bounty_bps: Option<u16>,
+    timelock_secs: Option<u32>,
+    liquidity_guard_key_type: Option<GuardKeyType>,
 ) -> Result<()> {
     let bump = ctx.bumps.config;
     let cfg = &mut ctx.accounts.config;
 
     let fee_bps = facilitator_fee_bps.unwrap_or(1000);
     require!(fee_bps <= 10_000, RfqError::InvalidFeeAmount);
+    let keeper_bounty_bps = keeper_bounty_bps.unwrap_or(0);
+    require!(
+        keeper_bounty_bps <= Config::MAX_KEEPER_BOUNTY_BPS,
+        RfqError::InvalidKeeperBounty
+    );
 
     cfg.admin = ctx.accounts.admin.key();
     cfg.usdc_mint = usdc_mint;
     cfg.treasury_wallet = treasury_wallet;
     cfg.liquidity_guard = liquidity_guard;
+    cfg.liquidity_guard_key_type = liquidity_guard_key_type.unwrap_or(GuardKeyType::Ed25519);
     cfg.facilitator_fee_bps = fee_bps;
+    cfg.transfer_fee_policy = transfer_fee_policy.unwrap_or(TransferFeePolicy::Reject);
+    cfg.keeper_bounty_bps = keeper_bounty_bps;
+    // every role starts with the admin, who hands them out with set_config_roles
+    cfg.pending_admin = None;
+    cfg.fee_manager = cfg.admin;
+    cfg.guard_manager = cfg.admin;
+    cfg.pauser = cfg.admin;
+    cfg.paused = 0;
+    cfg.timelock_secs = timelock_secs.unwrap_or(Config::DEFAULT_TIMELOCK_SECS);
+    cfg.pending_update = None;
+    cfg.live_rfq_count = 0;
+    let generation = &mut ctx.accounts.config_generation;
+    generation.generation = generation
+        .generation
+        .checked_add(1)
+        .ok_or(RfqError::ArithmeticOverflow)?;
+    generation.bump = ctx.bumps.config_generation;
+    cfg.generation = generation.generation;
     cfg.bump = bump;
 
+    emit_cpi!(ConfigUpdated {
+        config: cfg.key(),
+        admin: cfg.admin,
+        usdc_mint: cfg.usdc_mint,
+        treasury_wallet: cfg.treasury_wallet,
+        liquidity_guard: cfg.liquidity_guard,
+        liquidity_guard_key_type: cfg.liquidity_guard_key_type,
+        facilitator_fee_bps: cfg.facilitator_fee_bps,
+        transfer_fee_policy: cfg.transfer_fee_policy,
+        keeper_bounty_bps: cfg.keeper_bounty_bps,
+        timelock_secs: cfg.timelock_secs,
+        closed: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    emit_cpi!(ConfigRolesUpdated {
+        config: cfg.key(),
+        fee_manager: cfg.fee_manager,
+        guard_manager: cfg.guard_manager,
+        pauser: cfg.pauser,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -1,9 +1,31 @@
 pub mod close_config;
 pub use close_config::*;
+pub mod accept_admin;
+pub use accept_admin::*;
+pub mod add_guard_key;
+pub use add_guard_key::*;
+pub mod cancel_config_update;
+pub use cancel_config_update::*;
+pub mod execute_config_update;
+pub use execute_config_update::*;
+pub mod force_close_config;
+pub use force_close_config::*;
 pub mod init_config;
 pub use init_config::*;
+pub mod propose_admin;
+pub use propose_admin::*;
+pub mod queue_config_update;
+pub use queue_config_update::*;
+pub mod remove_guard_key;
+pub use remove_guard_key::*;
+pub mod revoke_guard_key;
+pub use revoke_guard_key::*;
 pub mod rfq;
 pub use rfq::*;
+pub mod set_config_roles;
+pub use set_config_roles::*;
+pub mod set_pause;
+pub use set_pause::*;
 pub mod update_config;
 pub use update_config::*;
 pub mod quote;
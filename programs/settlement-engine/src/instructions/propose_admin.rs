@@ -0,0 +1,34 @@
+use crate::events::AdminProposed;
+use crate::state::config::Config;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// First step of an admin transfer: the current admin names its successor, who
+/// takes over only once it signs accept_admin. Passing None cancels a pending transfer.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        has_one = admin @ RfqError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn propose_admin_handler(ctx: Context<ProposeAdmin>, new_admin: Option<Pubkey>) -> Result<()> {
+    let cfg = &mut ctx.accounts.config;
+    cfg.pending_admin = new_admin;
+
+    emit_cpi!(AdminProposed {
+        config: cfg.key(),
+        admin: cfg.admin,
+        pending_admin: cfg.pending_admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::events::ConfigUpdateQueued;
+use crate::state::config::{Config, GuardKeyType, PendingConfigUpdate};
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Queues a timelocked change; it can be executed by anyone once `eta` is reached.
+/// The signer must hold the role of every field: admin for the USDC mint, treasury
+/// and timelock, guard manager for the liquidity guard and its key type, fee manager
+/// for the fee.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct QueueConfigUpdate<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.pending_update.is_none() @ RfqError::ConfigUpdatePending,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn queue_config_update_handler(
+    ctx: Context<QueueConfigUpdate>,
+    new_usdc_mint: Option<Pubkey>,
+    new_treasury_wallet: Option<Pubkey>,
+    new_liquidity_guard: Option<Pubkey>,
+    new_facilitator_fee_bps: Option<u16>,
+    new_timelock_secs: Option<u32>,
+    new_liquidity_guard_key_type: Option<GuardKeyType>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let cfg = &mut ctx.accounts.config;
+
+    require!(
+        new_usdc_mint.is_some()
+            || new_treasury_wallet.is_some()
+            || new_liquidity_guard.is_some()
+            || new_liquidity_guard_key_type.is_some()
+            || new_facilitator_fee_bps.is_some()
+            || new_timelock_secs.is_some(),
+        RfqError::InvalidParams
+    );
+    if new_usdc_mint.is_some() || new_treasury_wallet.is_some() || new_timelock_secs.is_some() {
+        require_keys_eq!(authority, cfg.admin, RfqError::Unauthorized);
+    }
+    if new_liquidity_guard.is_some() || new_liquidity_guard_key_type.is_some() {
+        require_keys_eq!(authority, cfg.guard_manager, RfqError::Unauthorized);
+    }
+    if let Some(v) = new_facilitator_fee_bps {
+        require_keys_eq!(authority, cfg.fee_manager, RfqError::Unauthorized);
+        require!(v <= 10_000, RfqError::InvalidFeeAmount);
+    }
+
+    let eta = Clock::get()?
+        .unix_timestamp
+        .checked_add(i64::from(cfg.timelock_secs))
+        .ok_or(RfqError::ArithmeticOverflow)?;
+    let pending = PendingConfigUpdate {
+        usdc_mint: new_usdc_mint,
+        treasury_wallet: new_treasury_wallet,
+        liquidity_guard: new_liquidity_guard,
+        liquidity_guard_key_type: new_liquidity_guard_key_type,
+        facilitator_fee_bps: new_facilitator_fee_bps,
+        timelock_secs: new_timelock_secs,
+        queued_by: authority,
+        eta,
+    };
+    cfg.pending_update = Some(pending);
+
+    emit_cpi!(ConfigUpdateQueued {
+        config: cfg.key(),
+        update: pending,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::events::QuoteClosed;
+use crate::state::rfq::{Rfq, RfqState};
+use crate::state::{CommitGuard, Config, Quote, SlashedBondsTracker};
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Returns the rent of a resolved quote (and of its commit guard) to the taker.
+/// refund_quote_bonds and complete_settlement already close the quotes they refund;
+/// this covers quotes whose bond was slashed. Anyone may call it (e.g. the maker,
+/// so close_rfq is not held up); the rent always goes to the taker.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseQuote<'info> {
+    #[account(mut)]
+    pub taker: SystemAccount<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [Rfq::SEED_PREFIX, rfq.maker.key().as_ref(), rfq.uuid.as_ref()],
+        bump = rfq.bump,
+        has_one = config,
+    )]
+    pub rfq: Box<Account<'info, Rfq>>,
+
+    #[account(
+        mut,
+        close = taker,
+        seeds = [Quote::SEED_PREFIX, rfq.key().as_ref(), taker.key().as_ref()],
+        bump = quote.bump,
+        has_one = rfq,
+        has_one = taker,
+    )]
+    pub quote: Box<Account<'info, Quote>>,
+
+    #[account(
+        mut,
+        close = taker,
+        seeds = [CommitGuard::SEED_PREFIX, quote.commit_hash.as_ref()],
+        bump = commit_guard.bump,
+        constraint = commit_guard.quote == quote.key() @ RfqError::InvalidQuote,
+    )]
+    pub commit_guard: Box<Account<'info, CommitGuard>>,
+
+    #[account(
+        seeds = [SlashedBondsTracker::SEED_PREFIX, rfq.key().as_ref()],
+        bump = slashed_bonds_tracker.bump,
+    )]
+    pub slashed_bonds_tracker: Box<Account<'info, SlashedBondsTracker>>,
+}
+
+pub fn close_quote_handler(ctx: Context<CloseQuote>) -> Result<()> {
+    let rfq = &mut ctx.accounts.rfq;
+    let quote = &ctx.accounts.quote;
+
+    // refunded quotes are closed where they are refunded, so only slashed ones remain:
+    // unrevealed bonds are seized once the tracker is resolved; selected quotes
+    // that are not refunded by the time the RFQ is over were slashed by close_incomplete
+    let slashed = (!quote.is_revealed() && ctx.accounts.slashed_bonds_tracker.is_resolved())
+        || (quote.selected && matches!(rfq.state, RfqState::Settled | RfqState::Incomplete));
+    require!(slashed, RfqError::QuoteNotResolved);
+
+    rfq.record_quote_closed()?;
+
+    emit_cpi!(QuoteClosed {
+        rfq: rfq.key(),
+        quote: quote.key(),
+        taker: quote.taker,
+        slashed,
+        closed_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
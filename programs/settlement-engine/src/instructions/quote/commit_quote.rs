@@ -2,17 +2,22 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::sysvar::instructions::{
     load_current_index_checked, load_instruction_at_checked, ID as INSTRUCTIONS_ID,
 };
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 use crate::{
+    allowlist,
+    events::QuoteCommitted,
+    guard_attestation::{GuardAttestation, ATTESTATION_LEN},
     state::{
-        config::Config,
+        config::{Config, GuardKeyType},
+        guard_key::GuardKey,
         quote::*,
         rfq::{Rfq, RfqState},
     },
     RfqError,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(commit_hash: [u8; 32])]
 pub struct CommitQuote<'info> {
@@ -24,19 +29,31 @@ pub struct CommitQuote<'info> {
     #[account(
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_COMMIT_QUOTE) @ RfqError::ProtocolPaused,
     )]
     pub config: Account<'info, Config>,
 
+    /// Registry entry for the signing guard key, when it is not rfq.liquidity_guard
+    #[account(
+        seeds = [GuardKey::SEED_PREFIX, guard_key.key.as_ref()],
+        bump = guard_key.bump,
+    )]
+    pub guard_key: Option<Account<'info, GuardKey>>,
+
     #[account(
         mut,
         has_one = config,
+        constraint = !rfq.paused @ RfqError::RfqPaused,
         constraint = matches!(rfq.state, RfqState::Open | RfqState::Committed) @ RfqError::InvalidRfqState,
     )]
     pub rfq: Box<Account<'info, Rfq>>,
 
     /// USDC mint from config
-    #[account(address = rfq.usdc_mint)]
-    pub usdc_mint: Account<'info, Mint>,
+    #[account(
+        address = rfq.usdc_mint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
 
     /// One Quote account per (rfq, taker)
     #[account(
@@ -62,16 +79,18 @@ pub struct CommitQuote<'info> {
         mut,
         associated_token::mint = usdc_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = token_program,
     )]
-    pub bonds_escrow: Account<'info, TokenAccount>,
+    pub bonds_escrow: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         token::mint = usdc_mint,
         token::authority = taker,
+        token::token_program = token_program,
         constraint =!taker_payment_account.is_frozen() @ RfqError::TakerPaymentAccountClosed,
     )]
-    pub taker_payment_account: Account<'info, TokenAccount>,
+    pub taker_payment_account: InterfaceAccount<'info, TokenAccount>,
 
     /// Needed because we `init` PDAs (quote, commit_guard)
     pub system_program: Program<'info, System>,
@@ -79,37 +98,46 @@ pub struct CommitQuote<'info> {
     /// CHECK: Address asserted to be the instructions sysvar
     #[account(address = INSTRUCTIONS_ID)]
     pub instruction_sysvar: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn commit_quote_handler(
     ctx: Context<CommitQuote>,
     commit_hash: [u8; 32],
     liquidity_proof: [u8; 64],
     facilitator: Option<Pubkey>,
+    verify_ix_index: Option<u16>,
+    reveal_delegate: Option<Pubkey>,
+    allowlist_proof: Vec<[u8; 32]>,
 ) -> Result<()> {
-    // Verify preflighted Ed25519 signature
-    // Safely get prior instruction
-    let current_index = load_current_index_checked(&ctx.accounts.instruction_sysvar)?;
-    let prev_index = current_index
-        .checked_sub(1)
-        .ok_or_else(|| RfqError::NoEd25519Instruction)?;
-    let ed25519_ix =
-        load_instruction_at_checked(prev_index as usize, &ctx.accounts.instruction_sysvar)?;
+    // Verify preflighted Ed25519 / Secp256r1 signature, found at `verify_ix_index`
+    // (so several commits can share a transaction) or else the prior instruction.
+    // The attestation binds it to this commit, so any index is safe to accept.
+    let sig_index = match verify_ix_index {
+        Some(index) => index,
+        None => load_current_index_checked(&ctx.accounts.instruction_sysvar)?
+            .checked_sub(1)
+            .ok_or(RfqError::NoEd25519Instruction)?,
+    };
+    let sig_ix =
+        load_instruction_at_checked(sig_index as usize, &ctx.accounts.instruction_sysvar)
+            .map_err(|_| error!(RfqError::NoEd25519Instruction))?;
     #[cfg(feature = "debug-logs")]
-    msg!("Prev ix program_id: {}", ed25519_ix.program_id);
+    msg!("Prev ix program_id: {}", sig_ix.program_id);
 
-    // Must be native Ed25519
-    let expected = Pubkey::from_str_const("Ed25519SigVerify111111111111111111111111111");
-    require_keys_eq!(
-        ed25519_ix.program_id,
-        expected,
-        RfqError::InvalidEd25519Program
-    );
+    // Must be native Ed25519 or Secp256r1; both share the same offsets layout
+    let Some(key_type) = GuardKeyType::from_precompile(&sig_ix.program_id) else {
+        return err!(RfqError::InvalidEd25519Program);
+    };
+    let pubkey_len = key_type.pubkey_len();
 
-    // Parse Ed25519 instruction
-    let data = &ed25519_ix.data;
-    require!(data.len() >= 112, RfqError::InvalidEd25519Data);
+    // Parse signature verification instruction
+    let data = &sig_ix.data;
+    require!(
+        data.len() >= 16 + pubkey_len + 64,
+        RfqError::InvalidEd25519Data
+    );
     require!(data[0] == 1, RfqError::InvalidSignatureCount);
 
     let sig_offset = u16::from_le_bytes([data[2], data[3]]) as usize;
@@ -139,7 +167,7 @@ pub fn commit_quote_handler(
     require!(sig_ix_index == 0xFFFF, RfqError::InvalidOffset);
     require!(pubkey_ix_index == 0xFFFF, RfqError::InvalidOffset);
     require!(msg_ix_index == 0xFFFF, RfqError::InvalidOffset);
-    require!(msg_size == 32, RfqError::InvalidMessageSize);
+    require!(msg_size == ATTESTATION_LEN, RfqError::InvalidMessageSize);
 
     // Bounds
     require!(
@@ -147,37 +175,62 @@ pub fn commit_quote_handler(
         RfqError::InvalidEd25519Data
     );
     require!(
-        data.len().saturating_sub(pubkey_offset) >= 32,
+        data.len().saturating_sub(pubkey_offset) >= pubkey_len,
         RfqError::InvalidEd25519Data
     );
     require!(
-        data.len().saturating_sub(msg_offset) >= 32,
+        data.len().saturating_sub(msg_offset) >= msg_size,
         RfqError::InvalidEd25519Data
     );
 
-    // Authorized Liquidity Guard signer check
-    let pubkey_bytes = &data[pubkey_offset..pubkey_offset + 32];
-    require!(
-        pubkey_bytes == ctx.accounts.rfq.liquidity_guard.as_ref(),
-        RfqError::UnauthorizedSigner
-    );
+    let now = Clock::get()?.unix_timestamp;
 
-    // Bind exact 32-byte message
-    let verified_hash_slice = &data[msg_offset..msg_offset + 32];
+    // Authorized Liquidity Guard signer check: the RFQ's snapshotted key, or a
+    // registered key valid at commit time, of the precompile's key type
+    let pubkey_bytes = &data[pubkey_offset..pubkey_offset + pubkey_len];
+    let rfq = &ctx.accounts.rfq;
+    let authorized = (rfq.liquidity_guard_key_type == key_type
+        && key_type.matches(pubkey_bytes, &rfq.liquidity_guard))
+        || ctx.accounts.guard_key.as_ref().is_some_and(|g| {
+            g.key_type == key_type && key_type.matches(pubkey_bytes, &g.key) && g.is_valid_at(now)
+        });
+    require!(authorized, RfqError::UnauthorizedSigner);
+
+    // Bind the versioned attestation to this program, RFQ, taker and commit_hash
+    let attestation = GuardAttestation::parse(&data[msg_offset..msg_offset + msg_size])?;
+    require_keys_eq!(attestation.program_id, crate::ID, RfqError::InvalidAttestation);
+    require_keys_eq!(
+        attestation.rfq,
+        ctx.accounts.rfq.key(),
+        RfqError::InvalidAttestation
+    );
+    require_keys_eq!(
+        attestation.taker,
+        ctx.accounts.taker.key(),
+        RfqError::InvalidAttestation
+    );
     require!(
-        verified_hash_slice == &commit_hash,
+        attestation.commit_hash == commit_hash,
         RfqError::CommitHashMismatch
     );
+    require!(now <= attestation.valid_until, RfqError::AttestationExpired);
 
     // Bind exact 64-byte signature (liquidity_proof)
     let verified_signature_slice = &data[sig_offset..sig_offset + 64];
     require!(
-        verified_signature_slice == &liquidity_proof,
+        verified_signature_slice == liquidity_proof,
         RfqError::LiquidityProofSignatureMismatch
     );
 
+    // Private RFQs only accept takers proven to be in the maker's allowlist
+    if let Some(root) = ctx.accounts.rfq.taker_allowlist_root {
+        require!(
+            allowlist::verify(&root, &ctx.accounts.taker.key(), &allowlist_proof),
+            RfqError::TakerNotAllowlisted
+        );
+    }
+
     // Process Commit Quote
-    let now = Clock::get()?.unix_timestamp;
     let rfq = &mut ctx.accounts.rfq;
 
     let Some(commit_deadline) = rfq.commit_deadline() else {
@@ -190,14 +243,19 @@ pub fn commit_quote_handler(
     };
 
     // Transfer taker bond USDC into RFQ's vault
-    let cpi_accounts = Transfer {
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.taker_payment_account.to_account_info(),
+        mint: ctx.accounts.usdc_mint.to_account_info(),
         to: ctx.accounts.bonds_escrow.to_account_info(),
         authority: ctx.accounts.taker.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, rfq.bond_amount)?;
+    token_interface::transfer_checked(
+        cpi_ctx,
+        rfq.bond_amount,
+        ctx.accounts.usdc_mint.decimals,
+    )?;
 
     // Fill Quote (commit-only fields)
     let quote = &mut ctx.accounts.quote;
@@ -214,14 +272,17 @@ pub fn commit_quote_handler(
     quote.taker = ctx.accounts.taker.key();
     quote.commit_hash = commit_hash;
     quote.liquidity_proof = liquidity_proof;
+    quote.max_notional = attestation.max_notional;
     quote.committed_at = now;
     quote.revealed_at = None;
     quote.max_funding_deadline = funding_deadline;
     quote.selected = false;
     quote.bonds_refunded_at = None;
     quote.quote_amount = None; // to be filled on reveal
+    quote.base_amount = None;
     quote.taker_payment_account = ctx.accounts.taker_payment_account.key();
     quote.facilitator = facilitator;
+    quote.reveal_delegate = reveal_delegate;
 
     rfq.state = RfqState::Committed;
     rfq.committed_count = rfq
@@ -229,5 +290,17 @@ pub fn commit_quote_handler(
         .checked_add(1)
         .ok_or(RfqError::ArithmeticOverflow)?;
 
+    emit_cpi!(QuoteCommitted {
+        rfq: rfq.key(),
+        quote: quote.key(),
+        taker: quote.taker,
+        commit_guard: commit_guard.key(),
+        bond_amount: rfq.bond_amount,
+        facilitator,
+        reveal_delegate,
+        max_notional: attestation.max_notional,
+        committed_at: now,
+    });
+
     Ok(())
 }
@@ -6,3 +6,5 @@ pub mod reveal_quote;
 pub use reveal_quote::*;
 pub mod refund_quote_bonds;
 pub use refund_quote_bonds::*;
+pub mod close_quote;
+pub use close_quote::*;
@@ -1,19 +1,25 @@
+use crate::events::{BondsRefunded, BondsSlashed, RfqClosed};
+use crate::instructions::open_rfq::refund_prefunded_base;
 use crate::state::rfq::{Rfq, RfqState};
 use crate::slashing::compute_slashed_amount;
-use crate::state::{Config, Quote, SlashedBondsTracker};
+use crate::state::{
+    CommitGuard, Config, ConfigGeneration, Quote, SelectionPause, SlashedBondsTracker,
+};
 use crate::RfqError;
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RefundQuoteBonds<'info> {
     #[account(mut)]
     pub taker: Signer<'info>,
 
     #[account(
+        mut,
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
     )]
@@ -33,39 +39,57 @@ pub struct RefundQuoteBonds<'info> {
         constraint = matches!(rfq.state, RfqState::Revealed | RfqState::Selected | RfqState::Settled | RfqState::Ignored | RfqState::Incomplete) @ RfqError::InvalidRfqState,)]
     pub rfq: Box<Account<'info, Rfq>>,
 
+    /// Closed back to the taker once the bond is refunded
     #[account(
         mut,
+        close = taker,
         seeds = [Quote::SEED_PREFIX, rfq.key().as_ref(), taker.key().as_ref()],
         bump = quote.bump,
     )]
     pub quote: Box<Account<'info, Quote>>,
 
-    #[account(address = rfq.usdc_mint)]
-    pub usdc_mint: Box<Account<'info, Mint>>,
+    /// The RFQ no longer takes commits, so the guard can go with the quote
+    #[account(
+        mut,
+        close = taker,
+        seeds = [CommitGuard::SEED_PREFIX, quote.commit_hash.as_ref()],
+        bump = commit_guard.bump,
+        constraint = commit_guard.quote == quote.key() @ RfqError::InvalidQuote,
+    )]
+    pub commit_guard: Box<Account<'info, CommitGuard>>,
+
+    #[account(
+        address = rfq.usdc_mint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         init_if_needed,
         payer = taker,
         associated_token::mint = usdc_mint,
         associated_token::authority = treasury_wallet,
+        associated_token::token_program = token_program,
     )]
-    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = usdc_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = token_program,
         address = rfq.bonds_escrow,
     )]
-    pub bonds_escrow: Box<Account<'info, TokenAccount>>,
+    pub bonds_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         token::mint = usdc_mint,
         token::authority = taker,
+        token::token_program = token_program,
         address = quote.taker_payment_account,
     )]
-    pub taker_payment_account: Box<Account<'info, TokenAccount>>,
+    pub taker_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -76,12 +100,59 @@ pub struct RefundQuoteBonds<'info> {
     )]
     pub slashed_bonds_tracker: Box<Account<'info, SlashedBondsTracker>>,
 
+    #[account(
+        seeds = [ConfigGeneration::SEED_PREFIX],
+        bump = config_generation.bump,
+    )]
+    pub config_generation: Box<Account<'info, ConfigGeneration>>,
+
+    /// Ignored path, once any selection pause exists: the last SelectionPause started
+    /// at or before the selection deadline (if any), and the one right after it
+    /// (unless it is the latest). Together they prove whether a pause overlapped the window.
+    pub selection_pause: Option<Box<Account<'info, SelectionPause>>>,
+    pub next_selection_pause: Option<Box<Account<'info, SelectionPause>>>,
+
+    /// Required when the maker bond is returned rather than slashed: nothing was
+    /// selected, but selection was paused during the selection window
+    #[account(
+        mut,
+        address = rfq.maker_payment_account @ RfqError::UnauthorizedMakerPaymentAccount,
+    )]
+    pub maker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Firm RFQs only: the pre-funded base is returned to the maker
+    #[account(
+        address = rfq.base_mint @ RfqError::InvalidBaseMint,
+        mint::token_program = base_token_program,
+    )]
+    pub base_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = rfq,
+        associated_token::token_program = base_token_program,
+    )]
+    pub vault_base_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = base_mint,
+        token::authority = rfq.maker,
+        token::token_program = base_token_program,
+    )]
+    pub maker_base_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub base_token_program: Option<Interface<'info, TokenInterface>>,
+
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn refund_quote_bonds_handler(ctx: Context<RefundQuoteBonds>) -> Result<()> {
+pub fn refund_quote_bonds_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RefundQuoteBonds<'info>>,
+) -> Result<()> {
     let rfq = &mut ctx.accounts.rfq;
     let quote = &mut ctx.accounts.quote;
     let slashed_bonds_tracker = &mut ctx.accounts.slashed_bonds_tracker;
@@ -110,57 +181,140 @@ pub fn refund_quote_bonds_handler(ctx: Context<RefundQuoteBonds>) -> Result<()>
     ];
 
     // Refund taker's bond
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.bonds_escrow.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
                 to: ctx.accounts.taker_payment_account.to_account_info(),
                 authority: rfq.to_account_info(),
             },
             &[seeds_rfq],
         ),
         rfq.bond_amount,
+        ctx.accounts.usdc_mint.decimals,
     )?;
 
     // update quote
     quote.bonds_refunded_at = Some(now);
 
+    emit_cpi!(BondsRefunded {
+        rfq: rfq.key(),
+        quote: Some(quote.key()),
+        owner: quote.taker,
+        amount: rfq.bond_amount,
+        refunded_at: now,
+    });
+
     if !slashed_bonds_tracker.is_resolved() {
         match rfq.state {
             // maker didn't select a valid quote
-            // or taker didn't complete settlement
+            // or a winner didn't complete settlement
             RfqState::Revealed | RfqState::Selected => {
-                // Seize unrevealed bonds plus the maker/selected taker bond
-                let seized_amount = compute_slashed_amount(rfq, true)?;
+                // Seize unrevealed bonds, plus the maker bond when nothing was selected,
+                // unless selection was paused, protocol-wide or for this RFQ, at some
+                // point of [reveal_deadline, selection_deadline].
+                // No-show winners' bonds are seized per settlement by close_incomplete.
+                let ignored = matches!(rfq.state, RfqState::Revealed);
+                let maker_excused = ignored
+                    && (rfq.was_selection_paused()?
+                        || SelectionPause::paused_during(
+                            &ctx.accounts.config_generation,
+                            rfq.reveal_deadline().ok_or(RfqError::InvalidRfqState)?,
+                            rfq.selection_deadline().ok_or(RfqError::InvalidRfqState)?,
+                            ctx.accounts.selection_pause.as_deref().map(|p| &**p),
+                            ctx.accounts.next_selection_pause.as_deref().map(|p| &**p),
+                        )?);
+                let seized_amount = compute_slashed_amount(rfq, ignored && !maker_excused)?;
+
+                if maker_excused {
+                    let maker_payment_account = ctx
+                        .accounts
+                        .maker_payment_account
+                        .as_ref()
+                        .ok_or(RfqError::MissingMakerPaymentAccount)?;
+                    token_interface::transfer_checked(
+                        CpiContext::new_with_signer(
+                            ctx.accounts.token_program.to_account_info(),
+                            TransferChecked {
+                                from: ctx.accounts.bonds_escrow.to_account_info(),
+                                mint: ctx.accounts.usdc_mint.to_account_info(),
+                                to: maker_payment_account.to_account_info(),
+                                authority: rfq.to_account_info(),
+                            },
+                            &[seeds_rfq],
+                        ),
+                        rfq.bond_amount,
+                        ctx.accounts.usdc_mint.decimals,
+                    )?;
+                    emit_cpi!(BondsRefunded {
+                        rfq: rfq.key(),
+                        quote: None,
+                        owner: rfq.maker,
+                        amount: rfq.bond_amount,
+                        refunded_at: now,
+                    });
+                }
 
                 if seized_amount > 0 {
-                    token::transfer(
+                    token_interface::transfer_checked(
                         CpiContext::new_with_signer(
                             ctx.accounts.token_program.to_account_info(),
-                            Transfer {
+                            TransferChecked {
                                 from: ctx.accounts.bonds_escrow.to_account_info(),
+                                mint: ctx.accounts.usdc_mint.to_account_info(),
                                 to: ctx.accounts.treasury_ata.to_account_info(),
                                 authority: rfq.to_account_info(),
                             },
                             &[seeds_rfq],
                         ),
                         seized_amount,
+                        ctx.accounts.usdc_mint.decimals,
                     )?;
                 }
 
                 // update slashed bonds tracker
                 slashed_bonds_tracker.amount = Some(seized_amount);
                 slashed_bonds_tracker.seized_at = Some(now);
+                if seized_amount > 0 {
+                    emit_cpi!(BondsSlashed {
+                        rfq: rfq.key(),
+                        settlement: None,
+                        treasury_wallet: rfq.treasury_wallet,
+                        amount: seized_amount,
+                        keeper_bounty: 0,
+                        seized_at: now,
+                    });
+                }
                 // update rfq
                 if matches!(rfq.state, RfqState::Revealed) {
+                    // Firm RFQs: give the pre-funded base back to the maker
+                    refund_prefunded_base(
+                        rfq,
+                        ctx.accounts.base_mint.as_deref(),
+                        ctx.accounts.vault_base_ata.as_deref(),
+                        ctx.accounts.maker_base_account.as_deref(),
+                        ctx.accounts.base_token_program.as_ref(),
+                        ctx.remaining_accounts,
+                    )?;
                     rfq.state = RfqState::Ignored;
                     rfq.completed_at = Some(now);
+                    ctx.accounts.config.record_rfq_closed(rfq.config_generation)?;
+                    emit_cpi!(RfqClosed {
+                        rfq: rfq.key(),
+                        maker: rfq.maker,
+                        state: RfqState::Ignored,
+                        closed_at: now,
+                    });
                 }
             }
             _ => (), // do nothing
         }
     }
 
+    // the quote is closed to the taker on exit
+    rfq.record_quote_closed()?;
+
     Ok(())
 }
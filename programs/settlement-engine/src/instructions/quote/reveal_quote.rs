@@ -1,18 +1,22 @@
 use crate::{
+    events::QuoteRevealed,
+    guard_attestation::commit_hash,
     state::{
         config::Config,
         quote::Quote,
-        rfq::{Rfq, RfqState},
+        rfq::{Rfq, RfqState, SizeMode},
     },
     RfqError,
 };
 use anchor_lang::prelude::*;
-use solana_program::hash::hash;
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RevealQuote<'info> {
-    /// Taker revealing their previously committed quote
-    #[account(mut)]
-    pub taker: Signer<'info>,
+    /// Taker who committed the quote; need not sign when a reveal delegate does
+    pub taker: SystemAccount<'info>,
+
+    /// The taker, or the quote's reveal delegate
+    pub revealer: Signer<'info>,
 
     #[account(
         seeds = [Config::SEED_PREFIX],
@@ -35,6 +39,8 @@ pub struct RevealQuote<'info> {
         bump = quote.bump,
         has_one = rfq,
         has_one = taker,
+        constraint = revealer.key() == quote.taker
+            || quote.reveal_delegate == Some(revealer.key()) @ RfqError::Unauthorized,
         constraint = !quote.is_revealed() @ RfqError::QuoteAlreadyRevealed,
     )]
     pub quote: Account<'info, Quote>,
@@ -44,8 +50,8 @@ pub fn reveal_quote_handler(
     ctx: Context<RevealQuote>,
     // unique salt from the liquidity-guard response (NOT stored on-chain)
     salt: [u8; 64],
-    // revealed quote amount in smallest units of rfq.quote_mint
-    quote_amount: u64,
+    // revealed amount: quote tokens, or base tokens in quote-size RFQs (smallest units)
+    amount: u64,
 ) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
     let rfq = &mut ctx.accounts.rfq;
@@ -59,42 +65,52 @@ pub fn reveal_quote_handler(
         _ => return err!(RfqError::InvalidRfqState),
     }
 
+    // Quote-size RFQs bind the base mint and base amount in the quote mint/amount slots
+    let committed_mint = match rfq.size_mode {
+        SizeMode::Base => rfq.quote_mint,
+        SizeMode::Quote => rfq.base_mint,
+    };
+
     // Recompute commit_hash EXACTLY the same way liquidity-guard did.
     // This must match the Rust code in:
     //   https://github.com/unleaktrade/liquidity-guard
-    let mut buf: Vec<u8> = Vec::with_capacity(
-        64 + // salt
-        32 + // rfq pubkey
-        32 + // taker pubkey
-        32 + // quote mint
-        8  + // quote amount
-        8  + // bond amount
-        2, // taker_fee_bps
+    let computed = commit_hash(
+        &salt,
+        &rfq.key(),
+        &quote.taker,
+        &committed_mint,
+        amount,
+        rfq.bond_amount,
+        rfq.taker_fee_bps,
+        rfq.open_nonce,
     );
-    buf.extend_from_slice(&salt);
-    buf.extend_from_slice(rfq.key().as_ref());
-    buf.extend_from_slice(ctx.accounts.taker.key().as_ref());
-    buf.extend_from_slice(rfq.quote_mint.key().as_ref());
-    buf.extend_from_slice(&quote_amount.to_le_bytes());
-    buf.extend_from_slice(&rfq.bond_amount.to_le_bytes());
-    buf.extend_from_slice(&rfq.taker_fee_bps.to_le_bytes());
-
-    let computed = hash(&buf).to_bytes();
     #[cfg(feature = "debug-logs")]
     msg!("Computed commit hash: {:?}", computed);
     #[cfg(feature = "debug-logs")]
     msg!("Stored commit hash:   {:?}", quote.commit_hash);
     require!(computed == quote.commit_hash, RfqError::Unauthorized);
 
-    // Enforce price floor
+    // Enforce price floor (Sell), ceiling (Buy) or base floor (quote-size)
     require!(
-        quote_amount >= rfq.min_quote_amount,
+        rfq.within_price_limit(amount),
         RfqError::InvalidQuoteAmount
     );
 
+    // Committed amount must stay within the notional attested by the liquidity guard
+    require!(
+        amount <= quote.max_notional,
+        RfqError::ExceedsAttestedNotional
+    );
+
     // Mark as valid reveal
     quote.revealed_at = Some(now);
-    quote.quote_amount = Some(quote_amount);
+    match rfq.size_mode {
+        SizeMode::Base => quote.quote_amount = Some(amount),
+        SizeMode::Quote => {
+            quote.quote_amount = Some(rfq.quote_size);
+            quote.base_amount = Some(amount);
+        }
+    }
 
     // Update RFQ reveal counters/state
     rfq.revealed_count = rfq
@@ -102,6 +118,17 @@ pub fn reveal_quote_handler(
         .checked_add(1)
         .ok_or(RfqError::ArithmeticOverflow)?;
     rfq.state = RfqState::Revealed;
+    rfq.record_reveal(quote.key(), amount);
+
+    emit_cpi!(QuoteRevealed {
+        rfq: rfq.key(),
+        quote: quote.key(),
+        taker: quote.taker,
+        revealer: ctx.accounts.revealer.key(),
+        quote_amount: quote.quote_amount.ok_or(RfqError::InvalidQuoteState)?,
+        base_amount: quote.base_amount,
+        revealed_at: now,
+    });
 
     Ok(())
 }
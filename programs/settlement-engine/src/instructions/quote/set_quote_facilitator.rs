@@ -1,4 +1,5 @@
 use crate::{
+    events::FacilitatorUpdated,
     state::{
         quote::Quote,
         rfq::{FacilitatorUpdate, Rfq, RfqState},
@@ -7,6 +8,7 @@ use crate::{
 };
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SetQuoteFacilitator<'info> {
     #[account(mut)]
@@ -46,5 +48,11 @@ pub fn set_quote_facilitator_handler(
         FacilitatorUpdate::Clear => quote.facilitator = None,
         FacilitatorUpdate::Set(key) => quote.facilitator = Some(key),
     }
+    emit_cpi!(FacilitatorUpdated {
+        rfq: quote.rfq,
+        quote: Some(quote.key()),
+        facilitator: quote.facilitator,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
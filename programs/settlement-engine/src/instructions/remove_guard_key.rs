@@ -0,0 +1,51 @@
+use crate::events::GuardKeyUpdated;
+use crate::state::{Config, GuardKey};
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Closes an expired guard key, refunding its rent to whoever registered it.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveGuardKey<'info> {
+    pub guard_manager: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        has_one = guard_manager @ RfqError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [GuardKey::SEED_PREFIX, guard_key.key.as_ref()],
+        bump = guard_key.bump,
+        has_one = rent_payer @ RfqError::InvalidRentPayer,
+    )]
+    pub guard_key: Account<'info, GuardKey>,
+
+    #[account(mut)]
+    pub rent_payer: SystemAccount<'info>,
+}
+
+pub fn remove_guard_key_handler(ctx: Context<RemoveGuardKey>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let guard_key = &ctx.accounts.guard_key;
+    require!(
+        guard_key.valid_until.is_some_and(|until| until <= now),
+        RfqError::GuardKeyStillValid
+    );
+
+    emit_cpi!(GuardKeyUpdated {
+        guard_key: guard_key.key(),
+        key: guard_key.key,
+        key_type: guard_key.key_type,
+        valid_from: guard_key.valid_from,
+        valid_until: guard_key.valid_until,
+        removed: true,
+        timestamp: now,
+    });
+
+    Ok(())
+}
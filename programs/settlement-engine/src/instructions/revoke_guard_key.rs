@@ -0,0 +1,50 @@
+use crate::events::GuardKeyUpdated;
+use crate::state::{Config, GuardKey};
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Ends a guard key's validity at `valid_until` (now when None). Takes effect
+/// immediately and can only shorten the window, never extend it.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeGuardKey<'info> {
+    pub guard_manager: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        has_one = guard_manager @ RfqError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [GuardKey::SEED_PREFIX, guard_key.key.as_ref()],
+        bump = guard_key.bump,
+    )]
+    pub guard_key: Account<'info, GuardKey>,
+}
+
+pub fn revoke_guard_key_handler(ctx: Context<RevokeGuardKey>, valid_until: Option<i64>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let guard_key = &mut ctx.accounts.guard_key;
+
+    let until = valid_until.unwrap_or(now);
+    require!(
+        guard_key.valid_until.is_none_or(|current| until <= current),
+        RfqError::InvalidGuardKeyWindow
+    );
+    guard_key.valid_until = Some(until);
+
+    emit_cpi!(GuardKeyUpdated {
+        guard_key: guard_key.key(),
+        key: guard_key.key,
+        key_type: guard_key.key_type,
+        valid_from: guard_key.valid_from,
+        valid_until: guard_key.valid_until,
+        removed: false,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
+use crate::events::RfqClosed;
 use crate::state::rfq::{Rfq, RfqState};
 use crate::RfqError;
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CancelRfq<'info> {
     #[account(mut)]
@@ -17,14 +19,21 @@ pub struct CancelRfq<'info> {
     pub rfq: Account<'info, Rfq>,
 }
 
-pub fn cancel_rfq_handler(_ctx: Context<CancelRfq>) -> Result<()> {
+pub fn cancel_rfq_handler(ctx: Context<CancelRfq>) -> Result<()> {
     #[cfg(feature = "debug-logs")]
     msg!(
         "RFQ {} cancelled by maker {}",
-        _ctx.accounts.rfq.key().to_string(),
-        _ctx.accounts.maker.key()
+        ctx.accounts.rfq.key().to_string(),
+        ctx.accounts.maker.key()
     );
 
+    emit_cpi!(RfqClosed {
+        rfq: ctx.accounts.rfq.key(),
+        maker: ctx.accounts.maker.key(),
+        state: RfqState::Draft,
+        closed_at: Clock::get()?.unix_timestamp,
+    });
+
     // Account will be closed automatically, transferring lamports to maker
     Ok(())
 }
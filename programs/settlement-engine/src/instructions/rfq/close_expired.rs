@@ -1,13 +1,20 @@
+use crate::events::{BondsRefunded, BondsSlashed, RfqClosed};
+use crate::instructions::open_rfq::refund_prefunded_base;
 use crate::state::rfq::{Rfq, RfqState};
-use crate::{slashing::compute_slashed_amount, state::Config, state::SlashedBondsTracker, RfqError};
+use crate::slashing::{compute_slashed_amount, seize_bonds};
+use crate::{state::Config, state::SlashedBondsTracker, RfqError};
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseExpired<'info> {
+    /// Anyone may expire the RFQ once the reveal deadline has passed
     #[account(mut)]
-    pub maker: Signer<'info>,
+    pub keeper: Signer<'info>,
+
+    pub maker: SystemAccount<'info>,
 
     #[account(
         mut,
@@ -19,20 +26,25 @@ pub struct CloseExpired<'info> {
     pub rfq: Box<Account<'info, Rfq>>,
 
     #[account(
+        mut,
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
     )]
     pub config: Account<'info, Config>,
 
-    #[account(address = rfq.usdc_mint)]
-    pub usdc_mint: Account<'info, Mint>,
+    #[account(
+        address = rfq.usdc_mint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
         associated_token::mint = usdc_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = token_program,
     )]
-    pub bonds_escrow: Account<'info, TokenAccount>,
+    pub bonds_escrow: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
@@ -42,19 +54,29 @@ pub struct CloseExpired<'info> {
 
     #[account(
         init_if_needed,
-        payer = maker,
+        payer = keeper,
         associated_token::mint = usdc_mint,
         associated_token::authority = treasury_wallet,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the keeper bounty out of the slashed bonds, when given
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+        token::token_program = token_program,
     )]
-    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+    pub keeper_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     #[account(
         mut,
         token::mint = usdc_mint,
         token::authority = maker,
+        token::token_program = token_program,
         constraint = rfq.maker_payment_account == maker_payment_account.key() @ RfqError::UnauthorizedMakerPaymentAccount,
     )]
-    pub maker_payment_account: Account<'info, TokenAccount>,
+    pub maker_payment_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
@@ -65,12 +87,39 @@ pub struct CloseExpired<'info> {
     )]
     pub slashed_bonds_tracker: Account<'info, SlashedBondsTracker>,
 
+    /// Firm RFQs only: the pre-funded base is returned to the maker
+    #[account(
+        address = rfq.base_mint @ RfqError::InvalidBaseMint,
+        mint::token_program = base_token_program,
+    )]
+    pub base_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = rfq,
+        associated_token::token_program = base_token_program,
+    )]
+    pub vault_base_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = base_mint,
+        token::authority = maker,
+        token::token_program = base_token_program,
+    )]
+    pub maker_base_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub base_token_program: Option<Interface<'info, TokenInterface>>,
+
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn close_expired_handler(ctx: Context<CloseExpired>) -> Result<()> {
+pub fn close_expired_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseExpired<'info>>,
+) -> Result<()> {
     let rfq = &mut ctx.accounts.rfq;
     let slashed_bonds_tracker = &mut ctx.accounts.slashed_bonds_tracker;
 
@@ -86,45 +135,79 @@ pub fn close_expired_handler(ctx: Context<CloseExpired>) -> Result<()> {
         rfq.uuid.as_ref(),
         &[rfq.bump],
     ];
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.bonds_escrow.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
                 to: ctx.accounts.maker_payment_account.to_account_info(),
                 authority: rfq.to_account_info(),
             },
             &[seeds_rfq],
         ),
         rfq.bond_amount,
+        ctx.accounts.usdc_mint.decimals,
     )?;
 
     if !slashed_bonds_tracker.is_resolved() {
         // Seize only unrevealed bonds (no selected/maker extra bond)
         let seized_amount = compute_slashed_amount(rfq, false)?;
-
-        if seized_amount > 0 {
-            token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.bonds_escrow.to_account_info(),
-                        to: ctx.accounts.treasury_ata.to_account_info(),
-                        authority: rfq.to_account_info(),
-                    },
-                    &[seeds_rfq],
-                ),
-                seized_amount,
-            )?;
-        }
+        let keeper_bounty = seize_bonds(
+            rfq,
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.usdc_mint,
+            &ctx.accounts.bonds_escrow,
+            &ctx.accounts.treasury_ata,
+            ctx.accounts.keeper_payment_account.as_deref(),
+            seized_amount,
+            seeds_rfq,
+        )?;
 
         // update slashed bonds tracker
         slashed_bonds_tracker.amount = Some(seized_amount);
+        slashed_bonds_tracker.keeper_bounty = keeper_bounty;
         slashed_bonds_tracker.seized_at = Some(now);
+
+        if seized_amount > 0 {
+            emit_cpi!(BondsSlashed {
+                rfq: rfq.key(),
+                settlement: None,
+                treasury_wallet: rfq.treasury_wallet,
+                amount: seized_amount,
+                keeper_bounty,
+                seized_at: now,
+            });
+        }
     }
+    // Firm RFQs: give the pre-funded base back to the maker
+    refund_prefunded_base(
+        rfq,
+        ctx.accounts.base_mint.as_deref(),
+        ctx.accounts.vault_base_ata.as_deref(),
+        ctx.accounts.maker_base_account.as_deref(),
+        ctx.accounts.base_token_program.as_ref(),
+        ctx.remaining_accounts,
+    )?;
+
     // update rfq
     rfq.state = RfqState::Expired;
     rfq.completed_at = Some(now);
+    ctx.accounts.config.record_rfq_closed(rfq.config_generation)?;
+
+    emit_cpi!(BondsRefunded {
+        rfq: rfq.key(),
+        quote: None,
+        owner: rfq.maker,
+        amount: rfq.bond_amount,
+        refunded_at: now,
+    });
+    emit_cpi!(RfqClosed {
+        rfq: rfq.key(),
+        maker: rfq.maker,
+        state: RfqState::Expired,
+        closed_at: now,
+    });
 
     Ok(())
 }
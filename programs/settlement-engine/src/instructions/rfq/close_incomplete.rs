@@ -1,17 +1,24 @@
-use crate::state::rfq::{Rfq, RfqState};
-use crate::slashing::compute_slashed_amount;
+use crate::events::{BondsRefunded, BondsSlashed, RfqClosed};
+use crate::state::rfq::{Rfq, RfqSide, RfqState};
+use crate::slashing::{compute_slashed_amount, seize_bonds};
 use crate::state::{Config, Settlement, SlashedBondsTracker};
+use crate::transfer_hook;
 use crate::RfqError;
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseIncomplete<'info> {
+    /// Anyone may close a settlement once its funding deadline has passed
     #[account(mut)]
-    pub maker: Signer<'info>,
+    pub keeper: Signer<'info>,
+
+    pub maker: SystemAccount<'info>,
 
     #[account(
+        mut,
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
     )]
@@ -29,49 +36,88 @@ pub struct CloseIncomplete<'info> {
 
     #[account(
         mut,
-        close = maker,
-        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref()],
+        close = settlement_rent_payer,
+        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref(), settlement.quote.as_ref()],
         bump = settlement.bump,
         has_one = rfq,
+        constraint = !settlement.is_complete() @ RfqError::InvalidRfqState,
     )]
     pub settlement: Box<Account<'info, Settlement>>,
 
-    #[account(address = settlement.base_mint)]
-    pub base_mint: Box<Account<'info, Mint>>,
+    /// Paid the settlement rent at selection (the maker, or the finalize_auction cranker)
+    #[account(mut, address = settlement.rent_payer @ RfqError::InvalidRentPayer)]
+    pub settlement_rent_payer: SystemAccount<'info>,
+
+    #[account(
+        address = settlement.base_mint,
+        mint::token_program = base_token_program,
+    )]
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
         associated_token::mint = base_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = base_token_program,
         address = settlement.vault_base_ata,
     )]
-    pub vault_base_ata: Box<Account<'info, TokenAccount>>,
+    pub vault_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         token::mint = base_mint,
         token::authority = maker,
+        token::token_program = base_token_program,
         address = settlement.maker_base_account,
     )]
-    pub maker_base_account: Box<Account<'info, TokenAccount>>,
+    pub maker_base_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Buy RFQs only: the maker's quote is refunded from the quote vault
+    #[account(
+        address = settlement.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = rfq,
+        token::token_program = quote_token_program,
+        address = settlement.vault_quote_ata,
+    )]
+    pub vault_quote_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    #[account(address = rfq.usdc_mint)]
-    pub usdc_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = maker,
+        address = settlement.maker_quote_account,
+    )]
+    pub maker_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        address = rfq.usdc_mint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
         associated_token::mint = usdc_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = token_program,
     )]
-    pub bonds_escrow: Box<Account<'info, TokenAccount>>,
+    pub bonds_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         token::mint = usdc_mint,
         token::authority = maker,
+        token::token_program = token_program,
         constraint = rfq.maker_payment_account == maker_payment_account.key() @ RfqError::UnauthorizedMakerPaymentAccount,
     )]
-    pub maker_payment_account: Box<Account<'info, TokenAccount>>,
+    pub maker_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -81,11 +127,20 @@ pub struct CloseIncomplete<'info> {
 
     #[account(
         init_if_needed,
-        payer = maker,
+        payer = keeper,
         associated_token::mint = usdc_mint,
         associated_token::authority = treasury_wallet,
+        associated_token::token_program = token_program,
     )]
-    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the keeper bounty out of the slashed bonds, when given
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+        token::token_program = token_program,
+    )]
+    pub keeper_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     #[account(
         mut,
@@ -97,78 +152,175 @@ pub struct CloseIncomplete<'info> {
     pub slashed_bonds_tracker: Box<Account<'info, SlashedBondsTracker>>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn close_incomplete_handler(ctx: Context<CloseIncomplete>) -> Result<()> {
+pub fn close_incomplete_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseIncomplete<'info>>,
+) -> Result<()> {
     let rfq = &mut ctx.accounts.rfq;
+    let settlement = &ctx.accounts.settlement;
     let slashed_bonds_tracker = &mut ctx.accounts.slashed_bonds_tracker;
     let now = Clock::get()?.unix_timestamp;
 
-    let deadline = rfq.funding_deadline().ok_or(RfqError::InvalidRfqState)?;
-    require!(now > deadline, RfqError::TooEarly);
+    // each winner has its own funding deadline
+    require!(now > settlement.funding_deadline, RfqError::TooEarly);
 
-    // Refund maker's bond
     let seeds_rfq: &[&[u8]] = &[
         Rfq::SEED_PREFIX,
         rfq.maker.as_ref(),
         rfq.uuid.as_ref(),
         &[rfq.bump],
     ];
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.bonds_escrow.to_account_info(),
-                to: ctx.accounts.maker_payment_account.to_account_info(),
-                authority: rfq.to_account_info(),
-            },
-            &[seeds_rfq],
-        ),
-        rfq.bond_amount,
-    )?;
-    // refund maker's base
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_base_ata.to_account_info(),
-                to: ctx.accounts.maker_base_account.to_account_info(),
-                authority: rfq.to_account_info(),
-            },
-            &[seeds_rfq],
-        ),
-        rfq.base_amount,
-    )?;
-
-    if !slashed_bonds_tracker.is_resolved() {
-        // Seize unrevealed bonds plus the selected taker bond
-        let seized_amount = compute_slashed_amount(rfq, true)?;
 
-        if seized_amount > 0 {
-            token::transfer(
+    // Refund maker's bond once no winner is left pending
+    let is_last_pending = rfq.pending_settlements()? == 1;
+    if is_last_pending {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.bonds_escrow.to_account_info(),
+                    mint: ctx.accounts.usdc_mint.to_account_info(),
+                    to: ctx.accounts.maker_payment_account.to_account_info(),
+                    authority: rfq.to_account_info(),
+                },
+                &[seeds_rfq],
+            ),
+            rfq.bond_amount,
+            ctx.accounts.usdc_mint.decimals,
+        )?;
+    }
+    // refund maker's deposit for this winner (whatever the vault holds for it, transfer fee included)
+    match settlement.side {
+        RfqSide::Sell => {
+            transfer_hook::transfer_checked(
                 CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.bonds_escrow.to_account_info(),
-                        to: ctx.accounts.treasury_ata.to_account_info(),
+                    ctx.accounts.base_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault_base_ata.to_account_info(),
+                        mint: ctx.accounts.base_mint.to_account_info(),
+                        to: ctx.accounts.maker_base_account.to_account_info(),
                         authority: rfq.to_account_info(),
                     },
                     &[seeds_rfq],
-                ),
-                seized_amount,
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                settlement.vault_base_amount,
+                ctx.accounts.base_mint.decimals,
+            )?;
+        }
+        RfqSide::Buy => {
+            let (
+                Some(quote_mint),
+                Some(vault_quote_ata),
+                Some(maker_quote_account),
+                Some(quote_token_program),
+            ) = (
+                &ctx.accounts.quote_mint,
+                &ctx.accounts.vault_quote_ata,
+                &ctx.accounts.maker_quote_account,
+                &ctx.accounts.quote_token_program,
+            )
+            else {
+                return err!(RfqError::MissingQuoteVaultAccount);
+            };
+            transfer_hook::transfer_checked(
+                CpiContext::new_with_signer(
+                    quote_token_program.to_account_info(),
+                    TransferChecked {
+                        from: vault_quote_ata.to_account_info(),
+                        mint: quote_mint.to_account_info(),
+                        to: maker_quote_account.to_account_info(),
+                        authority: rfq.to_account_info(),
+                    },
+                    &[seeds_rfq],
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                settlement.vault_quote_amount,
+                quote_mint.decimals,
             )?;
         }
-
-        // update slashed bonds tracker
-        slashed_bonds_tracker.amount = Some(seized_amount);
-        slashed_bonds_tracker.seized_at = Some(now);
     }
 
+    // Seize the no-show winner's bond, plus unrevealed bonds if not already seized
+    let seized_amount = if slashed_bonds_tracker.is_resolved() {
+        settlement.bond_amount
+    } else {
+        compute_slashed_amount(rfq, false)?
+            .checked_add(settlement.bond_amount)
+            .ok_or(RfqError::ArithmeticOverflow)?
+    };
+    let keeper_bounty = seize_bonds(
+        rfq,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.usdc_mint,
+        &ctx.accounts.bonds_escrow,
+        &ctx.accounts.treasury_ata,
+        ctx.accounts.keeper_payment_account.as_deref(),
+        seized_amount,
+        seeds_rfq,
+    )?;
+
+    // update slashed bonds tracker
+    let already_seized = slashed_bonds_tracker.amount.unwrap_or(0);
+    slashed_bonds_tracker.amount = Some(
+        already_seized
+            .checked_add(seized_amount)
+            .ok_or(RfqError::ArithmeticOverflow)?,
+    );
+    slashed_bonds_tracker.keeper_bounty = slashed_bonds_tracker
+        .keeper_bounty
+        .checked_add(keeper_bounty)
+        .ok_or(RfqError::ArithmeticOverflow)?;
+    slashed_bonds_tracker.seized_at = Some(now);
+
     // update rfq
-    rfq.state = RfqState::Incomplete;
-    rfq.settlement = None;
-    rfq.completed_at = Some(now);
+    rfq.incomplete_count = rfq
+        .incomplete_count
+        .checked_add(1)
+        .ok_or(RfqError::ArithmeticOverflow)?;
+    if rfq.settlement == Some(settlement.key()) {
+        rfq.settlement = None;
+    }
+    if is_last_pending {
+        // partially filled RFQs still count as settled
+        rfq.state = if rfq.settled_count > 0 {
+            RfqState::Settled
+        } else {
+            RfqState::Incomplete
+        };
+        rfq.completed_at = Some(now);
+        ctx.accounts.config.record_rfq_closed(rfq.config_generation)?;
+    }
+
+    if seized_amount > 0 {
+        emit_cpi!(BondsSlashed {
+            rfq: rfq.key(),
+            settlement: Some(settlement.key()),
+            treasury_wallet: rfq.treasury_wallet,
+            amount: seized_amount,
+            keeper_bounty,
+            seized_at: now,
+        });
+    }
+    if is_last_pending {
+        emit_cpi!(BondsRefunded {
+            rfq: rfq.key(),
+            quote: None,
+            owner: rfq.maker,
+            amount: rfq.bond_amount,
+            refunded_at: now,
+        });
+        emit_cpi!(RfqClosed {
+            rfq: rfq.key(),
+            maker: rfq.maker,
+            state: rfq.state.clone(),
+            closed_at: now,
+        });
+    }
     Ok(())
 }
@@ -0,0 +1,364 @@
+use crate::events::RfqReclaimed;
+use crate::state::rfq::{Rfq, RfqState};
+use crate::state::{
+    Config, FacilitatorRewardTracker, FeesTracker, Settlement, SlashedBondsTracker,
+};
+use crate::transfer_fee::harvest_withheld_fees;
+use crate::transfer_hook;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+/// Closes a finished RFQ and everything it owns, returning each rent to whoever paid it:
+/// the RFQ, bonds escrow and slashed-bonds tracker to the maker, the vaults and
+/// settlements to whoever created them at selection, fees trackers to their taker,
+/// reward trackers to their facilitator.
+/// Anyone may call it once every quote is closed, the slashed bonds are resolved and
+/// every facilitator reward is withdrawn. Nothing is owed from the escrows by then, so
+/// whatever they still hold (dust or unsolicited transfers) is swept to the maker's
+/// account for that mint, which must then be passed.
+///
+/// remaining_accounts: one `[settlement, fees_tracker, settlement rent payer, taker]`
+/// group per completed settlement (writable), followed by
+/// `[facilitator_reward_tracker, facilitator]` when its reward was withdrawn;
+/// close_incomplete already closed the others. Transfer-hook accounts for the sweeps
+/// go after the groups.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseRfq<'info> {
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        close = maker,
+        seeds = [Rfq::SEED_PREFIX, maker.key().as_ref(), rfq.uuid.as_ref()],
+        bump = rfq.bump,
+        has_one = maker,
+        has_one = config,
+        has_one = bonds_escrow,
+        constraint = matches!(rfq.state, RfqState::Settled | RfqState::Expired | RfqState::Ignored | RfqState::Incomplete) @ RfqError::InvalidRfqState,)]
+    pub rfq: Box<Account<'info, Rfq>>,
+
+    #[account(
+        address = rfq.usdc_mint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Required if the bonds escrow is not empty
+    #[account(
+        mut,
+        address = rfq.maker_payment_account @ RfqError::UnauthorizedMakerPaymentAccount,
+    )]
+    pub maker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = rfq,
+        associated_token::token_program = token_program,
+    )]
+    pub bonds_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        close = maker,
+        seeds = [SlashedBondsTracker::SEED_PREFIX, rfq.key().as_ref()],
+        bump = slashed_bonds_tracker.bump,
+    )]
+    pub slashed_bonds_tracker: Box<Account<'info, SlashedBondsTracker>>,
+
+    /// Required once the base vault was created (rfq.base_vault_payer is set). A vault created
+    /// by someone else has no recorded payer; pass it anyway to close it to the maker.
+    #[account(
+        mut,
+        address = rfq.base_mint @ RfqError::InvalidBaseMint,
+        mint::token_program = base_token_program,
+    )]
+    pub base_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = rfq,
+        associated_token::token_program = base_token_program,
+    )]
+    pub vault_base_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub base_vault_payer: Option<SystemAccount<'info>>,
+
+    pub base_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Required if the base vault is not empty
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = base_token_program,
+    )]
+    pub maker_base_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Required once the quote vault (fee escrow) was created (rfq.quote_vault_payer is set),
+    /// optional as for the base vault otherwise
+    #[account(
+        mut,
+        address = rfq.quote_mint @ RfqError::InvalidQuoteMint,
+        mint::token_program = quote_token_program,
+    )]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = rfq,
+        associated_token::token_program = quote_token_program,
+    )]
+    pub vault_quote_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub quote_vault_payer: Option<SystemAccount<'info>>,
+
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Required if the quote vault is not empty
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = quote_token_program,
+    )]
+    pub maker_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn close_rfq_handler<'info>(ctx: Context<'_, '_, 'info, 'info, CloseRfq<'info>>) -> Result<()> {
+    let rfq = &ctx.accounts.rfq;
+
+    // no bond can be outstanding: every quote was refunded or slashed, then closed
+    require!(
+        rfq.closed_quote_count == rfq.committed_count,
+        RfqError::OpenQuotesRemaining
+    );
+    require!(
+        ctx.accounts.slashed_bonds_tracker.is_resolved(),
+        RfqError::InvalidRfqState
+    );
+
+    // one group per completed settlement; a settlement closed twice fails to deserialize
+    let mut accounts = ctx.remaining_accounts;
+    for _ in 0..rfq.settled_count {
+        let [settlement_ai, fees_tracker_ai, rent_payer_ai, taker_ai, rest @ ..] = accounts else {
+            return err!(RfqError::InvalidSettlementAccounts);
+        };
+        accounts = rest;
+        let settlement: Account<'info, Settlement> = Account::try_from(settlement_ai)?;
+        require_keys_eq!(settlement.rfq, rfq.key(), RfqError::InvalidRfq);
+        require!(settlement.is_complete(), RfqError::InvalidSettlementAccounts);
+        require_keys_eq!(rent_payer_ai.key(), settlement.rent_payer, RfqError::InvalidRentPayer);
+        require_keys_eq!(taker_ai.key(), settlement.taker, RfqError::InvalidTaker);
+
+        // the fee escrow owes nothing once every facilitator reward was withdrawn
+        require!(
+            settlement.facilitator_reward == 0 || settlement.reward_withdrawn,
+            RfqError::RewardNotWithdrawn
+        );
+
+        // the fees tracker was paid for by the taker at completion
+        let (fees_tracker_pda, _) = Pubkey::find_program_address(
+            &[FeesTracker::SEED_PREFIX, rfq.key().as_ref(), settlement.quote.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            fees_tracker_ai.key(),
+            fees_tracker_pda,
+            RfqError::InvalidSettlementAccounts
+        );
+        let fees_tracker: Account<'info, FeesTracker> = Account::try_from(fees_tracker_ai)?;
+        fees_tracker.close(taker_ai.clone())?;
+
+        // the reward tracker was paid for by the facilitator at withdrawal
+        if settlement.reward_withdrawn {
+            let [reward_tracker_ai, facilitator_ai, rest @ ..] = accounts else {
+                return err!(RfqError::InvalidSettlementAccounts);
+            };
+            accounts = rest;
+            let facilitator = settlement
+                .facilitator
+                .ok_or(RfqError::InvalidSettlementAccounts)?;
+            require_keys_eq!(facilitator_ai.key(), facilitator, RfqError::Unauthorized);
+            let (reward_tracker_pda, _) = Pubkey::find_program_address(
+                &[
+                    FacilitatorRewardTracker::SEED_PREFIX,
+                    rfq.key().as_ref(),
+                    settlement.quote.as_ref(),
+                    facilitator.as_ref(),
+                ],
+                &crate::ID,
+            );
+            require_keys_eq!(
+                reward_tracker_ai.key(),
+                reward_tracker_pda,
+                RfqError::InvalidSettlementAccounts
+            );
+            let reward_tracker: Account<'info, FacilitatorRewardTracker> =
+                Account::try_from(reward_tracker_ai)?;
+            reward_tracker.close(facilitator_ai.clone())?;
+        }
+        settlement.close(rent_payer_ai.clone())?;
+    }
+    // what is left are the transfer-hook accounts for the sweeps
+    let hook_accounts = accounts;
+
+    let seeds_rfq: &[&[u8]] = &[
+        Rfq::SEED_PREFIX,
+        rfq.maker.as_ref(),
+        rfq.uuid.as_ref(),
+        &[rfq.bump],
+    ];
+
+    close_vault(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.usdc_mint,
+        &ctx.accounts.bonds_escrow,
+        ctx.accounts.maker_payment_account.as_deref(),
+        RfqError::MissingMakerPaymentAccount,
+        &ctx.accounts.maker.to_account_info(),
+        &rfq.to_account_info(),
+        seeds_rfq,
+        hook_accounts,
+    )?;
+
+    // with the bonds, settlements and rewards accounted for above, the vaults owe nothing
+    if rfq.base_vault_payer.is_some() || ctx.accounts.vault_base_ata.is_some() {
+        let (Some(base_mint), Some(vault_base_ata), Some(base_token_program)) = (
+            &ctx.accounts.base_mint,
+            &ctx.accounts.vault_base_ata,
+            &ctx.accounts.base_token_program,
+        ) else {
+            return err!(RfqError::MissingBaseVaultAccount);
+        };
+        let destination = match rfq.base_vault_payer {
+            Some(payer) => {
+                let base_vault_payer = ctx
+                    .accounts
+                    .base_vault_payer
+                    .as_ref()
+                    .ok_or(RfqError::MissingBaseVaultAccount)?;
+                require_keys_eq!(base_vault_payer.key(), payer, RfqError::InvalidRentPayer);
+                base_vault_payer.to_account_info()
+            }
+            // created by someone else before the program needed it: whoever paid is unknown
+            None => ctx.accounts.maker.to_account_info(),
+        };
+        close_vault(
+            &base_token_program.to_account_info(),
+            base_mint,
+            vault_base_ata,
+            ctx.accounts.maker_base_account.as_deref(),
+            RfqError::MissingMakerTokenAccount,
+            &destination,
+            &rfq.to_account_info(),
+            seeds_rfq,
+            hook_accounts,
+        )?;
+    }
+    if rfq.quote_vault_payer.is_some() || ctx.accounts.vault_quote_ata.is_some() {
+        let (Some(quote_mint), Some(vault_quote_ata), Some(quote_token_program)) = (
+            &ctx.accounts.quote_mint,
+            &ctx.accounts.vault_quote_ata,
+            &ctx.accounts.quote_token_program,
+        ) else {
+            return err!(RfqError::MissingQuoteVaultAccount);
+        };
+        let destination = match rfq.quote_vault_payer {
+            Some(payer) => {
+                let quote_vault_payer = ctx
+                    .accounts
+                    .quote_vault_payer
+                    .as_ref()
+                    .ok_or(RfqError::MissingQuoteVaultAccount)?;
+                require_keys_eq!(quote_vault_payer.key(), payer, RfqError::InvalidRentPayer);
+                quote_vault_payer.to_account_info()
+            }
+            // created by someone else before the program needed it: whoever paid is unknown
+            None => ctx.accounts.maker.to_account_info(),
+        };
+        close_vault(
+            &quote_token_program.to_account_info(),
+            quote_mint,
+            vault_quote_ata,
+            ctx.accounts.maker_quote_account.as_deref(),
+            RfqError::MissingMakerTokenAccount,
+            &destination,
+            &rfq.to_account_info(),
+            seeds_rfq,
+            hook_accounts,
+        )?;
+    }
+
+    emit_cpi!(RfqReclaimed {
+        rfq: rfq.key(),
+        maker: rfq.maker,
+        state: rfq.state.clone(),
+        settlements_closed: rfq.settled_count,
+        reclaimed_at: Clock::get()?.unix_timestamp,
+    });
+
+    // the RFQ and slashed-bonds tracker are closed to the maker on exit
+    Ok(())
+}
+
+// Closes an RFQ-owned token account, harvesting withheld transfer fees first.
+// Any balance left is owed to no one and is swept to `sweep_to` (the maker's account),
+// so a stray transfer cannot keep the vault open.
+#[allow(clippy::too_many_arguments)]
+fn close_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    sweep_to: Option<&InterfaceAccount<'info, TokenAccount>>,
+    missing_sweep_to: RfqError,
+    destination: &AccountInfo<'info>,
+    rfq: &AccountInfo<'info>,
+    seeds_rfq: &[&[u8]],
+    hook_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    if vault.amount > 0 {
+        transfer_hook::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TransferChecked {
+                    from: vault.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: sweep_to.ok_or(missing_sweep_to)?.to_account_info(),
+                    authority: rfq.clone(),
+                },
+                &[seeds_rfq],
+            )
+            .with_remaining_accounts(hook_accounts.to_vec()),
+            vault.amount,
+            mint.decimals,
+        )?;
+    }
+    harvest_withheld_fees(token_program, &mint.to_account_info(), &vault.to_account_info())?;
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.clone(),
+        CloseAccount {
+            account: vault.to_account_info(),
+            destination: destination.clone(),
+            authority: rfq.clone(),
+        },
+        &[seeds_rfq],
+    ))
+}
@@ -1,14 +1,17 @@
+use crate::events::{RfqAllowlistUpdated, RfqInitialized};
 use crate::state::{
-    config::Config,
-    rfq::{Rfq, RfqState},
+    config::{Config, TransferFeePolicy},
+    rfq::{PricingMode, Rfq, RfqSide, RfqState, SizeMode},
 };
+use crate::transfer_fee::check_transfer_fee_policy;
 use crate::RfqError;
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(uuid: [u8; 16])]
 pub struct InitRfq<'info> {
@@ -18,12 +21,20 @@ pub struct InitRfq<'info> {
     #[account(
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_INIT_RFQ) @ RfqError::ProtocolPaused,
     )]
     pub config: Account<'info, Config>,
 
     // Must be an account field (not just a Pubkey) for `associated_token::mint`
-    #[account(address = config.usdc_mint)]
-    pub usdc_mint: Account<'info, Mint>,
+    #[account(
+        address = config.usdc_mint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+
+    /// Base/quote mints, checked against the transfer-fee policy up front
+    pub base_mint: InterfaceAccount<'info, Mint>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         init,
@@ -40,22 +51,25 @@ pub struct InitRfq<'info> {
         payer = maker,
         associated_token::mint = usdc_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = token_program,
     )]
-    pub bonds_escrow: Account<'info, TokenAccount>,
+    pub bonds_escrow: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         token::mint = usdc_mint,
         token::authority = maker,
+        token::token_program = token_program,
         constraint =!maker_payment_account.is_frozen() @ RfqError::MakerPaymentAccountClosed,
     )]
-    pub maker_payment_account: Account<'info, TokenAccount>,
+    pub maker_payment_account: InterfaceAccount<'info, TokenAccount>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn init_rfq_handler(
     ctx: Context<InitRfq>,
     uuid: [u8; 16],
@@ -70,6 +84,16 @@ pub fn init_rfq_handler(
     selection_ttl_secs: u32,
     fund_ttl_secs: u32,
     facilitator: Option<Pubkey>,
+    allow_partial_fills: bool,
+    auto_select_best: bool,
+    require_best_quote: bool,
+    pricing_mode: PricingMode,
+    side: RfqSide,
+    max_quote_amount: u64,
+    size_mode: SizeMode,
+    quote_size: u64,
+    firm: bool,
+    taker_allowlist_root: Option<[u8; 32]>,
 ) -> Result<()> {
     let bump = ctx.bumps.rfq;
     let config = &ctx.accounts.config;
@@ -77,7 +101,17 @@ pub fn init_rfq_handler(
     require!(bond_amount > 0, RfqError::InvalidBondAmount);
     require!(taker_fee_bps <= 10_000, RfqError::InvalidFeeAmount);
     require!(base_amount > 0, RfqError::InvalidBaseAmount);
-    require!(min_quote_amount > 0, RfqError::InvalidMinQuoteAmount);
+    // the quote floor only bounds sell RFQs
+    if side == RfqSide::Sell {
+        require!(min_quote_amount > 0, RfqError::InvalidMinQuoteAmount);
+    }
+    // quote-size RFQs spend a fixed quote amount to buy base
+    if size_mode == SizeMode::Quote {
+        require!(side == RfqSide::Buy, RfqError::InvalidSizeMode);
+        require!(quote_size > 0, RfqError::InvalidQuoteSize);
+    } else if side == RfqSide::Buy {
+        require!(max_quote_amount > 0, RfqError::InvalidMaxQuoteAmount);
+    }
 
     // Lifetime invariants
     require!(commit_ttl_secs > 0, RfqError::InvalidCommitTTL);
@@ -85,12 +119,29 @@ pub fn init_rfq_handler(
     require!(selection_ttl_secs > 0, RfqError::InvalidSelectionTTL);
     require!(fund_ttl_secs > 0, RfqError::InvalidFundingTTL);
 
+    // Auto-best, must-pick-best, second-price and buy RFQs are single-winner
+    require!(
+        !(allow_partial_fills
+            && (auto_select_best
+                || require_best_quote
+                || pricing_mode == PricingMode::SecondPrice
+                || side == RfqSide::Buy)),
+        RfqError::InvalidSelectionMode
+    );
+    // firm RFQs pre-fund the whole base_amount for one winner
+    require!(
+        !(firm && (side == RfqSide::Buy || allow_partial_fills)),
+        RfqError::InvalidFirmMode
+    );
+
     // --- Initialize RFQ -----------------------------------------------------
     let rfq = &mut ctx.accounts.rfq;
     rfq.config = config.key();
     rfq.maker = ctx.accounts.maker.key();
     rfq.uuid = uuid;
     rfq.state = RfqState::Draft;
+    rfq.side = side;
+    rfq.size_mode = size_mode;
 
     // assets & economics
     rfq.base_mint = base_mint;
@@ -98,11 +149,33 @@ pub fn init_rfq_handler(
     rfq.usdc_mint = config.usdc_mint;
     rfq.treasury_wallet = config.treasury_wallet;
     rfq.liquidity_guard = config.liquidity_guard;
+    rfq.liquidity_guard_key_type = config.liquidity_guard_key_type;
     rfq.bond_amount = bond_amount;
     rfq.base_amount = base_amount;
     rfq.min_quote_amount = min_quote_amount;
+    rfq.max_quote_amount = max_quote_amount;
+    rfq.quote_size = quote_size;
     rfq.taker_fee_bps = taker_fee_bps;
     rfq.facilitator_fee_bps = config.facilitator_fee_bps;
+    rfq.keeper_bounty_bps = config.keeper_bounty_bps;
+
+    // transfer fees
+    rfq.transfer_fee_policy = config.transfer_fee_policy;
+    require_keys_eq!(ctx.accounts.base_mint.key(), base_mint, RfqError::InvalidBaseMint);
+    require_keys_eq!(ctx.accounts.quote_mint.key(), quote_mint, RfqError::InvalidQuoteMint);
+    rfq.base_transfer_fee = check_transfer_fee_policy(
+        &ctx.accounts.base_mint.to_account_info(),
+        rfq.transfer_fee_policy,
+    )?;
+    rfq.quote_transfer_fee = check_transfer_fee_policy(
+        &ctx.accounts.quote_mint.to_account_info(),
+        rfq.transfer_fee_policy,
+    )?;
+    // bonds are moved without gross-up, so the USDC mint may never charge a fee
+    check_transfer_fee_policy(
+        &ctx.accounts.usdc_mint.to_account_info(),
+        TransferFeePolicy::Reject,
+    )?;
 
     // ttls
     rfq.commit_ttl_secs = commit_ttl_secs;
@@ -121,12 +194,60 @@ pub fn init_rfq_handler(
 
     rfq.committed_count = 0;
     rfq.revealed_count = 0;
+    rfq.closed_quote_count = 0;
+    rfq.best_quote = None;
+    rfq.best_quote_amount = 0;
+    rfq.second_best_quote_amount = 0;
+    rfq.allow_partial_fills = allow_partial_fills;
+    rfq.auto_select_best = auto_select_best;
+    rfq.require_best_quote = require_best_quote;
+    rfq.pricing_mode = pricing_mode;
+    rfq.firm = firm;
+    rfq.prefunded_base_amount = 0;
     rfq.selected_quote = None;
     rfq.settlement = None;
+    rfq.selected_count = 0;
+    rfq.settled_count = 0;
+    rfq.incomplete_count = 0;
 
     rfq.bonds_escrow = ctx.accounts.bonds_escrow.key();
     rfq.maker_payment_account = ctx.accounts.maker_payment_account.key();
+    rfq.base_vault_payer = None;
+    rfq.quote_vault_payer = None;
     rfq.facilitator = facilitator;
+    rfq.taker_allowlist_root = taker_allowlist_root;
+    rfq.paused = false;
+    rfq.paused_since = None;
+    rfq.selection_window_paused = false;
+    rfq.config_generation = 0; // stamped by open_rfq
+    rfq.open_nonce = 0; // stamped by open_rfq
+
+    emit_cpi!(RfqInitialized {
+        rfq: rfq.key(),
+        maker: rfq.maker,
+        uuid,
+        side,
+        size_mode,
+        pricing_mode,
+        base_mint,
+        quote_mint,
+        bond_amount,
+        base_amount,
+        min_quote_amount,
+        max_quote_amount,
+        quote_size,
+        taker_fee_bps,
+        facilitator,
+        created_at: now,
+    });
+    if taker_allowlist_root.is_some() {
+        emit_cpi!(RfqAllowlistUpdated {
+            rfq: rfq.key(),
+            maker: rfq.maker,
+            taker_allowlist_root,
+            timestamp: now,
+        });
+    }
 
     Ok(())
 }
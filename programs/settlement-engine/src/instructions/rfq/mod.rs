@@ -4,11 +4,15 @@ pub mod close_incomplete;
 pub use close_incomplete::*;
 pub mod close_expired;
 pub use close_expired::*;
+pub mod close_rfq;
+pub use close_rfq::*;
 pub mod init_rfq;
 pub use init_rfq::*;
 pub mod open_rfq;
 pub use open_rfq::*;
 pub mod set_rfq_facilitator;
 pub use set_rfq_facilitator::*;
+pub mod set_rfq_pause;
+pub use set_rfq_pause::*;
 pub mod update_rfq;
 pub use update_rfq::*;
@@ -1,8 +1,17 @@
-use crate::state::rfq::{Rfq, RfqState};
-use crate::{state::Config, state::SlashedBondsTracker, RfqError};
+use crate::events::RfqOpened;
+use crate::instructions::select_quote::MakerVault;
+use crate::state::rfq::{Rfq, RfqSide, RfqState};
+use crate::transfer_fee::check_transfer_fee_policy;
+use crate::transfer_hook;
+use crate::vault::init_vault_if_needed;
+use crate::{state::Config, state::ConfigGeneration, state::SlashedBondsTracker, RfqError};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::associated_token::{
+    get_associated_token_address_with_program_id, AssociatedToken, Create,
+};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct OpenRfq<'info> {
     #[account(mut)]
@@ -14,33 +23,49 @@ pub struct OpenRfq<'info> {
         bump = rfq.bump,
         has_one = maker,
         has_one = config,
+        constraint = !rfq.paused @ RfqError::RfqPaused,
         constraint = matches!(rfq.state, RfqState::Draft) @ RfqError::InvalidRfqState,)]
     pub rfq: Box<Account<'info, Rfq>>,
 
     #[account(
+        mut,
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_OPEN_RFQ) @ RfqError::ProtocolPaused,
     )]
     pub config: Account<'info, Config>,
 
+    /// Hands out the RFQ's open nonce
+    #[account(
+        mut,
+        seeds = [ConfigGeneration::SEED_PREFIX],
+        bump = config_generation.bump,
+    )]
+    pub config_generation: Box<Account<'info, ConfigGeneration>>,
+
     // Must be an account field (not just a Pubkey) for `associated_token::mint`
-    #[account(address = rfq.usdc_mint)]
-    pub usdc_mint: Account<'info, Mint>,
+    #[account(
+        address = rfq.usdc_mint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
         associated_token::mint = usdc_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = token_program,
     )]
-    pub bonds_escrow: Account<'info, TokenAccount>,
+    pub bonds_escrow: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         token::mint = usdc_mint,
         token::authority = maker,
+        token::token_program = token_program,
         constraint = rfq.maker_payment_account == maker_payment_account.key() @ RfqError::UnauthorizedMakerPaymentAccount,
     )]
-    pub maker_payment_account: Account<'info, TokenAccount>,
+    pub maker_payment_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init,
@@ -51,11 +76,33 @@ pub struct OpenRfq<'info> {
     )]
     pub slashed_bonds_tracker: Account<'info, SlashedBondsTracker>,
 
+    /// Firm RFQs only: base_amount is moved into the vault at open
+    #[account(
+        address = rfq.base_mint @ RfqError::InvalidBaseMint,
+        mint::token_program = base_token_program,
+    )]
+    pub base_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = base_mint,
+        token::authority = maker,
+        token::token_program = base_token_program,
+    )]
+    pub maker_base_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: the RFQ's base ATA, checked and created by the handler if missing
+    #[account(mut)]
+    pub vault_base_ata: Option<UncheckedAccount<'info>>,
+
+    pub base_token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn open_rfq_handler(ctx: Context<OpenRfq>) -> Result<()> {
+pub fn open_rfq_handler<'info>(ctx: Context<'_, '_, 'info, 'info, OpenRfq<'info>>) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
     let rfq = &mut ctx.accounts.rfq;
     let slashed_bonds_tracker = &mut ctx.accounts.slashed_bonds_tracker;
@@ -63,7 +110,10 @@ pub fn open_rfq_handler(ctx: Context<OpenRfq>) -> Result<()> {
     // last-moment sanity (already enforced on init/update, but double-check)
     require!(rfq.bond_amount > 0, RfqError::InvalidParams);
     require!(rfq.base_amount > 0, RfqError::InvalidParams);
-    require!(rfq.min_quote_amount > 0, RfqError::InvalidParams);
+    if rfq.side == RfqSide::Sell {
+        require!(rfq.min_quote_amount > 0, RfqError::InvalidParams);
+    }
+    require!(rfq.price_limit() > 0, RfqError::InvalidParams);
     require!(rfq.taker_fee_bps <= 10_000, RfqError::InvalidParams);
     require!(rfq.commit_ttl_secs > 0, RfqError::InvalidParams);
     require!(rfq.reveal_ttl_secs > 0, RfqError::InvalidParams);
@@ -71,25 +121,154 @@ pub fn open_rfq_handler(ctx: Context<OpenRfq>) -> Result<()> {
     require!(rfq.fund_ttl_secs > 0, RfqError::InvalidParams);
 
     // Transfer maker bond USDC into RFQ's vault
-    let cpi_accounts = Transfer {
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.maker_payment_account.to_account_info(),
+        mint: ctx.accounts.usdc_mint.to_account_info(),
         to: ctx.accounts.bonds_escrow.to_account_info(),
         authority: ctx.accounts.maker.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, rfq.bond_amount)?;
+    token_interface::transfer_checked(
+        cpi_ctx,
+        rfq.bond_amount,
+        ctx.accounts.usdc_mint.decimals,
+    )?;
+
+    // Firm RFQs: move the maker's base into the vault now, so takers bond against real inventory
+    if rfq.firm {
+        let (
+            Some(base_mint),
+            Some(maker_base_account),
+            Some(vault_base_ata),
+            Some(base_token_program),
+            Some(associated_token_program),
+        ) = (
+            &ctx.accounts.base_mint,
+            &ctx.accounts.maker_base_account,
+            &ctx.accounts.vault_base_ata,
+            &ctx.accounts.base_token_program,
+            &ctx.accounts.associated_token_program,
+        ) else {
+            return err!(RfqError::MissingBaseVaultAccount);
+        };
+        require_keys_eq!(
+            vault_base_ata.key(),
+            get_associated_token_address_with_program_id(
+                &rfq.key(),
+                &base_mint.key(),
+                &base_token_program.key(),
+            ),
+            RfqError::PdaMismatch
+        );
+        require!(
+            !maker_base_account.is_frozen(),
+            RfqError::MakerBaseAccountClosed
+        );
+
+        let base_mint_info = base_mint.to_account_info();
+        rfq.base_transfer_fee = check_transfer_fee_policy(&base_mint_info, rfq.transfer_fee_policy)?;
+        // no quote leg yet: the vault only depends on the base mint
+        let vault = MakerVault::compute(rfq, &base_mint_info, &base_mint_info, 0)?;
+
+        init_vault_if_needed(
+            &associated_token_program.to_account_info(),
+            Create {
+                payer: ctx.accounts.maker.to_account_info(),
+                associated_token: vault_base_ata.to_account_info(),
+                authority: rfq.to_account_info(),
+                mint: base_mint_info.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: base_token_program.to_account_info(),
+            },
+            &mut rfq.base_vault_payer,
+        )?;
+
+        transfer_hook::transfer_checked(
+            CpiContext::new(
+                base_token_program.to_account_info(),
+                TransferChecked {
+                    from: maker_base_account.to_account_info(),
+                    mint: base_mint_info,
+                    to: vault_base_ata.to_account_info(),
+                    authority: ctx.accounts.maker.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            vault.deposit_amount,
+            base_mint.decimals,
+        )?;
+        rfq.prefunded_base_amount = vault.vault_base_amount;
+    }
 
     //update RFQ
     rfq.opened_at = Some(now);
     rfq.state = RfqState::Open;
+    rfq.config_generation = ctx.accounts.config.record_rfq_opened()?;
+    rfq.open_nonce = ctx.accounts.config_generation.next_open_nonce()?;
     //init slashed bonds tracker
     slashed_bonds_tracker.rfq = rfq.key();
     slashed_bonds_tracker.usdc_mint = rfq.usdc_mint;
     slashed_bonds_tracker.treasury_wallet = rfq.treasury_wallet;
     slashed_bonds_tracker.amount = None;
+    slashed_bonds_tracker.keeper_bounty = 0;
     slashed_bonds_tracker.seized_at = None;
     slashed_bonds_tracker.bump = ctx.bumps.slashed_bonds_tracker;
 
+    emit_cpi!(RfqOpened {
+        rfq: rfq.key(),
+        maker: rfq.maker,
+        bonds_escrow: rfq.bonds_escrow,
+        bond_amount: rfq.bond_amount,
+        prefunded_base_amount: rfq.prefunded_base_amount,
+        opened_at: now,
+        commit_deadline: rfq.commit_deadline().ok_or(RfqError::InvalidRfqState)?,
+        reveal_deadline: rfq.reveal_deadline().ok_or(RfqError::InvalidRfqState)?,
+    });
+
+    Ok(())
+}
+
+/// Returns a firm RFQ's pre-funded base to the maker (expired or ignored RFQs).
+/// Accounts are optional on the callers and only required while the vault holds base.
+pub(crate) fn refund_prefunded_base<'info>(
+    rfq: &mut Account<'info, Rfq>,
+    base_mint: Option<&InterfaceAccount<'info, Mint>>,
+    vault_base_ata: Option<&InterfaceAccount<'info, TokenAccount>>,
+    maker_base_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    base_token_program: Option<&Interface<'info, TokenInterface>>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    if !rfq.holds_prefunded_base() {
+        return Ok(());
+    }
+    let (Some(base_mint), Some(vault_base_ata), Some(maker_base_account), Some(base_token_program)) =
+        (base_mint, vault_base_ata, maker_base_account, base_token_program)
+    else {
+        return err!(RfqError::MissingBaseVaultAccount);
+    };
+
+    let seeds_rfq: &[&[u8]] = &[
+        Rfq::SEED_PREFIX,
+        rfq.maker.as_ref(),
+        rfq.uuid.as_ref(),
+        &[rfq.bump],
+    ];
+    transfer_hook::transfer_checked(
+        CpiContext::new_with_signer(
+            base_token_program.to_account_info(),
+            TransferChecked {
+                from: vault_base_ata.to_account_info(),
+                mint: base_mint.to_account_info(),
+                to: maker_base_account.to_account_info(),
+                authority: rfq.to_account_info(),
+            },
+            &[seeds_rfq],
+        )
+        .with_remaining_accounts(remaining_accounts.to_vec()),
+        rfq.prefunded_base_amount,
+        base_mint.decimals,
+    )?;
+    rfq.prefunded_base_amount = 0;
     Ok(())
 }
@@ -1,7 +1,9 @@
+use crate::events::FacilitatorUpdated;
 use crate::state::rfq::{FacilitatorUpdate, Rfq, RfqState};
 use crate::RfqError;
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SetRfqFacilitator<'info> {
     #[account(mut)]
@@ -32,5 +34,11 @@ pub fn set_rfq_facilitator_handler(
         FacilitatorUpdate::Clear => rfq.facilitator = None,
         FacilitatorUpdate::Set(key) => rfq.facilitator = Some(key),
     }
+    emit_cpi!(FacilitatorUpdated {
+        rfq: rfq.key(),
+        quote: None,
+        facilitator: rfq.facilitator,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
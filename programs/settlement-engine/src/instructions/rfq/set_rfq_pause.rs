@@ -0,0 +1,44 @@
+use crate::events::RfqPauseUpdated;
+use crate::state::config::Config;
+use crate::state::rfq::Rfq;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Pauses or unpauses a single RFQ. Like the global pause, it only blocks
+/// open_rfq, commit_quote and selection; exit paths stay available, and a maker
+/// whose selection window saw the pause is not slashed for ignoring the RFQ.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRfqPause<'info> {
+    pub pauser: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        has_one = pauser @ RfqError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Rfq::SEED_PREFIX, rfq.maker.as_ref(), rfq.uuid.as_ref()],
+        bump = rfq.bump,
+        has_one = config,
+    )]
+    pub rfq: Account<'info, Rfq>,
+}
+
+pub fn set_rfq_pause_handler(ctx: Context<SetRfqPause>, paused: bool) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let rfq = &mut ctx.accounts.rfq;
+    rfq.set_paused(paused, now);
+
+    emit_cpi!(RfqPauseUpdated {
+        rfq: rfq.key(),
+        pauser: ctx.accounts.pauser.key(),
+        paused,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -1,7 +1,11 @@
-use crate::state::rfq::{FacilitatorUpdate, Rfq, RfqState};
+use crate::events::{RfqAllowlistUpdated, RfqUpdated};
+use crate::state::rfq::{AllowlistUpdate, FacilitatorUpdate, Rfq, RfqSide, RfqState, SizeMode};
+use crate::transfer_fee::check_transfer_fee_policy;
 use crate::RfqError;
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateRfq<'info> {
     #[account(mut)]
@@ -14,8 +18,14 @@ pub struct UpdateRfq<'info> {
         has_one = maker,
         constraint = matches!(rfq.state, RfqState::Draft) @ RfqError::InvalidRfqState,)]
     pub rfq: Account<'info, Rfq>,
+
+    /// Required with new_base_mint / new_quote_mint: the new mint is checked
+    /// against the transfer-fee policy, as in init_rfq
+    pub base_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub quote_mint: Option<InterfaceAccount<'info, Mint>>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_rfq_handler(
     ctx: Context<UpdateRfq>,
     // Option<>s so the maker can patch specific fields
@@ -30,14 +40,25 @@ pub fn update_rfq_handler(
     new_selection_ttl_secs: Option<u32>,
     new_fund_ttl_secs: Option<u32>,
     new_facilitator_update: Option<FacilitatorUpdate>,
+    new_allowlist_update: Option<AllowlistUpdate>,
+    new_max_quote_amount: Option<u64>,
+    new_quote_size: Option<u64>,
 ) -> Result<()> {
     let rfq = &mut ctx.accounts.rfq;
 
     if let Some(v) = new_base_mint {
+        let mint = ctx.accounts.base_mint.as_ref().ok_or(RfqError::InvalidBaseMint)?;
+        require_keys_eq!(mint.key(), v, RfqError::InvalidBaseMint);
         rfq.base_mint = v;
+        rfq.base_transfer_fee =
+            check_transfer_fee_policy(&mint.to_account_info(), rfq.transfer_fee_policy)?;
     }
     if let Some(v) = new_quote_mint {
+        let mint = ctx.accounts.quote_mint.as_ref().ok_or(RfqError::InvalidQuoteMint)?;
+        require_keys_eq!(mint.key(), v, RfqError::InvalidQuoteMint);
         rfq.quote_mint = v;
+        rfq.quote_transfer_fee =
+            check_transfer_fee_policy(&mint.to_account_info(), rfq.transfer_fee_policy)?;
     }
 
     if let Some(v) = new_bond_amount {
@@ -48,10 +69,24 @@ pub fn update_rfq_handler(
         require!(v > 0, RfqError::InvalidBaseAmount);
         rfq.base_amount = v;
     }
+    // side and size mode are fixed at init, so each bound is checked as init_rfq does
     if let Some(v) = new_min_quote_amount {
-        require!(v > 0, RfqError::InvalidMinQuoteAmount);
+        if rfq.side == RfqSide::Sell {
+            require!(v > 0, RfqError::InvalidMinQuoteAmount);
+        }
         rfq.min_quote_amount = v;
     }
+    if let Some(v) = new_max_quote_amount {
+        if rfq.side == RfqSide::Buy && rfq.size_mode == SizeMode::Base {
+            require!(v > 0, RfqError::InvalidMaxQuoteAmount);
+        }
+        rfq.max_quote_amount = v;
+    }
+    if let Some(v) = new_quote_size {
+        require!(rfq.size_mode == SizeMode::Quote, RfqError::InvalidSizeMode);
+        require!(v > 0, RfqError::InvalidQuoteSize);
+        rfq.quote_size = v;
+    }
     if let Some(v) = new_taker_fee_bps {
         require!(v <= 10_000, RfqError::InvalidFeeAmount);
         rfq.taker_fee_bps = v;
@@ -83,6 +118,36 @@ pub fn update_rfq_handler(
             }
         }
     }
+    if let Some(update) = new_allowlist_update.as_ref() {
+        rfq.taker_allowlist_root = match update {
+            AllowlistUpdate::Clear => None,
+            AllowlistUpdate::Set(root) => Some(*root),
+        };
+    }
+
+    emit_cpi!(RfqUpdated {
+        rfq: rfq.key(),
+        maker: rfq.maker,
+        base_mint: rfq.base_mint,
+        quote_mint: rfq.quote_mint,
+        bond_amount: rfq.bond_amount,
+        base_amount: rfq.base_amount,
+        min_quote_amount: rfq.min_quote_amount,
+        max_quote_amount: rfq.max_quote_amount,
+        quote_size: rfq.quote_size,
+        taker_fee_bps: rfq.taker_fee_bps,
+        facilitator: rfq.facilitator,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    if new_allowlist_update.is_some() {
+        emit_cpi!(RfqAllowlistUpdated {
+            rfq: rfq.key(),
+            maker: rfq.maker,
+            taker_allowlist_root: rfq.taker_allowlist_root,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
 
     Ok(())
 }
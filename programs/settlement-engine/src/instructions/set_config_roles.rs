@@ -0,0 +1,47 @@
+use crate::events::ConfigRolesUpdated;
+use crate::state::config::Config;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetConfigRoles<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        has_one = admin @ RfqError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn set_config_roles_handler(
+    ctx: Context<SetConfigRoles>,
+    new_fee_manager: Option<Pubkey>,
+    new_guard_manager: Option<Pubkey>,
+    new_pauser: Option<Pubkey>,
+) -> Result<()> {
+    let cfg = &mut ctx.accounts.config;
+
+    if let Some(v) = new_fee_manager {
+        cfg.fee_manager = v;
+    }
+    if let Some(v) = new_guard_manager {
+        cfg.guard_manager = v;
+    }
+    if let Some(v) = new_pauser {
+        cfg.pauser = v;
+    }
+
+    emit_cpi!(ConfigRolesUpdated {
+        config: cfg.key(),
+        fee_manager: cfg.fee_manager,
+        guard_manager: cfg.guard_manager,
+        pauser: cfg.pauser,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
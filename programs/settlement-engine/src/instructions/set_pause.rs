@@ -0,0 +1,98 @@
+use crate::events::PauseUpdated;
+use crate::state::config::{Config, ConfigGeneration};
+use crate::state::SelectionPause;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+
+/// Replaces the protocol-wide pause bitflags (0 unpauses everything).
+/// Setting or lifting PAUSE_SELECT_QUOTE opens or ends a SelectionPause record.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(mut)]
+    pub pauser: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        has_one = pauser @ RfqError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [ConfigGeneration::SEED_PREFIX],
+        bump = config_generation.bump,
+    )]
+    pub config_generation: Account<'info, ConfigGeneration>,
+
+    /// Required when PAUSE_SELECT_QUOTE is set: the next pause record
+    #[account(
+        init,
+        payer = pauser,
+        space = 8 + SelectionPause::INIT_SPACE,
+        seeds = [SelectionPause::SEED_PREFIX, &config_generation.select_pause_count.to_le_bytes()],
+        bump,
+    )]
+    pub new_selection_pause: Option<Account<'info, SelectionPause>>,
+
+    /// Required when PAUSE_SELECT_QUOTE is lifted: the pause record in force
+    #[account(
+        mut,
+        seeds = [SelectionPause::SEED_PREFIX, &config_generation.select_pause_count.saturating_sub(1).to_le_bytes()],
+        bump = open_selection_pause.bump,
+    )]
+    pub open_selection_pause: Option<Account<'info, SelectionPause>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_pause_handler(ctx: Context<SetPause>, paused: u8) -> Result<()> {
+    require!(paused & !Config::PAUSE_ALL == 0, RfqError::InvalidPauseFlags);
+
+    let now = Clock::get()?.unix_timestamp;
+    let cfg = &mut ctx.accounts.config;
+    let previous = cfg.paused;
+    let was_selection_paused = cfg.is_paused(Config::PAUSE_SELECT_QUOTE);
+    cfg.paused = paused;
+
+    // refund_quote_bonds does not slash makers whose selection window saw a pause
+    match (was_selection_paused, cfg.is_paused(Config::PAUSE_SELECT_QUOTE)) {
+        (false, true) => {
+            let generation = &mut ctx.accounts.config_generation;
+            let pause = ctx
+                .accounts
+                .new_selection_pause
+                .as_mut()
+                .ok_or(RfqError::InvalidSelectionPause)?;
+            pause.index = generation.select_pause_count;
+            pause.started_at = now;
+            pause.resumed_at = None;
+            pause.bump = ctx.bumps.new_selection_pause.ok_or(RfqError::InvalidSelectionPause)?;
+            generation.select_pause_count = generation
+                .select_pause_count
+                .checked_add(1)
+                .ok_or(RfqError::ArithmeticOverflow)?;
+        }
+        (true, false) => {
+            let pause = ctx
+                .accounts
+                .open_selection_pause
+                .as_mut()
+                .ok_or(RfqError::InvalidSelectionPause)?;
+            pause.resumed_at = Some(now);
+        }
+        _ => (),
+    }
+
+    emit_cpi!(PauseUpdated {
+        config: cfg.key(),
+        pauser: cfg.pauser,
+        previous,
+        paused,
+        timestamp: now,
+    });
+
+    Ok(())
+}
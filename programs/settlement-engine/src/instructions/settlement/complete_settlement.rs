@@ -1,19 +1,24 @@
+use crate::events::{BondsRefunded, BondsSlashed, RfqClosed, SettlementCompleted};
 use crate::rfq_errors::RfqError;
-use crate::state::rfq::{Rfq, RfqState};
+use crate::state::rfq::{Rfq, RfqSide, RfqState};
 use crate::slashing::compute_slashed_amount;
-use crate::state::{Config, FeesTracker, Quote, Settlement, SlashedBondsTracker};
+use crate::state::{CommitGuard, Config, FeesTracker, Quote, Settlement, SlashedBondsTracker};
+use crate::transfer_fee::{check_transfer_fee_unchanged, gross_up};
+use crate::transfer_hook;
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CompleteSettlement<'info> {
     #[account(mut)]
     pub taker: Signer<'info>,
 
     #[account(
+        mut,
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
     )]
@@ -32,22 +37,31 @@ pub struct CompleteSettlement<'info> {
     )]
     pub rfq: Box<Account<'info, Rfq>>,
 
-    //quote provided in remaining_accounts
+    //quote and its commit guard provided in remaining_accounts (both closed to the taker)
     #[account(
         mut,
-        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref()],
-        bump,
+        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref(), settlement.quote.as_ref()],
+        bump = settlement.bump,
     )]
     pub settlement: Box<Account<'info, Settlement>>,
 
-    #[account(address = rfq.usdc_mint)]
-    pub usdc_mint: Box<Account<'info, Mint>>,
+    #[account(
+        address = rfq.usdc_mint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(address = settlement.base_mint)]
-    pub base_mint: Box<Account<'info, Mint>>,
+    #[account(
+        address = settlement.base_mint,
+        mint::token_program = base_token_program,
+    )]
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(address = settlement.quote_mint)]
-    pub quote_mint: Box<Account<'info, Mint>>,
+    #[account(
+        address = settlement.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// USDC treasury ATA – receives slashed bonds only
     #[account(
@@ -55,8 +69,9 @@ pub struct CompleteSettlement<'info> {
         payer = taker,
         associated_token::mint = usdc_mint,
         associated_token::authority = treasury_wallet,
+        associated_token::token_program = token_program,
     )]
-    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// Quote-mint treasury ATA – receives the treasury's share of the taker fee
     #[account(
@@ -64,77 +79,102 @@ pub struct CompleteSettlement<'info> {
         payer = taker,
         associated_token::mint = quote_mint,
         associated_token::authority = treasury_wallet,
+        associated_token::token_program = quote_token_program,
     )]
-    pub treasury_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub treasury_quote_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// USDC bonds escrow (bonds only, no fees)
     #[account(
         mut,
         address = settlement.bonds_escrow,
+        token::mint = usdc_mint,
+        token::token_program = token_program,
     )]
-    pub bonds_escrow: Box<Account<'info, TokenAccount>>,
+    pub bonds_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Quote-mint fee escrow – holds facilitator share until withdraw_reward
+    /// Quote-mint fee escrow – holds facilitator share until withdraw_reward.
+    /// Same ATA as settlement.vault_quote_ata, which holds the maker's quote on Buy RFQs.
+    /// Created at selection, whose payer close_rfq refunds.
     #[account(
-        init_if_needed,
-        payer = taker,
+        mut,
         associated_token::mint = quote_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = quote_token_program,
     )]
-    pub fee_escrow: Box<Account<'info, TokenAccount>>,
+    pub fee_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         token::mint = usdc_mint,
         token::authority = taker,
+        token::token_program = token_program,
     )]
-    pub taker_payment_account: Box<Account<'info, TokenAccount>>,
+    pub taker_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         address = settlement.maker_payment_account,
+        token::mint = usdc_mint,
+        token::token_program = token_program,
     )]
-    pub maker_payment_account: Box<Account<'info, TokenAccount>>,
+    pub maker_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         address = settlement.vault_base_ata,
+        token::mint = base_mint,
+        token::token_program = base_token_program,
     )]
-    pub vault_base_ata: Box<Account<'info, TokenAccount>>,
+    pub vault_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = taker,
         associated_token::mint = base_mint,
         associated_token::authority = taker,
+        associated_token::token_program = base_token_program,
+    )]
+    pub taker_base_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Buy RFQs only: receives base_amount from the taker
+    #[account(
+        mut,
+        address = settlement.maker_base_account,
+        token::mint = base_mint,
+        token::token_program = base_token_program,
     )]
-    pub taker_base_account: Box<Account<'info, TokenAccount>>,
+    pub maker_base_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     #[account(
         mut,
         address = settlement.maker_quote_account,
+        token::mint = quote_mint,
+        token::token_program = quote_token_program,
     )]
-    pub maker_quote_account: Box<Account<'info, TokenAccount>>,
+    pub maker_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         token::mint = quote_mint,
         token::authority = taker,
+        token::token_program = quote_token_program,
     )]
-    pub taker_quote_account: Box<Account<'info, TokenAccount>>,
+    pub taker_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init,
         payer = taker,
         space = 8 + FeesTracker::INIT_SPACE,
-        seeds = [FeesTracker::SEED_PREFIX, rfq.key().as_ref()],
+        seeds = [FeesTracker::SEED_PREFIX, rfq.key().as_ref(), settlement.quote.as_ref()],
         bump,
     )]
     pub fees_tracker: Box<Account<'info, FeesTracker>>,
 
     // slashed_bonds_tracker provided in remaining_accounts
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
@@ -145,11 +185,10 @@ pub fn complete_settlement_handler<'info>(
     let settlement = &mut ctx.accounts.settlement;
     let fees_tracker = &mut ctx.accounts.fees_tracker;
 
-    let Some(funding_deadline) = rfq.funding_deadline() else {
-        return err!(RfqError::InvalidRfqState);
-    };
+    // each winner has its own funding deadline
     let now = Clock::get()?.unix_timestamp;
-    require!(now <= funding_deadline, RfqError::FundingTooLate);
+    require!(now <= settlement.funding_deadline, RfqError::FundingTooLate);
+    require!(!settlement.is_complete(), RfqError::InvalidRfqState);
     require!(
         matches!(rfq.state, RfqState::Selected),
         RfqError::InvalidRfqState
@@ -168,9 +207,9 @@ pub fn complete_settlement_handler<'info>(
         RfqError::InvalidTakerPaymentAccount
     );
 
-    // resolve quote and slashed_bonds_tracker from remaining_accounts (order-agnostic)
+    // resolve quote, commit guard and slashed_bonds_tracker from remaining_accounts (order-agnostic)
     require!(
-        ctx.remaining_accounts.len() >= 2,
+        ctx.remaining_accounts.len() >= 3,
         RfqError::MissingQuoteAccount
     );
 
@@ -202,43 +241,44 @@ pub fn complete_settlement_handler<'info>(
     require_keys_eq!(*quote_ai.owner, crate::ID, RfqError::InvalidOwner);
     require_keys_eq!(*slashed_ai.owner, crate::ID, RfqError::InvalidOwner);
 
-    let mut quote: Account<'info, Quote> = Account::try_from(quote_ai)?;
+    let quote: Account<'info, Quote> = Account::try_from(quote_ai)?;
     require_eq!(quote_bump, quote.bump, RfqError::BumpMismatch);
     require_eq!(quote.key(), settlement.quote, RfqError::InvalidQuote);
     require!(quote.selected, RfqError::InvalidQuoteState);
 
-    // Refund maker's bond (USDC)
+    let (commit_guard_expected_pda, _) = Pubkey::find_program_address(
+        &[CommitGuard::SEED_PREFIX, quote.commit_hash.as_ref()],
+        &crate::ID,
+    );
+    let commit_guard_ai = ctx
+        .remaining_accounts
+        .iter()
+        .find(|ai| ai.key() == commit_guard_expected_pda)
+        .ok_or(RfqError::MissingCommitGuardAccount)?;
+    let commit_guard: Account<'info, CommitGuard> = Account::try_from(commit_guard_ai)?;
+    require_keys_eq!(commit_guard.quote, quote.key(), RfqError::InvalidQuote);
+
     let seeds_rfq: &[&[u8]] = &[
         Rfq::SEED_PREFIX,
         rfq.maker.as_ref(),
         rfq.uuid.as_ref(),
         &[rfq.bump],
     ];
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.bonds_escrow.to_account_info(),
-                to: ctx.accounts.maker_payment_account.to_account_info(),
-                authority: rfq.to_account_info(),
-            },
-            &[seeds_rfq],
-        ),
-        settlement.bond_amount,
-    )?;
 
     // Refund taker's bond (USDC)
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.bonds_escrow.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
                 to: ctx.accounts.taker_payment_account.to_account_info(),
                 authority: rfq.to_account_info(),
             },
             &[seeds_rfq],
         ),
         settlement.bond_amount,
+        ctx.accounts.usdc_mint.decimals,
     )?;
 
     // --- Fee collection (paid in quote_mint tokens) ---
@@ -253,61 +293,151 @@ pub fn complete_settlement_handler<'info>(
         .checked_sub(facilitator_share)
         .ok_or(RfqError::ArithmeticOverflow)?;
 
+    // Both legs are grossed up with the live fee, which may not exceed the one
+    // snapshotted at selection
+    check_transfer_fee_unchanged(
+        &ctx.accounts.base_mint.to_account_info(),
+        settlement.base_transfer_fee,
+    )?;
+    check_transfer_fee_unchanged(
+        &ctx.accounts.quote_mint.to_account_info(),
+        settlement.quote_transfer_fee,
+    )?;
+
+    // Transfer-fee mints: the taker pays the fee on top of each leg so
+    // receivers get the agreed amounts. The escrow is grossed up twice since
+    // withdraw_reward pays the fee again on the way out.
+    let quote_mint_info = ctx.accounts.quote_mint.to_account_info();
+    let treasury_transfer = gross_up(&quote_mint_info, treasury_share)?;
+    let facilitator_transfer =
+        gross_up(&quote_mint_info, gross_up(&quote_mint_info, facilitator_share)?)?;
+
     // Treasury share → treasury_quote_ata (in quote_mint tokens)
     if treasury_share > 0 {
-        token::transfer(
+        transfer_hook::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                ctx.accounts.quote_token_program.to_account_info(),
+                TransferChecked {
                     from: ctx.accounts.taker_quote_account.to_account_info(),
+                    mint: ctx.accounts.quote_mint.to_account_info(),
                     to: ctx.accounts.treasury_quote_ata.to_account_info(),
                     authority: ctx.accounts.taker.to_account_info(),
                 },
-            ),
-            treasury_share,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            treasury_transfer,
+            ctx.accounts.quote_mint.decimals,
         )?;
     }
-    // Facilitator share → fee_escrow (in quote_mint tokens, claimed via withdraw_reward)
+    // Facilitator share → fee_escrow (in quote_mint tokens, claimed via withdraw_reward).
+    // The amount actually credited is recorded so withdraw_reward pays out exactly that.
+    let escrowed_before = ctx.accounts.fee_escrow.amount;
     if facilitator_share > 0 {
-        token::transfer(
+        transfer_hook::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                ctx.accounts.quote_token_program.to_account_info(),
+                TransferChecked {
                     from: ctx.accounts.taker_quote_account.to_account_info(),
+                    mint: ctx.accounts.quote_mint.to_account_info(),
                     to: ctx.accounts.fee_escrow.to_account_info(),
                     authority: ctx.accounts.taker.to_account_info(),
                 },
-            ),
-            facilitator_share,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            facilitator_transfer,
+            ctx.accounts.quote_mint.decimals,
         )?;
+        ctx.accounts.fee_escrow.reload()?;
     }
+    let facilitator_reward = ctx
+        .accounts
+        .fee_escrow
+        .amount
+        .checked_sub(escrowed_before)
+        .ok_or(RfqError::ArithmeticOverflow)?;
 
-    // Deliver base asset from vault to taker
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_base_ata.to_account_info(),
-                to: ctx.accounts.taker_base_account.to_account_info(),
-                authority: rfq.to_account_info(),
-            },
-            &[seeds_rfq],
-        ),
-        settlement.base_amount,
-    )?;
+    match settlement.side {
+        RfqSide::Sell => {
+            // Deliver base asset from vault to taker (vault_base_amount nets base_amount)
+            transfer_hook::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.base_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault_base_ata.to_account_info(),
+                        mint: ctx.accounts.base_mint.to_account_info(),
+                        to: ctx.accounts.taker_base_account.to_account_info(),
+                        authority: rfq.to_account_info(),
+                    },
+                    &[seeds_rfq],
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                settlement.vault_base_amount,
+                ctx.accounts.base_mint.decimals,
+            )?;
 
-    // Deliver quote asset from taker to maker
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.taker_quote_account.to_account_info(),
-                to: ctx.accounts.maker_quote_account.to_account_info(),
-                authority: ctx.accounts.taker.to_account_info(),
-            },
-        ),
-        settlement.quote_amount,
-    )?;
+            // Deliver quote asset from taker to maker
+            transfer_hook::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.quote_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.taker_quote_account.to_account_info(),
+                        mint: ctx.accounts.quote_mint.to_account_info(),
+                        to: ctx.accounts.maker_quote_account.to_account_info(),
+                        authority: ctx.accounts.taker.to_account_info(),
+                    },
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                gross_up(&quote_mint_info, settlement.quote_amount)?,
+                ctx.accounts.quote_mint.decimals,
+            )?;
+        }
+        RfqSide::Buy => {
+            let maker_base_account = ctx
+                .accounts
+                .maker_base_account
+                .as_ref()
+                .ok_or(RfqError::MissingMakerBaseAccount)?;
+            require_keys_eq!(
+                ctx.accounts.fee_escrow.key(),
+                settlement.vault_quote_ata,
+                RfqError::MissingQuoteVaultAccount
+            );
+
+            // Deliver base asset from taker to maker (taker pays the base transfer fee)
+            let base_mint_info = ctx.accounts.base_mint.to_account_info();
+            transfer_hook::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.base_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.taker_base_account.to_account_info(),
+                        mint: base_mint_info.clone(),
+                        to: maker_base_account.to_account_info(),
+                        authority: ctx.accounts.taker.to_account_info(),
+                    },
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                gross_up(&base_mint_info, settlement.base_amount)?,
+                ctx.accounts.base_mint.decimals,
+            )?;
+
+            // Deliver quote asset from vault to taker (vault_quote_amount nets quote_amount)
+            transfer_hook::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.quote_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.fee_escrow.to_account_info(),
+                        mint: ctx.accounts.quote_mint.to_account_info(),
+                        to: ctx.accounts.taker_quote_account.to_account_info(),
+                        authority: rfq.to_account_info(),
+                    },
+                    &[seeds_rfq],
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                settlement.vault_quote_amount,
+                ctx.accounts.quote_mint.decimals,
+            )?;
+        }
+    }
 
     let mut slashed_bonds_tracker: Account<'info, SlashedBondsTracker> =
         Account::try_from(slashed_ai)?;
@@ -322,28 +452,68 @@ pub fn complete_settlement_handler<'info>(
         let seized_amount = compute_slashed_amount(rfq, false)?;
 
         if seized_amount > 0 {
-            token::transfer(
+            token_interface::transfer_checked(
                 CpiContext::new_with_signer(
                     ctx.accounts.token_program.to_account_info(),
-                    Transfer {
+                    TransferChecked {
                         from: ctx.accounts.bonds_escrow.to_account_info(),
+                        mint: ctx.accounts.usdc_mint.to_account_info(),
                         to: ctx.accounts.treasury_ata.to_account_info(),
                         authority: rfq.to_account_info(),
                     },
                     &[seeds_rfq],
                 ),
                 seized_amount,
+                ctx.accounts.usdc_mint.decimals,
             )?;
         }
 
         slashed_bonds_tracker.amount = Some(seized_amount);
         slashed_bonds_tracker.seized_at = Some(now);
         slashed_bonds_tracker.exit(ctx.program_id)?; // persist modifications
+
+        if seized_amount > 0 {
+            emit_cpi!(BondsSlashed {
+                rfq: rfq.key(),
+                settlement: None,
+                treasury_wallet: rfq.treasury_wallet,
+                amount: seized_amount,
+                keeper_bounty: 0,
+                seized_at: now,
+            });
+        }
+    }
+
+    // The RFQ is settled once no winner is left pending; the maker's bond is refunded then.
+    let is_last_pending = rfq.pending_settlements()? == 1;
+    if is_last_pending {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.bonds_escrow.to_account_info(),
+                    mint: ctx.accounts.usdc_mint.to_account_info(),
+                    to: ctx.accounts.maker_payment_account.to_account_info(),
+                    authority: rfq.to_account_info(),
+                },
+                &[seeds_rfq],
+            ),
+            rfq.bond_amount,
+            ctx.accounts.usdc_mint.decimals,
+        )?;
     }
 
     // update rfq
-    rfq.state = RfqState::Settled;
-    rfq.completed_at = Some(now);
+    rfq.settled_count = rfq
+        .settled_count
+        .checked_add(1)
+        .ok_or(RfqError::ArithmeticOverflow)?;
+    rfq.record_quote_closed()?;
+    if is_last_pending {
+        rfq.state = RfqState::Settled;
+        rfq.completed_at = Some(now);
+        ctx.accounts.config.record_rfq_closed(rfq.config_generation)?;
+    }
     //update settlement
     settlement.completed_at = Some(now);
     settlement.taker_funded_at = Some(now);
@@ -358,8 +528,47 @@ pub fn complete_settlement_handler<'info>(
     fees_tracker.payed_at = now;
     fees_tracker.bump = ctx.bumps.fees_tracker;
 
-    quote.bonds_refunded_at = Some(now);
-    quote.exit(ctx.program_id)?; // persist modifications
+    settlement.facilitator = (facilitator_share > 0).then_some(quote.facilitator).flatten();
+    settlement.facilitator_reward = facilitator_reward;
+
+    // the taker's bond is refunded: hand the quote and commit guard rent back
+    quote.close(ctx.accounts.taker.to_account_info())?;
+    commit_guard.close(ctx.accounts.taker.to_account_info())?;
+
+    emit_cpi!(BondsRefunded {
+        rfq: rfq.key(),
+        quote: Some(quote.key()),
+        owner: settlement.taker,
+        amount: settlement.bond_amount,
+        refunded_at: now,
+    });
+    emit_cpi!(SettlementCompleted {
+        rfq: rfq.key(),
+        quote: quote.key(),
+        settlement: settlement.key(),
+        maker: settlement.maker,
+        taker: settlement.taker,
+        base_amount: settlement.base_amount,
+        quote_amount: settlement.quote_amount,
+        treasury_fee: treasury_share,
+        facilitator_fee: facilitator_share,
+        completed_at: now,
+    });
+    if is_last_pending {
+        emit_cpi!(BondsRefunded {
+            rfq: rfq.key(),
+            quote: None,
+            owner: rfq.maker,
+            amount: rfq.bond_amount,
+            refunded_at: now,
+        });
+        emit_cpi!(RfqClosed {
+            rfq: rfq.key(),
+            maker: rfq.maker,
+            state: rfq.state.clone(),
+            closed_at: now,
+        });
+    }
 
     Ok(())
 }
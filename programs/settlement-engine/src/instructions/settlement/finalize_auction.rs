@@ -0,0 +1,322 @@
+use crate::events::QuoteSelected;
+use crate::instructions::select_quote::{build_settlement, MakerVault};
+use crate::state::rfq::{Rfq, RfqSide, RfqState};
+use crate::state::Settlement;
+use crate::state::{Config, Quote};
+use crate::transfer_fee::check_transfer_fee_policy;
+use crate::transfer_hook;
+use crate::vault::init_vault_if_needed;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{AssociatedToken, Create},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+/// Permissionless selection for auto-best RFQs.
+/// Every revealed quote of the RFQ must be passed in remaining_accounts (any order),
+/// followed by transfer-hook extras if needed; `quote` must be the best of them.
+/// The maker's deposit (base when selling, quote when buying) is pulled through
+/// a token delegation to the RFQ PDA.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeAuction<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: authority of the maker ATAs, checked against rfq.maker
+    #[account(address = rfq.maker @ RfqError::Unauthorized)]
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_SELECT_QUOTE) @ RfqError::ProtocolPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [Rfq::SEED_PREFIX, rfq.maker.as_ref(), rfq.uuid.as_ref()],
+        bump = rfq.bump,
+        has_one = config,
+        constraint = !rfq.paused @ RfqError::RfqPaused,
+    )]
+    pub rfq: Box<Account<'info, Rfq>>,
+
+    #[account(
+        mut,
+        seeds = [Quote::SEED_PREFIX, rfq.key().as_ref(), quote.taker.as_ref()],
+        bump = quote.bump,
+    )]
+    pub quote: Box<Account<'info, Quote>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Settlement::INIT_SPACE,
+        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref(), quote.key().as_ref()],
+        bump,
+    )]
+    pub settlement: Box<Account<'info, Settlement>>,
+
+    #[account(mint::token_program = quote_token_program)]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The maker's quote ATA; must already exist, the cranker does not create accounts for the maker
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = quote_token_program,
+    )]
+    pub maker_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: quote vault for Buy RFQs (also the fee escrow): the RFQ's quote ATA, created by the handler if missing
+    #[account(
+        mut,
+        seeds = [rfq.key().as_ref(), quote_token_program.key().as_ref(), quote_mint.key().as_ref()],
+        seeds::program = associated_token_program.key(),
+        bump,
+    )]
+    pub vault_quote_ata: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = base_token_program)]
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: base vault: the RFQ's base ATA, created by the handler if missing
+    #[account(
+        mut,
+        seeds = [rfq.key().as_ref(), base_token_program.key().as_ref(), base_mint.key().as_ref()],
+        seeds::program = associated_token_program.key(),
+        bump,
+    )]
+    pub vault_base_ata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = base_mint,
+        token::authority = maker,
+        token::token_program = base_token_program,
+    )]
+    pub maker_base_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn finalize_auction_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FinalizeAuction<'info>>,
+) -> Result<()> {
+    let rfq = &mut ctx.accounts.rfq;
+    let quote = &mut ctx.accounts.quote;
+    let settlement = &mut ctx.accounts.settlement;
+    let maker_base_account = &ctx.accounts.maker_base_account;
+    let base_mint = &ctx.accounts.base_mint;
+    let quote_mint = &ctx.accounts.quote_mint;
+
+    require!(rfq.auto_select_best, RfqError::AutoSelectDisabled);
+
+    let now = Clock::get()?.unix_timestamp;
+    match (rfq.reveal_deadline(), rfq.selection_deadline()) {
+        (Some(reveal_deadline), Some(selection_deadline)) => {
+            require!(now > reveal_deadline, RfqError::SelectionTooEarly);
+            require!(now <= selection_deadline, RfqError::SelectionTooLate);
+        }
+        _ => return err!(RfqError::InvalidRfqState),
+    }
+
+    require!(
+        matches!(rfq.state, RfqState::Revealed),
+        RfqError::InvalidRfqState
+    );
+    require!(!rfq.has_selection(), RfqError::AlreadySelected);
+
+    // Scan the full set of revealed quotes: distinct, revealed, of this RFQ,
+    // and exactly rfq.revealed_count of them, so none can be left out.
+    let rfq_key = rfq.key();
+    let revealed = rfq.revealed_count as usize;
+    require!(
+        ctx.remaining_accounts.len() >= revealed,
+        RfqError::IncompleteQuoteSet
+    );
+    let mut seen: Vec<Pubkey> = Vec::with_capacity(revealed);
+    let mut best: Option<(Pubkey, u64, i64)> = None;
+    for quote_ai in ctx.remaining_accounts[..revealed].iter() {
+        // Account::try_from checks owner and discriminator
+        let candidate: Account<'info, Quote> = Account::try_from(quote_ai)?;
+        require_keys_eq!(candidate.rfq, rfq_key, RfqError::InvalidRfqAssociation);
+        require!(candidate.is_revealed(), RfqError::IncompleteQuoteSet);
+        require!(!seen.contains(quote_ai.key), RfqError::IncompleteQuoteSet);
+        seen.push(quote_ai.key());
+
+        let amount = candidate
+            .revealed_amount(rfq.size_mode)
+            .ok_or(RfqError::InvalidQuoteState)?;
+        let entry = (quote_ai.key(), amount, candidate.committed_at);
+        if best.is_none_or(|b| ranks_above(rfq, &entry, &b)) {
+            best = Some(entry);
+        }
+    }
+    let (best_quote, _, _) = best.ok_or(RfqError::IncompleteQuoteSet)?;
+    require_keys_eq!(quote.key(), best_quote, RfqError::NotBestQuote);
+    require!(!quote.selected, RfqError::InvalidQuoteState);
+
+    require!(
+        !maker_base_account.is_frozen(),
+        RfqError::MakerBaseAccountClosed
+    );
+    require!(base_mint.key() == rfq.base_mint, RfqError::InvalidBaseMint);
+    require!(
+        quote_mint.key() == rfq.quote_mint,
+        RfqError::InvalidQuoteMint
+    );
+
+    // Transfer-fee mints: enforce policy and refresh snapshots
+    let base_mint_info = base_mint.to_account_info();
+    rfq.base_transfer_fee = check_transfer_fee_policy(&base_mint_info, rfq.transfer_fee_policy)?;
+    rfq.quote_transfer_fee =
+        check_transfer_fee_policy(&quote_mint.to_account_info(), rfq.transfer_fee_policy)?;
+
+    let revealed_amount = quote
+        .revealed_amount(rfq.size_mode)
+        .ok_or(RfqError::InvalidQuoteState)?;
+    let (_, settled_quote_amount) = rfq.settled_amounts(revealed_amount, rfq.base_amount)?;
+    let vault = if rfq.firm {
+        MakerVault::prefunded(rfq)
+    } else {
+        MakerVault::compute(
+            rfq,
+            &base_mint_info,
+            &quote_mint.to_account_info(),
+            settled_quote_amount,
+        )?
+    };
+
+    // The cranker pays for the vaults it creates (a firm RFQ's base vault exists since open_rfq)
+    let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+    init_vault_if_needed(
+        &associated_token_program,
+        Create {
+            payer: ctx.accounts.payer.to_account_info(),
+            associated_token: ctx.accounts.vault_base_ata.to_account_info(),
+            authority: rfq.to_account_info(),
+            mint: base_mint_info.clone(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.base_token_program.to_account_info(),
+        },
+        &mut rfq.base_vault_payer,
+    )?;
+    init_vault_if_needed(
+        &associated_token_program,
+        Create {
+            payer: ctx.accounts.payer.to_account_info(),
+            associated_token: ctx.accounts.vault_quote_ata.to_account_info(),
+            authority: rfq.to_account_info(),
+            mint: quote_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.quote_token_program.to_account_info(),
+        },
+        &mut rfq.quote_vault_payer,
+    )?;
+
+    let (cpi_program, source, mint, vault_ata, decimals) = match rfq.side {
+        RfqSide::Sell => (
+            ctx.accounts.base_token_program.to_account_info(),
+            maker_base_account,
+            base_mint.to_account_info(),
+            ctx.accounts.vault_base_ata.to_account_info(),
+            base_mint.decimals,
+        ),
+        RfqSide::Buy => (
+            ctx.accounts.quote_token_program.to_account_info(),
+            &ctx.accounts.maker_quote_account,
+            quote_mint.to_account_info(),
+            ctx.accounts.vault_quote_ata.to_account_info(),
+            quote_mint.decimals,
+        ),
+    };
+
+    // Firm RFQs already hold the base since open_rfq
+    if !rfq.firm {
+        // The maker is not a signer here: the RFQ PDA must be approved as delegate
+        require!(
+            source.delegate == Some(rfq_key).into()
+                && source.delegated_amount >= vault.deposit_amount,
+            RfqError::InsufficientMakerDelegation
+        );
+
+        let seeds_rfq: &[&[u8]] = &[
+            Rfq::SEED_PREFIX,
+            rfq.maker.as_ref(),
+            rfq.uuid.as_ref(),
+            &[rfq.bump],
+        ];
+
+        // Transfert the maker's asset to the RFQ vault, as delegate
+        transfer_hook::transfer_checked(
+            CpiContext::new_with_signer(
+                cpi_program,
+                TransferChecked {
+                    from: source.to_account_info(),
+                    mint,
+                    to: vault_ata,
+                    authority: rfq.to_account_info(),
+                },
+                &[seeds_rfq],
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            vault.deposit_amount,
+            decimals,
+        )?;
+    }
+
+    // update rfq
+    rfq.state = RfqState::Selected;
+    rfq.prefunded_base_amount = 0; // now held for the settlement
+    rfq.selected_at = Some(now);
+    rfq.selected_quote = Some(quote.key());
+    rfq.settlement = Some(settlement.key());
+    rfq.selected_count = 1;
+
+    settlement.set_inner(build_settlement(
+        rfq,
+        quote,
+        maker_base_account.key(),
+        ctx.accounts.vault_base_ata.key(),
+        ctx.accounts.maker_quote_account.key(),
+        ctx.accounts.vault_quote_ata.key(),
+        rfq.base_amount,
+        &vault,
+        ctx.accounts.payer.key(),
+        now,
+        ctx.bumps.settlement,
+    )?);
+
+    //update quote
+    quote.selected = true;
+
+    emit_cpi!(QuoteSelected {
+        rfq: rfq.key(),
+        quote: quote.key(),
+        taker: quote.taker,
+        settlement: settlement.key(),
+        base_amount: settlement.base_amount,
+        quote_amount: settlement.quote_amount,
+        funding_deadline: settlement.funding_deadline,
+        selected_at: now,
+    });
+
+    Ok(())
+}
+
+// Better price for the maker wins (see Rfq::is_better_price); ties go to the
+// earliest commit, then to the lowest quote key.
+fn ranks_above(rfq: &Rfq, a: &(Pubkey, u64, i64), b: &(Pubkey, u64, i64)) -> bool {
+    if a.1 != b.1 {
+        return rfq.is_better_price(a.1, b.1);
+    }
+    (std::cmp::Reverse(a.2), std::cmp::Reverse(a.0)) > (std::cmp::Reverse(b.2), std::cmp::Reverse(b.0))
+}
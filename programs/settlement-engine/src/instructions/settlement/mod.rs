@@ -1,5 +1,9 @@
 pub mod select_quote;
 pub use select_quote::*;
+pub mod select_quotes;
+pub use select_quotes::*;
+pub mod finalize_auction;
+pub use finalize_auction::*;
 pub mod complete_settlement;
 pub use complete_settlement::*;
 pub mod withdraw_reward;
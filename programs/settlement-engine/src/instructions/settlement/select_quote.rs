@@ -1,13 +1,18 @@
-use crate::state::rfq::{Rfq, RfqState};
+use crate::events::QuoteSelected;
+use crate::state::rfq::{Rfq, RfqSide, RfqState};
 use crate::state::Settlement;
 use crate::state::{Config, Quote};
+use crate::transfer_fee::{check_transfer_fee_policy, gross_up};
+use crate::transfer_hook;
+use crate::vault::init_vault_if_needed;
 use crate::RfqError;
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    associated_token::{AssociatedToken, Create},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SelectQuote<'info> {
     #[account(mut)]
@@ -16,6 +21,7 @@ pub struct SelectQuote<'info> {
     #[account(
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_SELECT_QUOTE) @ RfqError::ProtocolPaused,
     )]
     pub config: Box<Account<'info, Config>>,
 
@@ -25,6 +31,7 @@ pub struct SelectQuote<'info> {
         bump = rfq.bump,
         has_one = maker @ RfqError::Unauthorized,
         has_one = config,
+        constraint = !rfq.paused @ RfqError::RfqPaused,
     )]
     pub rfq: Box<Account<'info, Rfq>>,
 
@@ -39,50 +46,64 @@ pub struct SelectQuote<'info> {
         init,
         payer = maker,
         space = 8 + Settlement::INIT_SPACE,
-        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref()],
+        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref(), quote.key().as_ref()],
         bump,
     )]
     pub settlement: Account<'info, Settlement>,
 
-    #[account()]
-    pub quote_mint: Box<Account<'info, Mint>>,
+    #[account(mint::token_program = quote_token_program)]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         init_if_needed,
         payer = maker,
         associated_token::mint = quote_mint,
         associated_token::authority = maker,
+        associated_token::token_program = quote_token_program,
     )]
-    pub maker_quote_account: Account<'info, TokenAccount>,
+    pub maker_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account()]
-    pub base_mint: Box<Account<'info, Mint>>,
+    /// CHECK: quote vault for Buy RFQs (also the fee escrow): the RFQ's quote ATA, created by the handler if missing
+    #[account(
+        mut,
+        seeds = [rfq.key().as_ref(), quote_token_program.key().as_ref(), quote_mint.key().as_ref()],
+        seeds::program = associated_token_program.key(),
+        bump,
+    )]
+    pub vault_quote_ata: UncheckedAccount<'info>,
 
+    #[account(mint::token_program = base_token_program)]
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: base vault: the RFQ's base ATA, created by the handler if missing
     #[account(
-        init_if_needed,
-        payer = maker,
-        associated_token::mint = base_mint,
-        associated_token::authority = rfq,
+        mut,
+        seeds = [rfq.key().as_ref(), base_token_program.key().as_ref(), base_mint.key().as_ref()],
+        seeds::program = associated_token_program.key(),
+        bump,
     )]
-    pub vault_base_ata: Account<'info, TokenAccount>,
+    pub vault_base_ata: UncheckedAccount<'info>,
 
     #[account(
         mut,
         token::mint = base_mint,
         token::authority = maker,
+        token::token_program = base_token_program,
     )]
-    pub maker_base_account: Box<Account<'info, TokenAccount>>,
+    pub maker_base_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn select_quote_handler(ctx: Context<SelectQuote>) -> Result<()> {
+pub fn select_quote_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SelectQuote<'info>>,
+) -> Result<()> {
     let rfq = &mut ctx.accounts.rfq;
     let quote = &mut ctx.accounts.quote;
     let settlement = &mut ctx.accounts.settlement;
-    let maker = &ctx.accounts.maker;
     let maker_base_account = &ctx.accounts.maker_base_account;
     let base_mint = &ctx.accounts.base_mint;
     let quote_mint = &ctx.accounts.quote_mint;
@@ -112,6 +133,14 @@ pub fn select_quote_handler(ctx: Context<SelectQuote>) -> Result<()> {
         RfqError::InvalidRfqState
     );
     require!(!rfq.has_selection(), RfqError::AlreadySelected);
+    require!(!rfq.auto_select_best, RfqError::AutoSelectEnabled);
+    // ties with the best price are as good as the best
+    if rfq.require_best_quote {
+        require!(
+            quote.revealed_amount(rfq.size_mode) == Some(rfq.best_quote_amount),
+            RfqError::NotBestQuote
+        );
+    }
 
     require!(
         !maker_base_account.is_frozen(),
@@ -124,51 +153,226 @@ pub fn select_quote_handler(ctx: Context<SelectQuote>) -> Result<()> {
         RfqError::InvalidQuoteMint
     );
 
-    // Transfert base tokens from maker to RFQ vault
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.maker_base_account.to_account_info(),
-        to: ctx.accounts.vault_base_ata.to_account_info(),
-        authority: ctx.accounts.maker.to_account_info(),
+    // Transfer-fee mints: enforce policy and refresh snapshots
+    let base_mint_info = base_mint.to_account_info();
+    rfq.base_transfer_fee = check_transfer_fee_policy(&base_mint_info, rfq.transfer_fee_policy)?;
+    rfq.quote_transfer_fee =
+        check_transfer_fee_policy(&quote_mint.to_account_info(), rfq.transfer_fee_policy)?;
+
+    let revealed_amount = quote
+        .revealed_amount(rfq.size_mode)
+        .ok_or(RfqError::InvalidQuoteState)?;
+    let (_, settled_quote_amount) = rfq.settled_amounts(revealed_amount, rfq.base_amount)?;
+    let vault = if rfq.firm {
+        MakerVault::prefunded(rfq)
+    } else {
+        MakerVault::compute(
+            rfq,
+            &base_mint_info,
+            &quote_mint.to_account_info(),
+            settled_quote_amount,
+        )?
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, rfq.base_amount)?;
+
+    // The maker pays for the vaults it creates (a firm RFQ's base vault exists since open_rfq)
+    let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+    init_vault_if_needed(
+        &associated_token_program,
+        Create {
+            payer: ctx.accounts.maker.to_account_info(),
+            associated_token: ctx.accounts.vault_base_ata.to_account_info(),
+            authority: rfq.to_account_info(),
+            mint: base_mint_info.clone(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.base_token_program.to_account_info(),
+        },
+        &mut rfq.base_vault_payer,
+    )?;
+    init_vault_if_needed(
+        &associated_token_program,
+        Create {
+            payer: ctx.accounts.maker.to_account_info(),
+            associated_token: ctx.accounts.vault_quote_ata.to_account_info(),
+            authority: rfq.to_account_info(),
+            mint: quote_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.quote_token_program.to_account_info(),
+        },
+        &mut rfq.quote_vault_payer,
+    )?;
+
+    // Transfert the maker's asset to the RFQ vault: base when selling, quote when buying.
+    // Firm RFQs already hold the base since open_rfq.
+    let (cpi_program, cpi_accounts, decimals) = match rfq.side {
+        RfqSide::Sell => (
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.maker_base_account.to_account_info(),
+                mint: ctx.accounts.base_mint.to_account_info(),
+                to: ctx.accounts.vault_base_ata.to_account_info(),
+                authority: ctx.accounts.maker.to_account_info(),
+            },
+            base_mint.decimals,
+        ),
+        RfqSide::Buy => (
+            ctx.accounts.quote_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.maker_quote_account.to_account_info(),
+                mint: ctx.accounts.quote_mint.to_account_info(),
+                to: ctx.accounts.vault_quote_ata.to_account_info(),
+                authority: ctx.accounts.maker.to_account_info(),
+            },
+            quote_mint.decimals,
+        ),
+    };
+    if !rfq.firm {
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_hook::transfer_checked(cpi_ctx, vault.deposit_amount, decimals)?;
+    }
 
     // update rfq
     rfq.state = RfqState::Selected;
+    rfq.prefunded_base_amount = 0; // now held for the settlement
     rfq.selected_at = Some(now);
     rfq.selected_quote = Some(quote.key());
     rfq.settlement = Some(settlement.key());
+    rfq.selected_count = 1;
 
-    // fill settlement
-    settlement.rfq = rfq.key();
-    settlement.quote = quote.key();
-    settlement.maker = maker.key();
-    settlement.taker = quote.taker;
-    settlement.base_mint = rfq.base_mint;
-    settlement.quote_mint = rfq.quote_mint;
-    settlement.base_amount = rfq.base_amount;
-    settlement.quote_amount = quote
-        .quote_amount
-        .ok_or_else(|| RfqError::InvalidQuoteState)?;
-    settlement.bond_amount = rfq.bond_amount;
-    settlement.taker_fee_bps = rfq.taker_fee_bps;
-    settlement.created_at = now;
-    settlement.completed_at = None;
-    settlement.maker_funded_at = Some(now);
-    settlement.taker_funded_at = None;
-    settlement.bump = ctx.bumps.settlement;
-    settlement.maker_payment_account = rfq.maker_payment_account;
-    settlement.taker_payment_account = quote.taker_payment_account;
-    settlement.bonds_escrow = rfq.bonds_escrow;
-    settlement.maker_base_account = maker_base_account.key();
-    settlement.taker_base_account = None;
-    settlement.vault_base_ata = ctx.accounts.vault_base_ata.key();
-    settlement.maker_quote_account = ctx.accounts.maker_quote_account.key();
-    settlement.taker_quote_account = None;
+    // fill settlement (single winner takes the whole base_amount)
+    settlement.set_inner(build_settlement(
+        rfq,
+        quote,
+        maker_base_account.key(),
+        ctx.accounts.vault_base_ata.key(),
+        ctx.accounts.maker_quote_account.key(),
+        ctx.accounts.vault_quote_ata.key(),
+        rfq.base_amount,
+        &vault,
+        ctx.accounts.maker.key(),
+        now,
+        ctx.bumps.settlement,
+    )?);
 
     //update quote
     quote.selected = true;
 
+    emit_cpi!(QuoteSelected {
+        rfq: rfq.key(),
+        quote: quote.key(),
+        taker: quote.taker,
+        settlement: settlement.key(),
+        base_amount: settlement.base_amount,
+        quote_amount: settlement.quote_amount,
+        funding_deadline: settlement.funding_deadline,
+        selected_at: now,
+    });
+
     Ok(())
 }
+
+/// What the vault must hold for one winner, and what the maker deposits to get there.
+/// The vault must be able to release the agreed amount net of the mint transfer fee,
+/// and the maker pays the fee on the way in as well.
+pub(crate) struct MakerVault {
+    pub vault_base_amount: u64,  // Sell: base_amount grossed up
+    pub vault_quote_amount: u64, // Buy: clearing quote amount grossed up
+    pub deposit_amount: u64,     // in base (Sell) or quote (Buy) tokens
+}
+
+impl MakerVault {
+    pub(crate) fn compute(
+        rfq: &Rfq,
+        base_mint: &AccountInfo,
+        quote_mint: &AccountInfo,
+        quote_amount: u64,
+    ) -> Result<Self> {
+        Ok(match rfq.side {
+            RfqSide::Sell => {
+                let vault_base_amount = gross_up(base_mint, rfq.base_amount)?;
+                MakerVault {
+                    vault_base_amount,
+                    vault_quote_amount: 0,
+                    deposit_amount: gross_up(base_mint, vault_base_amount)?,
+                }
+            }
+            RfqSide::Buy => {
+                let vault_quote_amount = gross_up(quote_mint, quote_amount)?;
+                MakerVault {
+                    vault_base_amount: 0,
+                    vault_quote_amount,
+                    deposit_amount: gross_up(quote_mint, vault_quote_amount)?,
+                }
+            }
+        })
+    }
+
+    /// Firm RFQs: the vault already holds the base deposited at open_rfq.
+    pub(crate) fn prefunded(rfq: &Rfq) -> Self {
+        MakerVault {
+            vault_base_amount: rfq.prefunded_base_amount,
+            vault_quote_amount: 0,
+            deposit_amount: 0,
+        }
+    }
+}
+
+/// Builds the settlement snapshot for one winning quote.
+/// `base_allocation` is the share of rfq.base_amount this winner receives; amounts
+/// follow `Rfq::settled_amounts` (clearing price pro-rated to the allocation, or the
+/// fixed quote size against the cleared base amount in quote-size RFQs).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn build_settlement(
+    rfq: &Account<Rfq>,
+    quote: &Account<Quote>,
+    maker_base_account: Pubkey,
+    vault_base_ata: Pubkey,
+    maker_quote_account: Pubkey,
+    vault_quote_ata: Pubkey,
+    base_allocation: u64,
+    vault: &MakerVault,
+    rent_payer: Pubkey,
+    now: i64,
+    bump: u8,
+) -> Result<Settlement> {
+    let revealed_amount = quote
+        .revealed_amount(rfq.size_mode)
+        .ok_or(RfqError::InvalidQuoteState)?;
+    let (base_amount, quote_amount) = rfq.settled_amounts(revealed_amount, base_allocation)?;
+    Ok(Settlement {
+        rfq: rfq.key(),
+        quote: quote.key(),
+        side: rfq.side,
+        maker: rfq.maker,
+        taker: quote.taker,
+        facilitator: None, // set by complete_settlement
+        rent_payer,
+        base_mint: rfq.base_mint,
+        quote_mint: rfq.quote_mint,
+        base_amount,
+        quote_amount,
+        bond_amount: rfq.bond_amount,
+        taker_fee_bps: rfq.taker_fee_bps,
+        vault_base_amount: vault.vault_base_amount,
+        vault_quote_amount: vault.vault_quote_amount,
+        base_transfer_fee: rfq.base_transfer_fee,
+        quote_transfer_fee: rfq.quote_transfer_fee,
+        facilitator_reward: 0, // set by complete_settlement
+        reward_withdrawn: false,
+        maker_payment_account: rfq.maker_payment_account,
+        taker_payment_account: quote.taker_payment_account,
+        bonds_escrow: rfq.bonds_escrow,
+        maker_base_account,
+        taker_base_account: None,
+        vault_base_ata,
+        maker_quote_account,
+        taker_quote_account: None,
+        vault_quote_ata,
+        created_at: now,
+        funding_deadline: now + rfq.fund_ttl_secs as i64,
+        completed_at: None,
+        maker_funded_at: Some(now),
+        taker_funded_at: None,
+        bump,
+    })
+}
@@ -0,0 +1,363 @@
+use crate::events::QuoteSelected;
+use crate::instructions::select_quote::{build_settlement, MakerVault};
+use crate::state::rfq::{Rfq, RfqState};
+use crate::state::Settlement;
+use crate::state::{Config, Quote};
+use crate::transfer_fee::{check_transfer_fee_policy, gross_up};
+use crate::transfer_hook;
+use crate::vault::init_vault_if_needed;
+use crate::RfqError;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+use anchor_spl::{
+    associated_token::{AssociatedToken, Create},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+/// Multi-winner selection: quotes and their settlement PDAs are passed in
+/// remaining_accounts as [quote_0, settlement_0, quote_1, settlement_1, ...],
+/// in the same order as `base_allocations`. Transfer-hook extras may follow.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SelectQuotes<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_SELECT_QUOTE) @ RfqError::ProtocolPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [Rfq::SEED_PREFIX, maker.key().as_ref(), rfq.uuid.as_ref()],
+        bump = rfq.bump,
+        has_one = maker @ RfqError::Unauthorized,
+        has_one = config,
+        constraint = !rfq.paused @ RfqError::RfqPaused,
+    )]
+    pub rfq: Box<Account<'info, Rfq>>,
+
+    #[account(mint::token_program = quote_token_program)]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = quote_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = quote_token_program,
+    )]
+    pub maker_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: fee escrow: the RFQ's quote ATA, created by the handler if missing
+    #[account(
+        mut,
+        seeds = [rfq.key().as_ref(), quote_token_program.key().as_ref(), quote_mint.key().as_ref()],
+        seeds::program = associated_token_program.key(),
+        bump,
+    )]
+    pub vault_quote_ata: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = base_token_program)]
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: base vault: the RFQ's base ATA, created by the handler if missing
+    #[account(
+        mut,
+        seeds = [rfq.key().as_ref(), base_token_program.key().as_ref(), base_mint.key().as_ref()],
+        seeds::program = associated_token_program.key(),
+        bump,
+    )]
+    pub vault_base_ata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = base_mint,
+        token::authority = maker,
+        token::token_program = base_token_program,
+    )]
+    pub maker_base_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn select_quotes_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SelectQuotes<'info>>,
+    base_allocations: Vec<u64>,
+) -> Result<()> {
+    let rfq = &mut ctx.accounts.rfq;
+    let maker_base_account = &ctx.accounts.maker_base_account;
+    let base_mint = &ctx.accounts.base_mint;
+    let quote_mint = &ctx.accounts.quote_mint;
+
+    require!(rfq.allow_partial_fills, RfqError::PartialFillsDisabled);
+
+    let now = Clock::get()?.unix_timestamp;
+    match (rfq.reveal_deadline(), rfq.selection_deadline()) {
+        (Some(reveal_deadline), Some(selection_deadline)) => {
+            require!(now > reveal_deadline, RfqError::SelectionTooEarly);
+            require!(now <= selection_deadline, RfqError::SelectionTooLate);
+        }
+        _ => return err!(RfqError::InvalidRfqState),
+    }
+
+    require!(
+        matches!(rfq.state, RfqState::Revealed),
+        RfqError::InvalidRfqState
+    );
+    require!(!rfq.has_selection(), RfqError::AlreadySelected);
+    require!(!rfq.auto_select_best, RfqError::AutoSelectEnabled);
+
+    // allocations: 1..=MAX_WINNERS non-zero shares summing to base_amount
+    let winners = base_allocations.len();
+    require!(winners > 0, RfqError::InvalidBaseAllocation);
+    require!(winners <= Rfq::MAX_WINNERS, RfqError::TooManyWinners);
+    let mut allocated: u64 = 0;
+    for allocation in base_allocations.iter() {
+        require!(*allocation > 0, RfqError::InvalidBaseAllocation);
+        allocated = allocated
+            .checked_add(*allocation)
+            .ok_or(RfqError::ArithmeticOverflow)?;
+    }
+    require!(
+        allocated == rfq.base_amount,
+        RfqError::InvalidBaseAllocation
+    );
+    require!(
+        ctx.remaining_accounts.len() >= 2 * winners,
+        RfqError::MissingSettlementAccount
+    );
+
+    require!(
+        !maker_base_account.is_frozen(),
+        RfqError::MakerBaseAccountClosed
+    );
+    require!(base_mint.key() == rfq.base_mint, RfqError::InvalidBaseMint);
+    require!(
+        quote_mint.key() == rfq.quote_mint,
+        RfqError::InvalidQuoteMint
+    );
+
+    // Transfer-fee mints: enforce policy and refresh snapshots
+    let base_mint_info = base_mint.to_account_info();
+    rfq.base_transfer_fee = check_transfer_fee_policy(&base_mint_info, rfq.transfer_fee_policy)?;
+    rfq.quote_transfer_fee =
+        check_transfer_fee_policy(&quote_mint.to_account_info(), rfq.transfer_fee_policy)?;
+
+    // Each winner's vault share is grossed up on its own, since each is released separately.
+    let mut vault_base_amounts = Vec::with_capacity(winners);
+    let mut vault_total: u64 = 0;
+    for allocation in base_allocations.iter() {
+        let vault_base_amount = gross_up(&base_mint_info, *allocation)?;
+        vault_total = vault_total
+            .checked_add(vault_base_amount)
+            .ok_or(RfqError::ArithmeticOverflow)?;
+        vault_base_amounts.push(vault_base_amount);
+    }
+    let deposit_amount = gross_up(&base_mint_info, vault_total)?;
+
+    // The maker pays for the vaults it creates, like select_quote. Partial fills are
+    // Sell-only: the quote vault just doubles as fee escrow for the completing takers.
+    let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+    init_vault_if_needed(
+        &associated_token_program,
+        Create {
+            payer: ctx.accounts.maker.to_account_info(),
+            associated_token: ctx.accounts.vault_base_ata.to_account_info(),
+            authority: rfq.to_account_info(),
+            mint: base_mint_info.clone(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.base_token_program.to_account_info(),
+        },
+        &mut rfq.base_vault_payer,
+    )?;
+    init_vault_if_needed(
+        &associated_token_program,
+        Create {
+            payer: ctx.accounts.maker.to_account_info(),
+            associated_token: ctx.accounts.vault_quote_ata.to_account_info(),
+            authority: rfq.to_account_info(),
+            mint: quote_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.quote_token_program.to_account_info(),
+        },
+        &mut rfq.quote_vault_payer,
+    )?;
+
+    // Transfert base tokens from maker to RFQ vault (all winners at once)
+    transfer_hook::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
+                from: maker_base_account.to_account_info(),
+                mint: base_mint.to_account_info(),
+                to: ctx.accounts.vault_base_ata.to_account_info(),
+                authority: ctx.accounts.maker.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        deposit_amount,
+        base_mint.decimals,
+    )?;
+
+    let rfq_key = rfq.key();
+    for (i, base_allocation) in base_allocations.iter().enumerate() {
+        let quote_ai = &ctx.remaining_accounts[2 * i];
+        let settlement_ai = &ctx.remaining_accounts[2 * i + 1];
+
+        // Account::try_from checks owner and discriminator
+        let mut quote: Account<'info, Quote> = Account::try_from(quote_ai)?;
+        require_keys_eq!(quote.rfq, rfq_key, RfqError::InvalidRfqAssociation);
+        let expected_quote = Pubkey::create_program_address(
+            &[
+                Quote::SEED_PREFIX,
+                rfq_key.as_ref(),
+                quote.taker.as_ref(),
+                &[quote.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| error!(RfqError::PdaMismatch))?;
+        require_keys_eq!(quote_ai.key(), expected_quote, RfqError::PdaMismatch);
+        require!(quote.is_revealed(), RfqError::InvalidQuoteState);
+        // also rejects the same quote listed twice: it was persisted as selected below
+        require!(!quote.selected, RfqError::InvalidQuoteState);
+
+        let (expected_settlement, settlement_bump) = Pubkey::find_program_address(
+            &[Settlement::SEED_PREFIX, rfq_key.as_ref(), quote_ai.key.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            settlement_ai.key(),
+            expected_settlement,
+            RfqError::PdaMismatch
+        );
+        create_settlement_account(
+            &ctx.accounts.maker.to_account_info(),
+            settlement_ai,
+            &ctx.accounts.system_program.to_account_info(),
+            &[
+                Settlement::SEED_PREFIX,
+                rfq_key.as_ref(),
+                quote_ai.key.as_ref(),
+                &[settlement_bump],
+            ],
+        )?;
+
+        let settlement = build_settlement(
+            rfq,
+            &quote,
+            maker_base_account.key(),
+            ctx.accounts.vault_base_ata.key(),
+            ctx.accounts.maker_quote_account.key(),
+            ctx.accounts.vault_quote_ata.key(),
+            *base_allocation,
+            &MakerVault {
+                vault_base_amount: vault_base_amounts[i],
+                vault_quote_amount: 0,
+                deposit_amount: 0, // deposited above for all winners
+            },
+            ctx.accounts.maker.key(),
+            now,
+            settlement_bump,
+        )?;
+        settlement.try_serialize(&mut &mut settlement_ai.try_borrow_mut_data()?[..])?;
+
+        quote.selected = true;
+        quote.exit(ctx.program_id)?; // persist modifications
+
+        emit_cpi!(QuoteSelected {
+            rfq: rfq_key,
+            quote: quote_ai.key(),
+            taker: quote.taker,
+            settlement: settlement_ai.key(),
+            base_amount: settlement.base_amount,
+            quote_amount: settlement.quote_amount,
+            funding_deadline: settlement.funding_deadline,
+            selected_at: now,
+        });
+    }
+
+    // update rfq
+    rfq.state = RfqState::Selected;
+    rfq.selected_at = Some(now);
+    rfq.selected_quote = None;
+    rfq.settlement = None;
+    rfq.selected_count = winners as u16;
+
+    Ok(())
+}
+
+// Same as Anchor's `init`: create_account, or top-up/allocate/assign when the PDA
+// was pre-funded by a third party.
+fn create_settlement_account<'info>(
+    payer: &AccountInfo<'info>,
+    settlement: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let space = 8 + Settlement::INIT_SPACE;
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = settlement.lamports();
+
+    if current_lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: settlement.clone(),
+                },
+                &[seeds],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    require_keys_eq!(
+        *settlement.owner,
+        system_program::ID,
+        RfqError::InvalidOwner
+    );
+    let top_up = rent.saturating_sub(current_lamports);
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: settlement.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: settlement.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: settlement.clone(),
+            },
+            &[seeds],
+        ),
+        &crate::ID,
+    )
+}
@@ -1,12 +1,15 @@
+use crate::events::RewardWithdrawn;
 use crate::state::rfq::{Rfq, RfqState};
-use crate::state::{Config, FacilitatorRewardTracker, Quote, Settlement};
+use crate::state::{Config, FacilitatorRewardTracker, Settlement};
+use crate::transfer_hook;
 use crate::RfqError;
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct WithdrawReward<'info> {
     #[account(mut)]
@@ -23,46 +26,42 @@ pub struct WithdrawReward<'info> {
         seeds = [Rfq::SEED_PREFIX, rfq.maker.key().as_ref(), rfq.uuid.as_ref()],
         bump = rfq.bump,
         has_one = config,
-        constraint = matches!(rfq.state, RfqState::Settled) @ RfqError::InvalidRfqState,
+        constraint = matches!(rfq.state, RfqState::Selected | RfqState::Settled) @ RfqError::InvalidRfqState,
     )]
     pub rfq: Box<Account<'info, Rfq>>,
 
     #[account(
-        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref()],
+        mut,
+        seeds = [Settlement::SEED_PREFIX, rfq.key().as_ref(), settlement.quote.as_ref()],
         bump = settlement.bump,
         has_one = rfq,
-        has_one = quote @ RfqError::InvalidQuote,
         constraint = settlement.is_complete() @ RfqError::InvalidRfqState,
     )]
     pub settlement: Box<Account<'info, Settlement>>,
 
     #[account(
-        seeds = [Quote::SEED_PREFIX, rfq.key().as_ref(), settlement.taker.as_ref()],
-        bump = quote.bump,
-        has_one = rfq @ RfqError::InvalidRfqAssociation,
-        constraint = quote.selected @ RfqError::InvalidQuoteState,
-        constraint = quote.revealed_at.is_some() @ RfqError::InvalidQuoteState,
+        address = settlement.quote_mint,
+        mint::token_program = quote_token_program,
     )]
-    pub quote: Box<Account<'info, Quote>>,
-
-    #[account(address = settlement.quote_mint)]
-    pub quote_mint: Box<Account<'info, Mint>>,
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// Fee escrow holds the facilitator's share in quote_mint tokens
     #[account(
         mut,
         associated_token::mint = quote_mint,
         associated_token::authority = rfq,
+        associated_token::token_program = quote_token_program,
     )]
-    pub fee_escrow: Box<Account<'info, TokenAccount>>,
+    pub fee_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = facilitator,
         associated_token::mint = quote_mint,
         associated_token::authority = facilitator,
+        associated_token::token_program = quote_token_program,
     )]
-    pub facilitator_ata: Box<Account<'info, TokenAccount>>,
+    pub facilitator_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init,
@@ -71,6 +70,7 @@ pub struct WithdrawReward<'info> {
         seeds = [
             FacilitatorRewardTracker::SEED_PREFIX,
             rfq.key().as_ref(),
+            settlement.quote.as_ref(),
             facilitator.key().as_ref(),
         ],
         bump,
@@ -78,24 +78,29 @@ pub struct WithdrawReward<'info> {
     pub facilitator_reward_tracker: Box<Account<'info, FacilitatorRewardTracker>>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn withdraw_reward_handler(ctx: Context<WithdrawReward>) -> Result<()> {
+pub fn withdraw_reward_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawReward<'info>>,
+) -> Result<()> {
     let rfq = &ctx.accounts.rfq;
-    let settlement = &ctx.accounts.settlement;
-    let quote = &ctx.accounts.quote;
+    let settlement = &mut ctx.accounts.settlement;
 
+    // the quote is closed on completion; the settlement records who earned the share
     let facilitator_key = ctx.accounts.facilitator.key();
     require!(
-        rfq.facilitator == Some(facilitator_key) && quote.facilitator == Some(facilitator_key),
+        settlement.facilitator == Some(facilitator_key),
         RfqError::Unauthorized
     );
 
-    let total_fee = settlement.compute_total_fee()?;
-    let facilitator_share = settlement.compute_facilitator_share(total_fee, rfq.facilitator_fee_bps)?;
-    require!(facilitator_share > 0, RfqError::InvalidParams);
+    // pay out exactly what complete_settlement put in escrow: re-deriving it under the
+    // current epoch's transfer fee would strand dust (or fail) once a scheduled fee kicks in
+    let payout = settlement.facilitator_reward;
+    require!(payout > 0, RfqError::InvalidParams);
+    // close_rfq relies on this flag to know the fee escrow owes nothing more
+    settlement.reward_withdrawn = true;
 
     let seeds_rfq: &[&[u8]] = &[
         Rfq::SEED_PREFIX,
@@ -103,25 +108,36 @@ pub fn withdraw_reward_handler(ctx: Context<WithdrawReward>) -> Result<()> {
         rfq.uuid.as_ref(),
         &[rfq.bump],
     ];
-    token::transfer(
+    transfer_hook::transfer_checked(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.quote_token_program.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.fee_escrow.to_account_info(),
+                mint: ctx.accounts.quote_mint.to_account_info(),
                 to: ctx.accounts.facilitator_ata.to_account_info(),
                 authority: rfq.to_account_info(),
             },
             &[seeds_rfq],
-        ),
-        facilitator_share,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        payout,
+        ctx.accounts.quote_mint.decimals,
     )?;
 
     let reward_tracker = &mut ctx.accounts.facilitator_reward_tracker;
     reward_tracker.rfq = rfq.key();
     reward_tracker.facilitator = facilitator_key;
     reward_tracker.quote_mint = ctx.accounts.quote_mint.key();
-    reward_tracker.amount = facilitator_share;
+    reward_tracker.amount = payout;
     reward_tracker.claimed_at = Clock::get()?.unix_timestamp;
+
+    emit_cpi!(RewardWithdrawn {
+        rfq: rfq.key(),
+        quote: settlement.quote,
+        facilitator: facilitator_key,
+        amount: payout,
+        withdrawn_at: reward_tracker.claimed_at,
+    });
     reward_tracker.bump = ctx.bumps.facilitator_reward_tracker;
 
     Ok(())
@@ -1,46 +1,59 @@
-use crate::state::config::Config;
+use crate::events::ConfigUpdated;
+use crate::state::config::{Config, TransferFeePolicy};
 use crate::RfqError;
 use anchor_lang::prelude::*;
 
+/// Instant changes, signed by the fee manager. The USDC mint, treasury, liquidity
+/// guard and facilitator fee go through queue_config_update instead.
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateConfig<'info> {
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
 
     #[account(
         mut,
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
-        has_one = admin @ RfqError::Unauthorized,
     )]
     pub config: Account<'info, Config>,
 }
 
 pub fn update_config_handler(
     ctx: Context<UpdateConfig>,
-    new_admin: Option<Pubkey>,
-    new_usdc_mint: Option<Pubkey>,
-    new_treasury_wallet: Option<Pubkey>,
-    new_liquidity_guard: Option<Pubkey>,
-    new_facilitator_fee_bps: Option<u16>,
+    new_transfer_fee_policy: Option<TransferFeePolicy>,
+    new_keeper_bounty_bps: Option<u16>,
 ) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
     let cfg = &mut ctx.accounts.config;
 
-    if let Some(v) = new_admin {
-        cfg.admin = v;
-    }
-    if let Some(v) = new_usdc_mint {
-        cfg.usdc_mint = v;
-    }
-    if let Some(v) = new_treasury_wallet {
-        cfg.treasury_wallet = v;
-    }
-    if let Some(v) = new_liquidity_guard {
-        cfg.liquidity_guard = v;
+    require!(
+        new_transfer_fee_policy.is_some() || new_keeper_bounty_bps.is_some(),
+        RfqError::InvalidParams
+    );
+    require_keys_eq!(authority, cfg.fee_manager, RfqError::Unauthorized);
+
+    if let Some(v) = new_transfer_fee_policy {
+        cfg.transfer_fee_policy = v;
     }
-    if let Some(v) = new_facilitator_fee_bps {
-        require!(v <= 10_000, RfqError::InvalidFeeAmount);
-        cfg.facilitator_fee_bps = v;
+    if let Some(v) = new_keeper_bounty_bps {
+        require!(v <= Config::MAX_KEEPER_BOUNTY_BPS, RfqError::InvalidKeeperBounty);
+        cfg.keeper_bounty_bps = v;
     }
 
+    emit_cpi!(ConfigUpdated {
+        config: cfg.key(),
+        admin: cfg.admin,
+        usdc_mint: cfg.usdc_mint,
+        treasury_wallet: cfg.treasury_wallet,
+        liquidity_guard: cfg.liquidity_guard,
+        liquidity_guard_key_type: cfg.liquidity_guard_key_type,
+        facilitator_fee_bps: cfg.facilitator_fee_bps,
+        transfer_fee_policy: cfg.transfer_fee_policy,
+        keeper_bounty_bps: cfg.keeper_bounty_bps,
+        timelock_secs: cfg.timelock_secs,
+        closed: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
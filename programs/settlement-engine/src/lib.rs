@@ -2,10 +2,16 @@ use anchor_lang::prelude::*;
 use instructions::*;
 use rfq_errors::*;
 
+pub mod allowlist;
+pub mod events;
+pub mod guard_attestation;
 pub mod instructions;
 pub mod rfq_errors;
 pub mod slashing;
 pub mod state;
+pub mod transfer_fee;
+pub mod transfer_hook;
+pub mod vault;
 
 // Program ID
 declare_id!("7wrjbU1NbVtUCUGP1obi3aiT6QrjXZnH5XJDXMsKtkPG");
@@ -14,12 +20,17 @@ declare_id!("7wrjbU1NbVtUCUGP1obi3aiT6QrjXZnH5XJDXMsKtkPG");
 pub mod settlement_engine {
     use super::*;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn init_config(
         ctx: Context<InitConfig>,
         usdc_mint: Pubkey,
         treasury_wallet: Pubkey,
         liquidity_guard: Pubkey,
         facilitator_fee_bps: Option<u16>,
+        transfer_fee_policy: Option<state::config::TransferFeePolicy>,
+        keeper_bounty_bps: Option<u16>,
+        timelock_secs: Option<u32>,
+        liquidity_guard_key_type: Option<state::config::GuardKeyType>,
     ) -> Result<()> {
         init_config::init_config_handler(
             ctx,
@@ -27,32 +38,98 @@ pub mod settlement_engine {
             treasury_wallet,
             liquidity_guard,
             facilitator_fee_bps,
+            transfer_fee_policy,
+            keeper_bounty_bps,
+            timelock_secs,
+            liquidity_guard_key_type,
         )
     }
 
     pub fn update_config(
         ctx: Context<UpdateConfig>,
-        new_admin: Option<Pubkey>,
+        new_transfer_fee_policy: Option<state::config::TransferFeePolicy>,
+        new_keeper_bounty_bps: Option<u16>,
+    ) -> Result<()> {
+        update_config::update_config_handler(ctx, new_transfer_fee_policy, new_keeper_bounty_bps)
+    }
+
+    pub fn queue_config_update(
+        ctx: Context<QueueConfigUpdate>,
         new_usdc_mint: Option<Pubkey>,
         new_treasury_wallet: Option<Pubkey>,
         new_liquidity_guard: Option<Pubkey>,
         new_facilitator_fee_bps: Option<u16>,
+        new_timelock_secs: Option<u32>,
+        new_liquidity_guard_key_type: Option<state::config::GuardKeyType>,
     ) -> Result<()> {
-        update_config::update_config_handler(
+        queue_config_update::queue_config_update_handler(
             ctx,
-            new_admin,
             new_usdc_mint,
             new_treasury_wallet,
             new_liquidity_guard,
             new_facilitator_fee_bps,
+            new_timelock_secs,
+            new_liquidity_guard_key_type,
         )
     }
 
+    pub fn execute_config_update(ctx: Context<ExecuteConfigUpdate>) -> Result<()> {
+        execute_config_update::execute_config_update_handler(ctx)
+    }
+
+    pub fn cancel_config_update(ctx: Context<CancelConfigUpdate>) -> Result<()> {
+        cancel_config_update::cancel_config_update_handler(ctx)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Option<Pubkey>) -> Result<()> {
+        propose_admin::propose_admin_handler(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        accept_admin::accept_admin_handler(ctx)
+    }
+
+    pub fn set_config_roles(
+        ctx: Context<SetConfigRoles>,
+        new_fee_manager: Option<Pubkey>,
+        new_guard_manager: Option<Pubkey>,
+        new_pauser: Option<Pubkey>,
+    ) -> Result<()> {
+        set_config_roles::set_config_roles_handler(ctx, new_fee_manager, new_guard_manager, new_pauser)
+    }
+
+    pub fn add_guard_key(
+        ctx: Context<AddGuardKey>,
+        key: Pubkey,
+        valid_from: i64,
+        valid_until: Option<i64>,
+        key_type: Option<state::config::GuardKeyType>,
+    ) -> Result<()> {
+        add_guard_key::add_guard_key_handler(ctx, key, valid_from, valid_until, key_type)
+    }
+
+    pub fn revoke_guard_key(ctx: Context<RevokeGuardKey>, valid_until: Option<i64>) -> Result<()> {
+        revoke_guard_key::revoke_guard_key_handler(ctx, valid_until)
+    }
+
+    pub fn remove_guard_key(ctx: Context<RemoveGuardKey>) -> Result<()> {
+        remove_guard_key::remove_guard_key_handler(ctx)
+    }
+
+    pub fn set_pause(ctx: Context<SetPause>, paused: u8) -> Result<()> {
+        set_pause::set_pause_handler(ctx, paused)
+    }
+
     pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
         close_config::close_config_handler(ctx)
     }
 
+    pub fn force_close_config(ctx: Context<ForceCloseConfig>) -> Result<()> {
+        force_close_config::force_close_config_handler(ctx)
+    }
+
     // RFQ module
+    #[allow(clippy::too_many_arguments)]
     pub fn init_rfq(
         ctx: Context<InitRfq>,
         uuid: [u8; 16],
@@ -67,6 +144,16 @@ pub mod settlement_engine {
         selection_ttl_secs: u32,
         fund_ttl_secs: u32,
         facilitator: Option<Pubkey>,
+        allow_partial_fills: bool,
+        auto_select_best: bool,
+        require_best_quote: bool,
+        pricing_mode: state::rfq::PricingMode,
+        side: state::rfq::RfqSide,
+        max_quote_amount: u64,
+        size_mode: state::rfq::SizeMode,
+        quote_size: u64,
+        firm: bool,
+        taker_allowlist_root: Option<[u8; 32]>,
     ) -> Result<()> {
         init_rfq::init_rfq_handler(
             ctx,
@@ -82,9 +169,20 @@ pub mod settlement_engine {
             selection_ttl_secs,
             fund_ttl_secs,
             facilitator,
+            allow_partial_fills,
+            auto_select_best,
+            require_best_quote,
+            pricing_mode,
+            side,
+            max_quote_amount,
+            size_mode,
+            quote_size,
+            firm,
+            taker_allowlist_root,
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_rfq(
         ctx: Context<UpdateRfq>,
         new_base_mint: Option<Pubkey>,
@@ -98,6 +196,9 @@ pub mod settlement_engine {
         new_selection_ttl_secs: Option<u32>,
         new_fund_ttl_secs: Option<u32>,
         new_facilitator_update: Option<state::rfq::FacilitatorUpdate>,
+        new_allowlist_update: Option<state::rfq::AllowlistUpdate>,
+        new_max_quote_amount: Option<u64>,
+        new_quote_size: Option<u64>,
     ) -> Result<()> {
         update_rfq::update_rfq_handler(
             ctx,
@@ -112,10 +213,13 @@ pub mod settlement_engine {
             new_selection_ttl_secs,
             new_fund_ttl_secs,
             new_facilitator_update,
+            new_allowlist_update,
+            new_max_quote_amount,
+            new_quote_size,
         )
     }
 
-    pub fn open_rfq(ctx: Context<OpenRfq>) -> Result<()> {
+    pub fn open_rfq<'info>(ctx: Context<'_, '_, 'info, 'info, OpenRfq<'info>>) -> Result<()> {
         open_rfq::open_rfq_handler(ctx)
     }
     pub fn set_rfq_facilitator(
@@ -124,17 +228,32 @@ pub mod settlement_engine {
     ) -> Result<()> {
         set_rfq_facilitator::set_rfq_facilitator_handler(ctx, update)
     }
+    pub fn set_rfq_pause(ctx: Context<SetRfqPause>, paused: bool) -> Result<()> {
+        set_rfq_pause::set_rfq_pause_handler(ctx, paused)
+    }
     pub fn cancel_rfq(ctx: Context<CancelRfq>) -> Result<()> {
         cancel_rfq::cancel_rfq_handler(ctx)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn commit_quote(
         ctx: Context<CommitQuote>,
         commit_hash: [u8; 32],
         liquidity_proof: [u8; 64],
         facilitator: Option<Pubkey>,
+        verify_ix_index: Option<u16>,
+        reveal_delegate: Option<Pubkey>,
+        allowlist_proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        commit_quote::commit_quote_handler(ctx, commit_hash, liquidity_proof, facilitator)
+        commit_quote::commit_quote_handler(
+            ctx,
+            commit_hash,
+            liquidity_proof,
+            facilitator,
+            verify_ix_index,
+            reveal_delegate,
+            allowlist_proof,
+        )
     }
 
     pub fn set_quote_facilitator(
@@ -147,34 +266,65 @@ pub mod settlement_engine {
     pub fn reveal_quote(
         ctx: Context<RevealQuote>,
         salt: [u8; 64],
-        quote_amount: u64,
+        amount: u64,
     ) -> Result<()> {
-        reveal_quote::reveal_quote_handler(ctx, salt, quote_amount)
+        reveal_quote::reveal_quote_handler(ctx, salt, amount)
     }
 
-    pub fn select_quote(ctx: Context<SelectQuote>) -> Result<()> {
+    pub fn select_quote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SelectQuote<'info>>,
+    ) -> Result<()> {
         select_quote::select_quote_handler(ctx)
     }
 
+    pub fn select_quotes<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SelectQuotes<'info>>,
+        base_allocations: Vec<u64>,
+    ) -> Result<()> {
+        select_quotes::select_quotes_handler(ctx, base_allocations)
+    }
+
+    pub fn finalize_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeAuction<'info>>,
+    ) -> Result<()> {
+        finalize_auction::finalize_auction_handler(ctx)
+    }
+
     pub fn complete_settlement<'info>(
         ctx: Context<'_, '_, 'info, 'info, CompleteSettlement<'info>>,
     ) -> Result<()> {
         complete_settlement::complete_settlement_handler(ctx)
     }
 
-    pub fn close_expired(ctx: Context<CloseExpired>) -> Result<()> {
+    pub fn close_expired<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseExpired<'info>>,
+    ) -> Result<()> {
         close_expired::close_expired_handler(ctx)
     }
 
-    pub fn close_incomplete(ctx: Context<CloseIncomplete>) -> Result<()> {
+    pub fn close_incomplete<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseIncomplete<'info>>,
+    ) -> Result<()> {
         close_incomplete::close_incomplete_handler(ctx)
     }
 
-    pub fn refund_quote_bonds(ctx: Context<RefundQuoteBonds>) -> Result<()> {
+    pub fn refund_quote_bonds<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundQuoteBonds<'info>>,
+    ) -> Result<()> {
         refund_quote_bonds::refund_quote_bonds_handler(ctx)
     }
 
-    pub fn withdraw_reward(ctx: Context<WithdrawReward>) -> Result<()> {
+    pub fn close_quote(ctx: Context<CloseQuote>) -> Result<()> {
+        close_quote::close_quote_handler(ctx)
+    }
+
+    pub fn withdraw_reward<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawReward<'info>>,
+    ) -> Result<()> {
         withdraw_reward::withdraw_reward_handler(ctx)
     }
+
+    pub fn close_rfq<'info>(ctx: Context<'_, '_, 'info, 'info, CloseRfq<'info>>) -> Result<()> {
+        close_rfq::close_rfq_handler(ctx)
+    }
 }
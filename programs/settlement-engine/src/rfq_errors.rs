@@ -64,11 +64,11 @@ pub enum RfqError {
     RevealTooLate,
     #[msg("Reveal attempted too early")]
     RevealTooEarly,
-    #[msg("No Ed25519 instruction found")]
+    #[msg("No Ed25519 / Secp256r1 instruction found")]
     NoEd25519Instruction,
-    #[msg("Invalid Ed25519 program ID")]
+    #[msg("Invalid Ed25519 / Secp256r1 program ID")]
     InvalidEd25519Program,
-    #[msg("Invalid Ed25519 instruction data")]
+    #[msg("Invalid Ed25519 / Secp256r1 instruction data")]
     InvalidEd25519Data,
     #[msg("Invalid signature count")]
     InvalidSignatureCount,
@@ -120,4 +120,94 @@ pub enum RfqError {
     MissingSlashedBondsTrackerAccount,
     #[msg("Quote account missing")]
     MissingQuoteAccount,
+    #[msg("Mint carries a Token-2022 transfer fee, which the configured policy rejects")]
+    TransferFeeMintRejected,
+    #[msg("Invalid Token-2022 mint data")]
+    InvalidMintData,
+    #[msg("RFQ does not allow partial fills")]
+    PartialFillsDisabled,
+    #[msg("Base allocations must be non-zero and sum to base_amount")]
+    InvalidBaseAllocation,
+    #[msg("Too many winners selected")]
+    TooManyWinners,
+    #[msg("Settlement account missing")]
+    MissingSettlementAccount,
+    #[msg("Partial fills cannot be combined with best-price selection modes")]
+    InvalidSelectionMode,
+    #[msg("RFQ uses automatic best-price selection")]
+    AutoSelectEnabled,
+    #[msg("RFQ does not use automatic best-price selection")]
+    AutoSelectDisabled,
+    #[msg("Every revealed quote must be supplied exactly once")]
+    IncompleteQuoteSet,
+    #[msg("Quote is not the best revealed quote")]
+    NotBestQuote,
+    #[msg("RFQ is not approved as delegate for the maker deposit")]
+    InsufficientMakerDelegation,
+    #[msg("Invalid Max Quote Amount")]
+    InvalidMaxQuoteAmount,
+    #[msg("Quote vault account missing")]
+    MissingQuoteVaultAccount,
+    #[msg("Maker base account missing")]
+    MissingMakerBaseAccount,
+    #[msg("Quote-size RFQs must be buy-side")]
+    InvalidSizeMode,
+    #[msg("Invalid Quote Size")]
+    InvalidQuoteSize,
+    #[msg("Firm RFQs must be sell-side and single-winner")]
+    InvalidFirmMode,
+    #[msg("Base vault account missing")]
+    MissingBaseVaultAccount,
+    #[msg("Quote bond is neither refunded nor slashed yet")]
+    QuoteNotResolved,
+    #[msg("CommitGuard account missing")]
+    MissingCommitGuardAccount,
+    #[msg("Some quotes of this RFQ are still open")]
+    OpenQuotesRemaining,
+    #[msg("RFQ escrow still holds bonds, base or unclaimed rewards")]
+    EscrowNotEmpty,
+    #[msg("Every settlement must be supplied with its fees tracker, rent payer and taker")]
+    InvalidSettlementAccounts,
+    #[msg("Rent destination does not match the recorded payer")]
+    InvalidRentPayer,
+    #[msg("Keeper bounty exceeds the maximum")]
+    InvalidKeeperBounty,
+    #[msg("Instruction is paused protocol-wide")]
+    ProtocolPaused,
+    #[msg("RFQ is paused")]
+    RfqPaused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("A config update is already queued")]
+    ConfigUpdatePending,
+    #[msg("No config update is queued")]
+    NoPendingConfigUpdate,
+    #[msg("Config update timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Config still has live RFQs")]
+    LiveRfqsRemaining,
+    #[msg("Invalid guard key validity window")]
+    InvalidGuardKeyWindow,
+    #[msg("Guard key is still valid")]
+    GuardKeyStillValid,
+    #[msg("Liquidity guard attestation does not match this commit")]
+    InvalidAttestation,
+    #[msg("Liquidity guard attestation has expired")]
+    AttestationExpired,
+    #[msg("Quote exceeds the notional attested by the liquidity guard")]
+    ExceedsAttestedNotional,
+    #[msg("Taker is not in the RFQ's allowlist")]
+    TakerNotAllowlisted,
+    #[msg("Maker payment account missing")]
+    MissingMakerPaymentAccount,
+    #[msg("Facilitator reward has not been withdrawn")]
+    RewardNotWithdrawn,
+    #[msg("Maker token account missing")]
+    MissingMakerTokenAccount,
+    #[msg("Selection pause accounts do not prove the pause history")]
+    InvalidSelectionPause,
+    #[msg("Selection is paused")]
+    SelectionPaused,
+    #[msg("Mint transfer fee increased since selection")]
+    TransferFeeChanged,
 }
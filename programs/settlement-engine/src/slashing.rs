@@ -1,6 +1,7 @@
 use crate::rfq_errors::RfqError;
 use crate::state::rfq::Rfq;
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TransferChecked};
 
 // Computes total slashed bonds for an RFQ.
 // Base slashing = (committed_count - revealed_count) * bond_amount.
@@ -25,3 +26,59 @@ pub fn compute_slashed_amount(rfq: &Rfq, include_actor_bond: bool) -> Result<u64
         .checked_mul(rfq.bond_amount)
         .ok_or_else(|| error!(RfqError::ArithmeticOverflow))
 }
+
+// Keeper bounty = floor(seized_amount * rfq.keeper_bounty_bps / 10_000).
+pub fn compute_keeper_bounty(rfq: &Rfq, seized_amount: u64) -> Result<u64> {
+    (seized_amount as u128)
+        .checked_mul(rfq.keeper_bounty_bps as u128)
+        .and_then(|v| v.checked_div(10_000))
+        .and_then(|v| u64::try_from(v).ok())
+        .ok_or_else(|| error!(RfqError::ArithmeticOverflow))
+}
+
+// Moves seized bonds out of the bonds escrow: the keeper bounty to the keeper's
+// USDC account (when one is given), the rest to the treasury. Returns the bounty paid.
+#[allow(clippy::too_many_arguments)]
+pub fn seize_bonds<'info>(
+    rfq: &Account<'info, Rfq>,
+    token_program: &AccountInfo<'info>,
+    usdc_mint: &InterfaceAccount<'info, Mint>,
+    bonds_escrow: &InterfaceAccount<'info, TokenAccount>,
+    treasury_ata: &InterfaceAccount<'info, TokenAccount>,
+    keeper_payment_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    seized_amount: u64,
+    seeds_rfq: &[&[u8]],
+) -> Result<u64> {
+    let keeper_bounty = match keeper_payment_account {
+        Some(_) => compute_keeper_bounty(rfq, seized_amount)?,
+        None => 0,
+    };
+    let treasury_amount = seized_amount
+        .checked_sub(keeper_bounty)
+        .ok_or_else(|| error!(RfqError::ArithmeticOverflow))?;
+
+    let transfer = |to: &InterfaceAccount<'info, TokenAccount>, amount: u64| -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TransferChecked {
+                    from: bonds_escrow.to_account_info(),
+                    mint: usdc_mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: rfq.to_account_info(),
+                },
+                &[seeds_rfq],
+            ),
+            amount,
+            usdc_mint.decimals,
+        )
+    };
+    if let Some(keeper_payment_account) = keeper_payment_account {
+        transfer(keeper_payment_account, keeper_bounty)?;
+    }
+    transfer(treasury_ata, treasury_amount)?;
+    Ok(keeper_bounty)
+}
@@ -8,11 +8,158 @@ pub struct Config {
     pub admin: Pubkey,               // admin authority
     pub usdc_mint: Pubkey,           // USDC mint for fees/bonds
     pub treasury_wallet: Pubkey, // treasury wallet authority
-    pub liquidity_guard: Pubkey, // liquidity guard public key (for ed25519 signatures verification)
+    pub liquidity_guard: Pubkey, // liquidity guard public key (for ed25519 / secp256r1 signatures verification)
+    pub liquidity_guard_key_type: GuardKeyType, // signature scheme of liquidity_guard
     pub facilitator_fee_bps: u16, // facilitator fee in BPS (1 BPS = 0.01%)
+    pub transfer_fee_policy: TransferFeePolicy, // how Token-2022 transfer-fee mints are handled
+    pub keeper_bounty_bps: u16,   // share of slashed bonds paid to whoever cranks close_expired / close_incomplete
+    pub pending_admin: Option<Pubkey>, // proposed admin, until it calls accept_admin
+    pub fee_manager: Pubkey,     // may change facilitator fee, transfer-fee policy and keeper bounty
+    pub guard_manager: Pubkey,   // may rotate the liquidity guard key
+    pub pauser: Pubkey,          // may pause and unpause new activity
+    pub paused: u8,              // PAUSE_* bitflags; exit paths are never paused
+    pub timelock_secs: u32,      // delay between queue_config_update and execute_config_update
+    pub pending_update: Option<PendingConfigUpdate>, // at most one queued change at a time
+    pub live_rfq_count: u64,     // RFQs opened and not yet Settled / Incomplete / Expired / Ignored
+    pub generation: u64,         // ConfigGeneration.generation at init; open_rfq stamps it on the RFQ
     pub bump: u8,                // PDA bump
 }
 
+/// Policy applied to base/quote mints carrying a non-zero Token-2022 transfer fee.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum TransferFeePolicy {
+    Reject,  // refuse such mints at init_rfq / select_quote
+    GrossUp, // accept them; senders pay the fee on top so receivers get the agreed amount
+}
+
+/// Signature scheme of a liquidity guard key, checked through the matching precompile.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum GuardKeyType {
+    Ed25519,   // the key is the ed25519 public key
+    Secp256r1, // P-256 (cloud HSM / passkey); the key is the x-coordinate of the point
+}
+
+impl GuardKeyType {
+    pub const ED25519_PROGRAM_ID: Pubkey =
+        Pubkey::from_str_const("Ed25519SigVerify111111111111111111111111111");
+    pub const SECP256R1_PROGRAM_ID: Pubkey =
+        Pubkey::from_str_const("Secp256r1SigVerify1111111111111111111111111");
+
+    /// Key type verified by a signature precompile, if `program_id` is one we accept.
+    pub fn from_precompile(program_id: &Pubkey) -> Option<Self> {
+        if *program_id == Self::ED25519_PROGRAM_ID {
+            Some(Self::Ed25519)
+        } else if *program_id == Self::SECP256R1_PROGRAM_ID {
+            Some(Self::Secp256r1)
+        } else {
+            None
+        }
+    }
+
+    /// Public key length in the precompile instruction data (secp256r1 keys are compressed).
+    pub fn pubkey_len(&self) -> usize {
+        match self {
+            Self::Ed25519 => 32,
+            Self::Secp256r1 => 33,
+        }
+    }
+
+    /// Whether the precompile's public key bytes are `key`. A secp256r1 key matches
+    /// either parity prefix: signing for the negated point still takes the guard's
+    /// private key.
+    pub fn matches(&self, pubkey_bytes: &[u8], key: &Pubkey) -> bool {
+        match self {
+            Self::Ed25519 => pubkey_bytes == key.as_ref(),
+            Self::Secp256r1 => {
+                matches!(pubkey_bytes.first(), Some(0x02 | 0x03))
+                    && &pubkey_bytes[1..] == key.as_ref()
+            }
+        }
+    }
+}
+
+/// Change queued by queue_config_update; `None` fields are left unchanged.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct PendingConfigUpdate {
+    pub usdc_mint: Option<Pubkey>,
+    pub treasury_wallet: Option<Pubkey>,
+    pub liquidity_guard: Option<Pubkey>,
+    pub liquidity_guard_key_type: Option<GuardKeyType>,
+    pub facilitator_fee_bps: Option<u16>,
+    pub timelock_secs: Option<u32>,
+    pub queued_by: Pubkey,
+    pub eta: i64, // earliest execute_config_update time
+}
+
 impl Config {
     pub const SEED_PREFIX: &'static [u8] = b"config";
+    /// Upper bound on the keeper bounty: 10% of the slashed bonds.
+    pub const MAX_KEEPER_BOUNTY_BPS: u16 = 1_000;
+    /// Timelock applied when init_config is not given one: 2 days.
+    pub const DEFAULT_TIMELOCK_SECS: u32 = 172_800;
+
+    /// Pause bitflags. Only instructions that start new activity can be paused;
+    /// refunds, expiry, incomplete closes and in-deadline settlement stay available.
+    pub const PAUSE_INIT_RFQ: u8 = 1 << 0;
+    pub const PAUSE_OPEN_RFQ: u8 = 1 << 1;
+    pub const PAUSE_COMMIT_QUOTE: u8 = 1 << 2;
+    /// select_quote, select_quotes and finalize_auction
+    pub const PAUSE_SELECT_QUOTE: u8 = 1 << 3;
+    pub const PAUSE_ALL: u8 = Self::PAUSE_INIT_RFQ
+        | Self::PAUSE_OPEN_RFQ
+        | Self::PAUSE_COMMIT_QUOTE
+        | Self::PAUSE_SELECT_QUOTE;
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.paused & flag != 0
+    }
+
+    /// Counts an RFQ opened now; returns the generation to stamp on it.
+    pub fn record_rfq_opened(&mut self) -> Result<u64> {
+        self.live_rfq_count = self
+            .live_rfq_count
+            .checked_add(1)
+            .ok_or_else(|| error!(crate::rfq_errors::RfqError::ArithmeticOverflow))?;
+        Ok(self.generation)
+    }
+
+    /// RFQs opened under an earlier config (stranded by force_close_config) were
+    /// never counted by this one, so only the current generation is decremented.
+    pub fn record_rfq_closed(&mut self, rfq_generation: u64) -> Result<()> {
+        if rfq_generation != self.generation {
+            return Ok(());
+        }
+        self.live_rfq_count = self
+            .live_rfq_count
+            .checked_sub(1)
+            .ok_or_else(|| error!(crate::rfq_errors::RfqError::ArithmeticOverflow))?;
+        Ok(())
+    }
+}
+
+/// Outlives the Config: bumped by every init_config, so a re-initialized config
+/// can tell its RFQs from the ones opened under a force-closed predecessor.
+/// Also numbers the SelectionPause history and the RFQ open nonces, which must
+/// outlive the Config too.
+/// PDA: seeds = ["config_generation"].
+#[account]
+#[derive(InitSpace)]
+pub struct ConfigGeneration {
+    pub generation: u64,
+    pub select_pause_count: u64, // SelectionPause accounts created so far (next index)
+    pub rfq_open_count: u64,     // RFQs ever opened; open_rfq hands out the next value as rfq.open_nonce
+    pub bump: u8,
+}
+
+impl ConfigGeneration {
+    pub const SEED_PREFIX: &'static [u8] = b"config_generation";
+
+    /// Hands out the next RFQ open nonce; never repeats, even across re-created configs.
+    pub fn next_open_nonce(&mut self) -> Result<u64> {
+        let nonce = self.rfq_open_count;
+        self.rfq_open_count = nonce
+            .checked_add(1)
+            .ok_or_else(|| error!(crate::rfq_errors::RfqError::ArithmeticOverflow))?;
+        Ok(nonce)
+    }
 }
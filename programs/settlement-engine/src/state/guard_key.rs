@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::state::config::GuardKeyType;
+
+/// Liquidity guard key accepted by commit_quote alongside the key snapshotted
+/// into each RFQ, so guard keys can rotate and run redundantly.
+/// PDA: seeds = ["guard_key", key]
+#[account]
+#[derive(InitSpace)]
+pub struct GuardKey {
+    pub key: Pubkey,               // key the guard signs attestations with
+    pub key_type: GuardKeyType,    // signature scheme of key
+    pub valid_from: i64,           // first commit time the key is accepted
+    pub valid_until: Option<i64>,  // accepted strictly before this time; None until revoked
+    pub rent_payer: Pubkey,        // refunded by remove_guard_key
+    pub bump: u8,
+}
+
+impl GuardKey {
+    pub const SEED_PREFIX: &'static [u8] = b"guard_key";
+
+    pub fn is_valid_at(&self, ts: i64) -> bool {
+        ts >= self.valid_from && self.valid_until.is_none_or(|until| ts < until)
+    }
+}
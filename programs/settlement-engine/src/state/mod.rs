@@ -12,3 +12,7 @@ pub mod slashed_bonds_tracker;
 pub use slashed_bonds_tracker::*;
 pub mod facilitator_reward_tracker;
 pub use facilitator_reward_tracker::*;
+pub mod guard_key;
+pub use guard_key::*;
+pub mod selection_pause;
+pub use selection_pause::*;
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::state::rfq::SizeMode;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Quote {
@@ -12,17 +14,24 @@ pub struct Quote {
     /// 32-byte commit hash and 64-byte liquidity_proof from liquidity-guard
     pub commit_hash: [u8; 32],
     pub liquidity_proof: [u8; 64],
+    /// Largest committed amount (in the committed mint) the liquidity guard attested
+    pub max_notional: u64,
     pub committed_at: i64,
     pub revealed_at: Option<i64>,
     pub max_funding_deadline: i64,
     pub bonds_refunded_at: Option<i64>,
     pub quote_amount: Option<u64>,
+    /// Revealed base amount (quote-size RFQs only; quote_amount is then the RFQ's quote_size)
+    pub base_amount: Option<u64>,
     pub taker_payment_account: Pubkey,
     pub selected: bool,
 
     //facilitator
     pub facilitator: Option<Pubkey>,
 
+    /// May submit reveal_quote on the taker's behalf (e.g. a relayer or the liquidity guard)
+    pub reveal_delegate: Option<Pubkey>,
+
     pub bump: u8,
 }
 
@@ -33,6 +42,14 @@ impl Quote {
         self.revealed_at.is_some()
     }
 
+    /// The amount this quote competes on: base in quote-size RFQs, quote otherwise.
+    pub fn revealed_amount(&self, size_mode: SizeMode) -> Option<u64> {
+        match size_mode {
+            SizeMode::Base => self.quote_amount,
+            SizeMode::Quote => self.base_amount,
+        }
+    }
+
     pub fn are_bonds_refunded(&self) -> bool {
         self.bonds_refunded_at.is_some()
     }
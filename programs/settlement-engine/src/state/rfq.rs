@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::state::config::{GuardKeyType, TransferFeePolicy};
+
 #[account]
 #[derive(InitSpace)]
 pub struct Rfq {
@@ -8,6 +10,8 @@ pub struct Rfq {
     pub maker: Pubkey,
     pub uuid: [u8; 16],
     pub state: RfqState,
+    pub side: RfqSide, // Sell: maker delivers base; Buy: maker delivers quote
+    pub size_mode: SizeMode, // which side of the trade is fixed; takers compete on the other
 
     // assets
     pub base_mint: Pubkey,
@@ -15,13 +19,22 @@ pub struct Rfq {
     pub usdc_mint: Pubkey,           // snapshot of config.usdc_mint at init
     pub treasury_wallet: Pubkey, // snapshot of config.treasury_wallet at init
     pub liquidity_guard: Pubkey,     // snapshot of config.liquidity_guard at init
+    pub liquidity_guard_key_type: GuardKeyType, // snapshot of config.liquidity_guard_key_type at init
 
     // economics (u64 in smallest units)
     pub bond_amount: u64,         // maker bond in USDC
-    pub base_amount: u64,         // exact base tokens maker will deliver
-    pub min_quote_amount: u64,    // minimum quote taker must deliver
+    pub base_amount: u64,         // exact base tokens maker will deliver (minimum base accepted in quote-size mode)
+    pub quote_size: u64,          // quote-size mode: exact quote tokens the maker will spend
+    pub min_quote_amount: u64,    // minimum quote taker must deliver (Sell)
+    pub max_quote_amount: u64,    // maximum quote maker will pay (Buy)
     pub taker_fee_bps: u16,      // protocol fee in BPS of quote_amount (paid in quote_mint)
     pub facilitator_fee_bps: u16, // snapshot of config.facilitator_fee_bps at init
+    pub keeper_bounty_bps: u16,   // snapshot of config.keeper_bounty_bps at init
+
+    // Token-2022 transfer fees
+    pub transfer_fee_policy: TransferFeePolicy, // snapshot of config.transfer_fee_policy at init
+    pub base_transfer_fee: Option<TransferFeeSnapshot>, // base mint fee seen at init/select
+    pub quote_transfer_fee: Option<TransferFeeSnapshot>, // quote mint fee seen at init/select
 
     // TTLs (seconds) – ALL relative to opened_at (not created_at)
     pub commit_ttl_secs: u32,
@@ -38,18 +51,45 @@ pub struct Rfq {
     // activity counters
     pub committed_count: u16,
     pub revealed_count: u16,
+    pub closed_quote_count: u16, // quotes closed once their bond was refunded or slashed
+
+    // standing price, maintained by reveal_quote (0 = none yet); "best" depends on side.
+    // In quote-size mode these are revealed base amounts.
+    pub best_quote: Option<Pubkey>,
+    pub best_quote_amount: u64,
+    pub second_best_quote_amount: u64,
 
     // selection & funding flags
-    pub selected_quote: Option<Pubkey>,
-    pub settlement: Option<Pubkey>,
+    pub allow_partial_fills: bool,        // maker may split base_amount across several quotes
+    pub auto_select_best: bool,           // best quote is selected by finalize_auction, not the maker
+    pub require_best_quote: bool,         // select_quote must pick a quote at best_quote_amount
+    pub pricing_mode: PricingMode,        // what the winner pays: own bid or second price
+    pub firm: bool,                       // maker pre-funds base_amount into vault_base_ata at open
+    pub prefunded_base_amount: u64,       // firm: base held in the vault until selection or refund
+    pub selected_quote: Option<Pubkey>,   // single-winner selection only
+    pub settlement: Option<Pubkey>,       // single-winner selection only
+    pub selected_count: u16,              // settlements created at selection
+    pub settled_count: u16,               // settlements completed by their taker
+    pub incomplete_count: u16,            // settlements closed after a funding no-show
 
     // escrow & maker references
     pub bonds_escrow: Pubkey, // ATA(owner = rfq PDA, mint = rfq.usdc_mint) – bonds only
     pub maker_payment_account: Pubkey,
+    pub base_vault_payer: Option<Pubkey>,  // created vault_base_ata and paid its rent, refunded by close_rfq
+    pub quote_vault_payer: Option<Pubkey>, // created vault_quote_ata (fee escrow) and paid its rent
 
     //facilitator
     pub facilitator: Option<Pubkey>,
 
+    // private RFQs: Merkle root of the takers allowed to commit (see allowlist.rs)
+    pub taker_allowlist_root: Option<[u8; 32]>,
+
+    pub paused: bool, // set by the config pauser: blocks open, commit and selection on this RFQ
+    pub paused_since: Option<i64>, // when this RFQ was paused, while it stays paused
+    pub selection_window_paused: bool, // an ended RFQ pause overlapped [reveal_deadline, selection_deadline]
+    pub config_generation: u64, // config.generation at open; other generations are not in live_rfq_count
+    pub open_nonce: u64, // unique per open_rfq, ever; bound into every commit hash
+
     pub bump: u8,
 }
 
@@ -66,19 +106,61 @@ pub enum RfqState {
     Incomplete, // taker did not fund in time after being selected
 }
 
+/// Which asset the maker delivers.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum RfqSide {
+    Sell, // maker sells base_amount, takers compete on the highest quote_amount
+    Buy,  // maker buys base_amount, takers compete on the lowest quote_amount
+}
+
+/// Which amount the maker fixes at init.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum SizeMode {
+    Base,  // base_amount is fixed, takers reveal a quote amount
+    Quote, // quote_size is fixed, takers reveal a base amount (Buy only)
+}
+
+/// How the settled quote amount is derived from the winning reveal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PricingMode {
+    FirstPrice,  // winner pays its own quote_amount
+    SecondPrice, // winner clears at second_best_quote_amount (the price limit if it was the only reveal)
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub enum FacilitatorUpdate {
     Clear,
     Set(Pubkey),
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum AllowlistUpdate {
+    Clear,         // open the RFQ to any taker
+    Set([u8; 32]), // restrict commits to takers under this Merkle root
+}
+
+/// Token-2022 transfer fee in effect for a mint when it was inspected.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct TransferFeeSnapshot {
+    pub transfer_fee_basis_points: u16,
+    pub maximum_fee: u64,
+    pub epoch: u64, // epoch the fee was read at
+}
+
 impl Rfq {
     pub const SEED_PREFIX: &'static [u8] = b"rfq";
+    /// Upper bound on winners in a single `select_quotes` call.
+    pub const MAX_WINNERS: usize = 8;
 
     pub fn is_draft(&self) -> bool {
         matches!(self.state, RfqState::Draft)
     }
 
+    /// Firm RFQ whose pre-funded base still sits in the vault for the maker.
+    pub fn holds_prefunded_base(&self) -> bool {
+        self.firm && self.prefunded_base_amount > 0
+    }
+
     pub fn has_selection(&self) -> bool {
         self.selected_at.is_some()
     }
@@ -87,6 +169,132 @@ impl Rfq {
         self.opened_at
     }
 
+    /// Whether revealed amount `a` beats `b` for the maker: higher quote when selling,
+    /// lower quote when buying, more base for a fixed quote size.
+    pub fn is_better_price(&self, a: u64, b: u64) -> bool {
+        match (self.size_mode, self.side) {
+            (SizeMode::Quote, _) => a > b,
+            (SizeMode::Base, RfqSide::Sell) => a > b,
+            (SizeMode::Base, RfqSide::Buy) => a < b,
+        }
+    }
+
+    /// Folds a new reveal into the standing best / second-best price.
+    /// Must be called after revealed_count is incremented. On a tie the earlier reveal stays best.
+    pub fn record_reveal(&mut self, quote: Pubkey, quote_amount: u64) {
+        if self.best_quote.is_none() || self.is_better_price(quote_amount, self.best_quote_amount) {
+            self.second_best_quote_amount = self.best_quote_amount;
+            self.best_quote = Some(quote);
+            self.best_quote_amount = quote_amount;
+        } else if self.revealed_count == 2
+            || self.is_better_price(quote_amount, self.second_best_quote_amount)
+        {
+            self.second_best_quote_amount = quote_amount;
+        }
+    }
+
+    /// Amount the winner settles at, given its revealed amount.
+    /// Second-price RFQs only clear on a quote at the best price.
+    pub fn clearing_price(&self, revealed_amount: u64) -> Result<u64> {
+        match self.pricing_mode {
+            PricingMode::FirstPrice => Ok(revealed_amount),
+            PricingMode::SecondPrice => {
+                require!(
+                    revealed_amount == self.best_quote_amount,
+                    crate::rfq_errors::RfqError::NotBestQuote
+                );
+                if self.revealed_count >= 2 {
+                    Ok(self.second_best_quote_amount)
+                } else {
+                    Ok(self.price_limit())
+                }
+            }
+        }
+    }
+
+    /// The reveal limit: quote floor when selling, quote ceiling when buying,
+    /// base floor in quote-size mode.
+    pub fn price_limit(&self) -> u64 {
+        match (self.size_mode, self.side) {
+            (SizeMode::Quote, _) => self.base_amount,
+            (SizeMode::Base, RfqSide::Sell) => self.min_quote_amount,
+            (SizeMode::Base, RfqSide::Buy) => self.max_quote_amount,
+        }
+    }
+
+    /// Whether a revealed amount is within the reveal limit.
+    pub fn within_price_limit(&self, revealed_amount: u64) -> bool {
+        match (self.size_mode, self.side) {
+            (SizeMode::Base, RfqSide::Buy) => revealed_amount <= self.price_limit(),
+            _ => revealed_amount >= self.price_limit(),
+        }
+    }
+
+    /// (base_amount, quote_amount) a winner settles at, from its revealed amount and
+    /// its share of base_amount (the whole of it for single-winner RFQs).
+    pub fn settled_amounts(&self, revealed_amount: u64, base_allocation: u64) -> Result<(u64, u64)> {
+        let clearing = self.clearing_price(revealed_amount)?;
+        match self.size_mode {
+            SizeMode::Base => Ok((
+                base_allocation,
+                crate::state::Settlement::prorate_quote_amount(
+                    clearing,
+                    base_allocation,
+                    self.base_amount,
+                )?,
+            )),
+            SizeMode::Quote => Ok((clearing, self.quote_size)),
+        }
+    }
+
+    /// Counts a quote closed after its bond was resolved.
+    pub fn record_quote_closed(&mut self) -> Result<()> {
+        self.closed_quote_count = self
+            .closed_quote_count
+            .checked_add(1)
+            .ok_or_else(|| error!(crate::rfq_errors::RfqError::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Pauses or resumes this RFQ at `now`. An ended pause that overlapped the selection
+    /// window is remembered, so later pauses cannot erase it.
+    pub fn set_paused(&mut self, paused: bool, now: i64) {
+        match (self.paused, paused) {
+            (false, true) => self.paused_since = Some(now),
+            (true, false) => {
+                if let (Some(since), Some(reveal_deadline), Some(selection_deadline)) = (
+                    self.paused_since,
+                    self.reveal_deadline(),
+                    self.selection_deadline(),
+                ) {
+                    self.selection_window_paused |=
+                        since <= selection_deadline && now >= reveal_deadline;
+                }
+                self.paused_since = None;
+            }
+            _ => (),
+        }
+        self.paused = paused;
+    }
+
+    /// Whether this RFQ was paused at some point of its selection window; only
+    /// asked once the window is over, so a pause still in force reaches its end.
+    pub fn was_selection_paused(&self) -> Result<bool> {
+        let selection_deadline = self
+            .selection_deadline()
+            .ok_or_else(|| error!(crate::rfq_errors::RfqError::InvalidRfqState))?;
+        Ok(self.selection_window_paused
+            || self.paused_since.is_some_and(|since| since <= selection_deadline))
+    }
+
+    /// Settlements still waiting for their taker to fund (or to be closed as incomplete).
+    pub fn pending_settlements(&self) -> Result<u16> {
+        self.selected_count
+            .checked_sub(self.settled_count)
+            .and_then(|v| v.checked_sub(self.incomplete_count))
+            .ok_or_else(|| error!(crate::rfq_errors::RfqError::ArithmeticOverflow))
+    }
+
     /// Commit deadline = opened_at + commit_ttl
     pub fn commit_deadline(&self) -> Option<i64> {
         self.opened_at.map(|t| t + self.commit_ttl_secs as i64)
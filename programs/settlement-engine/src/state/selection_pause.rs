@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::rfq_errors::RfqError;
+use crate::state::config::ConfigGeneration;
+
+/// One protocol-wide selection pause (Config::PAUSE_SELECT_QUOTE), created by
+/// set_pause when the flag is set and ended when it is lifted. Never closed, so
+/// refund_quote_bonds can tell whether any pause overlapped an RFQ's selection window.
+/// PDA: seeds = ["selection_pause", index (u64 LE)].
+#[account]
+#[derive(InitSpace)]
+pub struct SelectionPause {
+    pub index: u64,
+    pub started_at: i64,
+    pub resumed_at: Option<i64>, // None while the pause is in force
+    pub bump: u8,
+}
+
+impl SelectionPause {
+    pub const SEED_PREFIX: &'static [u8] = b"selection_pause";
+
+    /// Whether selection was paused at some point of `[from, to]`.
+    ///
+    /// Pauses are numbered in start order and never overlap, so only the last pause
+    /// started at or before `to` can reach back to `from`. `last` must be that pause
+    /// (None if every pause started after `to`) and `next` the one right after it,
+    /// unless `last` is the latest: the caller can neither hide a pause nor pick one.
+    pub fn paused_during(
+        generation: &ConfigGeneration,
+        from: i64,
+        to: i64,
+        last: Option<&SelectionPause>,
+        next: Option<&SelectionPause>,
+    ) -> Result<bool> {
+        let next_index = match last {
+            Some(pause) => {
+                require!(pause.started_at <= to, RfqError::InvalidSelectionPause);
+                pause
+                    .index
+                    .checked_add(1)
+                    .ok_or(RfqError::ArithmeticOverflow)?
+            }
+            None => 0,
+        };
+        if next_index < generation.select_pause_count {
+            let next = next.ok_or(RfqError::InvalidSelectionPause)?;
+            require!(
+                next.index == next_index && next.started_at > to,
+                RfqError::InvalidSelectionPause
+            );
+        }
+        Ok(last.is_some_and(
+            |pause| !matches!(pause.resumed_at, Some(resumed_at) if resumed_at < from),
+        ))
+    }
+}
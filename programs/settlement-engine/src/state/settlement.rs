@@ -1,16 +1,21 @@
 use anchor_lang::prelude::*;
 use crate::rfq_errors::RfqError;
+use crate::state::rfq::{RfqSide, TransferFeeSnapshot};
 
 /// Captures the immutable settlement snapshot once a quote is selected.
+/// One per winning quote, keyed by (rfq, quote); `base_amount` is the winner's allocation.
 #[account]
 #[derive(InitSpace)]
 pub struct Settlement {
     pub rfq: Pubkey,
     pub quote: Pubkey,
+    pub side: RfqSide,
 
     // participants
     pub maker: Pubkey,
     pub taker: Pubkey,
+    pub facilitator: Option<Pubkey>, // credited with a fee share at completion
+    pub rent_payer: Pubkey,          // paid the settlement rent at selection (maker or cranker)
 
     // assets and economics
     pub base_mint: Pubkey,
@@ -19,6 +24,16 @@ pub struct Settlement {
     pub quote_amount: u64,
     pub bond_amount: u64,
     pub taker_fee_bps: u16,
+    // tokens held by the vault for the maker's leg, grossed up by the mint transfer fee:
+    // base_amount in vault_base_ata (Sell) or quote_amount in vault_quote_ata (Buy)
+    pub vault_base_amount: u64,
+    pub vault_quote_amount: u64,
+    pub base_transfer_fee: Option<TransferFeeSnapshot>,
+    pub quote_transfer_fee: Option<TransferFeeSnapshot>,
+    // quote_mint tokens credited to the fee escrow for the facilitator at completion,
+    // paid out as-is by withdraw_reward whatever the transfer fee is by then
+    pub facilitator_reward: u64,
+    pub reward_withdrawn: bool, // set by withdraw_reward; close_rfq requires it while a reward is owed
 
     /// Token Accounts
     // USDC
@@ -32,10 +47,11 @@ pub struct Settlement {
     // quote mint
     pub maker_quote_account: Pubkey,
     pub taker_quote_account: Option<Pubkey>,
-    // pub vault_quote_ata: Pubkey, // Facultative vault assuming the quote transfer occurs directly between the taker and the maker.
+    pub vault_quote_ata: Pubkey, // ATA(quote_mint, rfq): holds the maker's quote on Buy RFQs, doubles as fee escrow
 
     // timeline
     pub created_at: i64,
+    pub funding_deadline: i64, // per-winner: created_at + rfq.fund_ttl_secs
     pub completed_at: Option<i64>,
 
     // funding timestamps
@@ -60,6 +76,18 @@ impl Settlement {
         self.taker_funded_at.is_some()
     }
 
+    /// Quote owed for a partial allocation of the RFQ's base amount:
+    /// ceil(quote_amount * base_allocation / base_amount), so dust goes to the maker.
+    pub fn prorate_quote_amount(quote_amount: u64, base_allocation: u64, base_amount: u64) -> Result<u64> {
+        require!(base_amount > 0, RfqError::InvalidBaseAmount);
+        (quote_amount as u128)
+            .checked_mul(base_allocation as u128)
+            .and_then(|v| v.checked_add(base_amount as u128 - 1))
+            .and_then(|v| v.checked_div(base_amount as u128))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or_else(|| error!(RfqError::ArithmeticOverflow))
+    }
+
     /// Floor division, but guarantee at least 1 when taker_fee_bps > 0.
     pub fn compute_total_fee(&self) -> Result<u64> {
         if self.taker_fee_bps > 0 {
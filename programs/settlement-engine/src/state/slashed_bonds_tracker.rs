@@ -6,7 +6,8 @@ pub struct SlashedBondsTracker {
     pub rfq: Pubkey,
     pub usdc_mint: Pubkey,
     pub treasury_wallet: Pubkey,
-    pub amount: Option<u64>,
+    pub amount: Option<u64>, // total seized, keeper bounty included
+    pub keeper_bounty: u64,  // part of amount paid to keepers instead of the treasury
     pub seized_at: Option<i64>,
     pub bump: u8,
 }
@@ -0,0 +1,124 @@
+use crate::rfq_errors::RfqError;
+use crate::state::{config::TransferFeePolicy, rfq::TransferFeeSnapshot};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account as TokenAccountState, Mint},
+};
+
+// Reads the TransferFeeConfig extension of a Token-2022 mint.
+// SPL Token mints and Token-2022 mints without the extension yield None.
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)
+        .map_err(|_| error!(RfqError::InvalidMintData))?;
+    Ok(state.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+// Snapshots the fee in effect at the current epoch, or None when the mint has no transfer fee.
+pub fn snapshot_transfer_fee(mint: &AccountInfo) -> Result<Option<TransferFeeSnapshot>> {
+    let Some(config) = transfer_fee_config(mint)? else {
+        return Ok(None);
+    };
+    let epoch = Clock::get()?.epoch;
+    let fee = config.get_epoch_fee(epoch);
+    Ok(Some(TransferFeeSnapshot {
+        transfer_fee_basis_points: u16::from(fee.transfer_fee_basis_points),
+        maximum_fee: u64::from(fee.maximum_fee),
+        epoch,
+    }))
+}
+
+// Applies the transfer-fee policy to a mint and returns its fee snapshot.
+// Reject refuses any non-zero fee, current or already scheduled (newer_transfer_fee).
+pub fn check_transfer_fee_policy(
+    mint: &AccountInfo,
+    policy: TransferFeePolicy,
+) -> Result<Option<TransferFeeSnapshot>> {
+    if policy == TransferFeePolicy::Reject {
+        if let Some(config) = transfer_fee_config(mint)? {
+            let charges_fee = u16::from(config.older_transfer_fee.transfer_fee_basis_points) > 0
+                || u16::from(config.newer_transfer_fee.transfer_fee_basis_points) > 0;
+            require!(!charges_fee, RfqError::TransferFeeMintRejected);
+        }
+    }
+    snapshot_transfer_fee(mint)
+}
+
+// Refuses a mint whose fee at the current epoch exceeds the snapshot taken at
+// selection: the vaults were funded and the legs agreed under that fee, so a fee
+// raised (or scheduled, under Reject) since then must not be charged at completion.
+pub fn check_transfer_fee_unchanged(
+    mint: &AccountInfo,
+    snapshot: Option<TransferFeeSnapshot>,
+) -> Result<()> {
+    let Some(live) = snapshot_transfer_fee(mint)? else {
+        return Ok(());
+    };
+    if live.transfer_fee_basis_points == 0 {
+        return Ok(());
+    }
+    let (agreed_bps, agreed_maximum_fee) = snapshot.map_or((0, 0), |fee| {
+        (fee.transfer_fee_basis_points, fee.maximum_fee)
+    });
+    require!(
+        live.transfer_fee_basis_points <= agreed_bps && live.maximum_fee <= agreed_maximum_fee,
+        RfqError::TransferFeeChanged
+    );
+    Ok(())
+}
+
+// Amount the sender must transfer so the receiver gets exactly `net_amount`
+// under the fee in effect at the current epoch. Identity for fee-less mints.
+pub fn gross_up(mint: &AccountInfo, net_amount: u64) -> Result<u64> {
+    let Some(config) = transfer_fee_config(mint)? else {
+        return Ok(net_amount);
+    };
+    if net_amount == 0 {
+        return Ok(0);
+    }
+    let epoch = Clock::get()?.epoch;
+    config
+        .get_epoch_fee(epoch)
+        .calculate_pre_fee_amount(net_amount)
+        .ok_or_else(|| error!(RfqError::ArithmeticOverflow))
+}
+
+// Fees withheld in a Token-2022 account block CloseAccount; harvesting them
+// to the mint is permissionless. No-op for accounts without withheld fees.
+pub fn harvest_withheld_fees<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+) -> Result<()> {
+    if *token_account.owner != anchor_spl::token_2022::ID {
+        return Ok(());
+    }
+    let withheld = {
+        let data = token_account.try_borrow_data()?;
+        let state = StateWithExtensions::<TokenAccountState>::unpack(&data)
+            .map_err(|_| error!(RfqError::InvalidMintData))?;
+        state
+            .get_extension::<TransferFeeAmount>()
+            .map(|fees| u64::from(fees.withheld_amount))
+            .unwrap_or(0)
+    };
+    if withheld == 0 {
+        return Ok(());
+    }
+    let ix = spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+        token_program.key,
+        mint.key,
+        &[token_account.key],
+    )?;
+    invoke(&ix, &[token_program.clone(), mint.clone(), token_account.clone()])?;
+    Ok(())
+}
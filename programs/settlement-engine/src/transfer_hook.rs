@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_hook, StateWithExtensions},
+    state::Mint,
+};
+use anchor_spl::token_interface::{self, TransferChecked};
+
+// Whether the mint is a Token-2022 mint with a transfer-hook program set.
+fn has_transfer_hook(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(false);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    Ok(transfer_hook::get_program_id(&state).is_some())
+}
+
+// Drop-in replacement for `token_interface::transfer_checked` on base/quote legs.
+// For transfer-hook mints, the hook program, its extra-account-metas PDA and the
+// extra accounts it lists are looked up by key in `ctx.remaining_accounts` and
+// appended to the CPI. Other mints go through the regular CPI unchanged.
+pub fn transfer_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    if !has_transfer_hook(&ctx.accounts.mint)? {
+        return token_interface::transfer_checked(ctx, amount, decimals);
+    }
+    spl_token_2022::onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
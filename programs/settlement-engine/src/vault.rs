@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::{self, Create};
+
+// Creates an RFQ vault (the RFQ's associated token account for a mint) unless it already
+// exists, and records the payer in `vault_payer` only when it was created here.
+// Anyone can create an ATA for the RFQ beforehand: close_rfq must not hand that rent
+// to whoever happened to pass the account next, so a pre-existing vault keeps its record.
+pub fn init_vault_if_needed<'info>(
+    associated_token_program: &AccountInfo<'info>,
+    accounts: Create<'info>,
+    vault_payer: &mut Option<Pubkey>,
+) -> Result<()> {
+    if !accounts.associated_token.data_is_empty() {
+        return Ok(());
+    }
+    let payer = accounts.payer.key();
+    associated_token::create(CpiContext::new(associated_token_program.clone(), accounts))?;
+    *vault_payer = Some(payer);
+    Ok(())
+}
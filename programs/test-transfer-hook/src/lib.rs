@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+// Test fixture only: a Token-2022 transfer hook that requires one extra account
+// (a per-mint counter PDA) and counts the transfers it sees, so the tests can
+// check that settlement-engine forwards hook accounts on every leg.
+declare_id!("55RzDznxCKLaraXUpGHMoc271GRunNX2fWMckd9FVSjG");
+
+#[program]
+pub mod test_transfer_hook {
+    use super::*;
+
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        // the counter PDA is derived from the mint (index 1 of the Execute accounts)
+        let extra_account_metas = [ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: Counter::SEED_PREFIX.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )?];
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas,
+        )?;
+
+        ctx.accounts.counter.transfers = 0;
+        ctx.accounts.counter.bump = ctx.bumps.counter;
+        Ok(())
+    }
+
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+        ctx.accounts.counter.transfers = ctx
+            .accounts
+            .counter
+            .transfers
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: TLV account holding the extra account metas, written above
+    #[account(
+        init,
+        payer = payer,
+        space = ExtraAccountMetaList::size_of(1)?,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [Counter::SEED_PREFIX, mint.key().as_ref()],
+        bump,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account order is fixed by the transfer-hook interface's Execute instruction
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    /// CHECK: source token account, validated by Token-2022
+    pub source: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: destination token account, validated by Token-2022
+    pub destination: UncheckedAccount<'info>,
+    /// CHECK: source owner or delegate, validated by Token-2022
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: extra account metas PDA
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [Counter::SEED_PREFIX, mint.key().as_ref()],
+        bump = counter.bump,
+    )]
+    pub counter: Account<'info, Counter>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Counter {
+    pub transfers: u64,
+    pub bump: u8,
+}
+
+impl Counter {
+    pub const SEED_PREFIX: &'static [u8] = b"counter";
+}
@@ -3,6 +3,8 @@ import { Program } from "@coral-xyz/anchor";
 import { SettlementEngine } from "../target/types/settlement_engine";
 import { PublicKey, SystemProgram, Keypair } from "@solana/web3.js";
 import assert from "assert";
+import { liquidityGuard } from "./utils/liquidityGuard";
+import { waitForChainTime } from "./utils/time";
 
 anchor.setProvider(anchor.AnchorProvider.env());
 const provider = anchor.getProvider() as anchor.AnchorProvider;
@@ -42,11 +44,11 @@ describe("CONFIG", () => {
 
     const usdcMint = Keypair.generate().publicKey; // placeholder mint
     const treasury = Keypair.generate().publicKey;
-    const liquidityGuard = new PublicKey("5gfPFweV3zJovznZqBra3rv5tWJ5EHVzQY1PqvNA4HGg");
 
-    // init_config (admin is both payer and signer)
+    // init_config (admin is both payer and signer), with a short timelock for the test
+    const TIMELOCK_SECS = 5;
     await program.methods
-      .initConfig(usdcMint, treasury, liquidityGuard, null)
+      .initConfig(usdcMint, treasury, liquidityGuard, null, null, null, TIMELOCK_SECS, null)
       .accounts({
         admin: admin.publicKey,
       })
@@ -59,28 +61,142 @@ describe("CONFIG", () => {
     assert(cfg1.treasuryWallet.equals(treasury));
     assert(cfg1.liquidityGuard.equals(liquidityGuard));
     assert(cfg1.facilitatorFeeBps === 1000); // default 10%
+    assert("reject" in cfg1.transferFeePolicy); // default policy
     console.log("stored admin pubkey:", cfg1.admin.toBase58());
 
-    // update_config (must be signed by current admin)
+    assert(cfg1.pendingAdmin === null);
+    assert(cfg1.feeManager.equals(admin.publicKey)); // every role starts with the admin
+    assert(cfg1.guardManager.equals(admin.publicKey));
+    assert(cfg1.pauser.equals(admin.publicKey));
+    assert(cfg1.timelockSecs === TIMELOCK_SECS);
+    assert(cfg1.pendingUpdate === null);
+    assert(cfg1.liveRfqCount.isZero());
+    assert(cfg1.generation.gtn(0)); // bumped by every init_config
+
+    // two-step admin transfer: propose (current admin) then accept (new admin)
     await program.methods
-      .updateConfig(newAdmin.publicKey, null, null, null, null)
+      .proposeAdmin(newAdmin.publicKey)
       .accounts({ admin: admin.publicKey, config: cfgPda })
       .signers([admin])
       .rpc();
 
+    const cfgProposed = await program.account.config.fetch(cfgPda);
+    assert(cfgProposed.admin.equals(admin.publicKey), "admin should not change before accept");
+    assert(cfgProposed.pendingAdmin!.equals(newAdmin.publicKey));
+
+    let failed = false;
+    try {
+      await program.methods
+        .acceptAdmin()
+        .accounts({ newAdmin: admin.publicKey, config: cfgPda })
+        .signers([admin])
+        .rpc();
+    } catch { failed = true; }
+    assert(failed, "accept_admin should fail for anyone but the pending admin");
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({ newAdmin: newAdmin.publicKey, config: cfgPda })
+      .signers([newAdmin])
+      .rpc();
+
     const cfg2 = await program.account.config.fetch(cfgPda);
     assert(cfg2.admin.equals(newAdmin.publicKey));
+    assert(cfg2.pendingAdmin === null);
     console.log("rotated admin pubkey:", newAdmin.publicKey.toBase58());
 
-    // rotate mint + treasury + liquidity guard with new admin
+    // roles are handed out by the admin; each gates its own fields
+    const feeManager = Keypair.generate();
+    const guardManager = Keypair.generate();
+    const pauser = Keypair.generate().publicKey;
+    failed = false;
+    try {
+      await program.methods
+        .setConfigRoles(feeManager.publicKey, guardManager.publicKey, pauser)
+        .accounts({ admin: admin.publicKey, config: cfgPda })
+        .signers([admin])
+        .rpc();
+    } catch { failed = true; }
+    assert(failed, "set_config_roles should fail for the previous admin");
+
+    await program.methods
+      .setConfigRoles(feeManager.publicKey, guardManager.publicKey, pauser)
+      .accounts({ admin: newAdmin.publicKey, config: cfgPda })
+      .signers([newAdmin])
+      .rpc();
+    const cfgRoles = await program.account.config.fetch(cfgPda);
+    assert(cfgRoles.feeManager.equals(feeManager.publicKey));
+    assert(cfgRoles.guardManager.equals(guardManager.publicKey));
+    assert(cfgRoles.pauser.equals(pauser));
+
+    // timelocked rotation of mint + treasury (admin), liquidity guard (guard manager) and fee (fee manager)
     const usdcMint2 = Keypair.generate().publicKey;
     const treasury2 = Keypair.generate().publicKey;
     const liquidityGuard2 = Keypair.generate().publicKey;
+    const queue = (
+      signer: Keypair,
+      usdc: PublicKey | null,
+      treasuryWallet: PublicKey | null,
+      guard: PublicKey | null,
+      feeBps: number | null,
+    ) => program.methods
+      .queueConfigUpdate(usdc, treasuryWallet, guard, feeBps, null, null)
+      .accounts({ authority: signer.publicKey, config: cfgPda })
+      .signers([signer])
+      .rpc();
+    // anyone may execute once the eta is reached
+    const executeWhenDue = async () => {
+      const { pendingUpdate } = await program.account.config.fetch(cfgPda);
+      await waitForChainTime(provider.connection, pendingUpdate!.eta.toNumber(), "config timelock");
+      await program.methods
+        .executeConfigUpdate()
+        .accounts({ executor: provider.wallet.publicKey, config: cfgPda })
+        .rpc();
+    };
+
+    failed = false;
+    try { await queue(newAdmin, null, null, liquidityGuard2, null); } catch { failed = true; }
+    assert(failed, "only the guard manager may rotate the liquidity guard");
+
+    await queue(newAdmin, usdcMint2, treasury2, null, null);
+    const cfgQueued = await program.account.config.fetch(cfgPda);
+    assert(cfgQueued.pendingUpdate!.usdcMint.equals(usdcMint2));
+    assert(cfgQueued.usdcMint.equals(usdcMint), "queued change should not apply yet");
+
+    failed = false;
+    try { await queue(guardManager, null, null, liquidityGuard2, null); } catch { failed = true; }
+    assert(failed, "a second update cannot be queued while one is pending");
+
+    failed = false;
+    try {
+      await program.methods
+        .executeConfigUpdate()
+        .accounts({ executor: provider.wallet.publicKey, config: cfgPda })
+        .rpc();
+    } catch { failed = true; }
+    assert(failed, "execute_config_update should fail before the eta");
+    await executeWhenDue();
+
+    // the admin may cancel a change queued by another role
+    await queue(guardManager, null, null, Keypair.generate().publicKey, null);
     await program.methods
-      .updateConfig(null, usdcMint2, treasury2, liquidityGuard2, 2000)
-      .accounts({ admin: newAdmin.publicKey, config: cfgPda })
+      .cancelConfigUpdate()
+      .accounts({ authority: newAdmin.publicKey, config: cfgPda })
       .signers([newAdmin])
       .rpc();
+    assert((await program.account.config.fetch(cfgPda)).pendingUpdate === null);
+
+    await queue(guardManager, null, null, liquidityGuard2, null);
+    await executeWhenDue();
+    await queue(feeManager, null, null, null, 2000);
+    await executeWhenDue();
+
+    // the transfer-fee policy and keeper bounty change instantly
+    await program.methods
+      .updateConfig({ grossUp: {} }, null)
+      .accounts({ authority: feeManager.publicKey, config: cfgPda })
+      .signers([feeManager])
+      .rpc();
 
     const cfg3 = await program.account.config.fetch(cfgPda);
     assert(cfg3.usdcMint.equals(usdcMint2));
@@ -89,19 +205,46 @@ describe("CONFIG", () => {
     assert(cfg3.liquidityGuard.equals(liquidityGuard2));
     assert(!cfg3.liquidityGuard.equals(liquidityGuard));
     assert(cfg3.facilitatorFeeBps === 2000); // 20%
+    assert("grossUp" in cfg3.transferFeePolicy);
     console.log("usdc mint:", cfg3.usdcMint.toBase58());
     console.log("treasury:", cfg3.treasuryWallet.toBase58());
     console.log("liquidity guard:", cfg3.liquidityGuard.toBase58());
 
-    let failed = false;
+    failed = false;
+    try { await queue(feeManager, null, null, null, 20000); } catch { failed = true; } // invalid fee bps
+    assert(failed, "queue_config_update should fail with invalid fee bps");
+
+    failed = false;
     try {
       await program.methods
-        .updateConfig(null, null, null, null, 20000) // invalid fee bps
-        .accounts({ admin: newAdmin.publicKey, config: cfgPda })
-        .signers([newAdmin])
+        .updateConfig(null, 1001) // above MAX_KEEPER_BOUNTY_BPS
+        .accounts({ authority: feeManager.publicKey, config: cfgPda })
+        .signers([feeManager])
         .rpc();
     } catch { failed = true; }
-    assert(failed, "update_config should fail with invalid fee bps");
+    assert(failed, "update_config should fail with invalid keeper bounty bps");
+
+    // an empty update is rejected, whoever signs it
+    failed = false;
+    try {
+      await program.methods
+        .updateConfig(null, null)
+        .accounts({ authority: feeManager.publicKey, config: cfgPda })
+        .signers([feeManager])
+        .rpc();
+    } catch (e) {
+      failed = true;
+      assert(String(e).includes("InvalidParams"), String(e));
+    }
+    assert(failed, "update_config should reject an empty update");
+
+    await program.methods
+      .updateConfig(null, 500)
+      .accounts({ authority: feeManager.publicKey, config: cfgPda })
+      .signers([feeManager])
+      .rpc();
+    const cfg4 = await program.account.config.fetch(cfgPda);
+    assert(cfg4.keeperBountyBps === 500);
     
     // close_config (must be signed by current admin)
     await program.methods
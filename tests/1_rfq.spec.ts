@@ -1,7 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { SettlementEngine } from "../target/types/settlement_engine";
-import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { ComputeBudgetProgram, Ed25519Program, Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
 import {
     createMint,
     getAssociatedTokenAddressSync,
@@ -11,8 +11,15 @@ import {
     getOrCreateAssociatedTokenAccount,
 } from "@solana/spl-token";
 import { v4 as uuidv4, parse as uuidParse } from "uuid";
+import nacl from "tweetnacl";
 import assert from "assert";
 import { expect } from "chai";
+import { createTransferFeeMint } from "./utils/transferFee";
+import { cpiEvents } from "./utils/events";
+import { allowlistRoot } from "./utils/allowlist";
+import { checkLiquidity, liquidityGuard, waitForLiquidityGuardReady } from "./utils/liquidityGuard";
+import { waitForChainTime } from "./utils/time";
+import { selectionPauseProof, setPauseAccounts } from "./utils/selectionPause";
 
 anchor.setProvider(anchor.AnchorProvider.env());
 const provider = anchor.getProvider() as anchor.AnchorProvider;
@@ -52,6 +59,8 @@ describe("RFQ", () => {
     const admin = Keypair.generate();
     let configPda: PublicKey;
     let usdcMint: PublicKey;
+    let baseMint: PublicKey;
+    let quoteMint: PublicKey;
     let treasury: PublicKey;
 
     before(async () => {
@@ -65,6 +74,9 @@ describe("RFQ", () => {
             null,                  // freeze authority
             6                      // decimals
         );
+        // base/quote mints are checked against the transfer-fee policy at init_rfq
+        [baseMint, quoteMint] = await Promise.all(
+            [9, 9].map(d => createMint(provider.connection, admin, admin.publicKey, null, d)));
 
         // 2) Ensure config exists and points to that mint
         [configPda] = PublicKey.findProgramAddressSync(
@@ -78,9 +90,8 @@ describe("RFQ", () => {
         } catch { needInit = true; }
         if (needInit) {
             treasury = Keypair.generate().publicKey;
-            const liquidityGuard = new PublicKey("5gfPFweV3zJovznZqBra3rv5tWJ5EHVzQY1PqvNA4HGg");
             await program.methods
-                .initConfig(usdcMint, treasury, liquidityGuard, null)
+                .initConfig(usdcMint, treasury, liquidityGuard, null, null, null, null, null)
                 .accounts({ admin: admin.publicKey })
                 .signers([admin])
                 .rpc();
@@ -88,11 +99,26 @@ describe("RFQ", () => {
     });
 
     after(async () => {
-        await program.methods
-            .closeConfig()
+        // RFQs opened by these tests are still live: close_config refuses, the forced path does not
+        expect((await program.account.config.fetch(configPda)).liveRfqCount.toNumber()).to.be.greaterThan(0);
+        let err: any;
+        try {
+            await program.methods
+                .closeConfig()
+                .accounts({ admin: admin.publicKey, config: configPda })
+                .signers([admin])
+                .rpc();
+        } catch (e) { err = e; }
+        expect(String(err)).to.contain("LiveRfqsRemaining");
+
+        const sig = await program.methods
+            .forceCloseConfig()
             .accounts({ admin: admin.publicKey, config: configPda })
             .signers([admin])
             .rpc();
+        const forced = (await cpiEvents(program, sig)).find(e => e.name === "configForceClosed");
+        assert.ok(forced, "ConfigForceClosed should be emitted");
+        expect(forced.data.liveRfqCount.toNumber()).to.be.greaterThan(0);
     });
 
     it("creates RFQ PDA with uuid and stores fields", async () => {
@@ -104,10 +130,6 @@ describe("RFQ", () => {
         const u = uuidBytes();
         const [rfqAddr, bump] = rfqPda(maker.publicKey, u);
 
-
-        const baseMint = Keypair.generate().publicKey;
-        const quoteMint = Keypair.generate().publicKey;
-
         const commitTTL = 60, revealTTL = 60, selectionTTL = 60, fundingTTL = 60;
 
         const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
@@ -132,7 +154,7 @@ describe("RFQ", () => {
             1_000_000 //sufficient for bond
         );
 
-        await program.methods
+        const initSig = await program.methods
             .initRfq(
                 Array.from(u),
                 baseMint,
@@ -145,9 +167,20 @@ describe("RFQ", () => {
                 revealTTL,
                 selectionTTL,
                 fundingTTL,
-                facilitator.publicKey
+                facilitator.publicKey,
+                false,
+                false,
+                false,
+                { firstPrice: {} },
+                { sell: {} },
+                new anchor.BN(0),
+                { base: {} },
+                new anchor.BN(0),
+                false,
+                null
             )
             .accounts({
+                baseMint, quoteMint,
                 maker: maker.publicKey,
                 config: configPda,
                 usdcMint,
@@ -175,6 +208,11 @@ describe("RFQ", () => {
         assert.strictEqual(rfq.revealTtlSecs, revealTTL);
         assert.strictEqual(rfq.selectionTtlSecs, selectionTTL);
         assert.strictEqual(rfq.fundTtlSecs, fundingTTL);
+        assert.strictEqual(rfq.allowPartialFills, false);
+        assert.strictEqual(rfq.autoSelectBest, false);
+        assert.strictEqual(rfq.requireBestQuote, false);
+        assert.ok("sell" in rfq.side, "side should default to Sell in tests");
+        assert.strictEqual(rfq.bestQuote, null);
         assert(rfq.bondsEscrow.equals(bondsEscrow), "bonds_escrow mismatch");
         assert(rfq.makerPaymentAccount.equals(makerPaymentAccount), "maker_payment_ata mismatch");
         expect(rfq.state).to.have.property('draft');
@@ -190,6 +228,14 @@ describe("RFQ", () => {
         assert(rfq.openedAt === null, "opened_at should be null");
         assert(rfq.selectedAt === null, "selected_at should be null");
         assert(rfq.completedAt === null, "completed_at should be null");
+
+        const events = await cpiEvents(program, initSig);
+        const initialized = events.find(e => e.name === "rfqInitialized");
+        assert.ok(initialized, "RfqInitialized should be emitted");
+        assert(initialized.data.rfq.equals(rfqAddr), "event rfq mismatch");
+        assert(initialized.data.maker.equals(maker.publicKey), "event maker mismatch");
+        assert.ok(initialized.data.baseAmount.eq(rfq.baseAmount), "event base amount mismatch");
+        assert.ok(initialized.data.createdAt.eq(rfq.createdAt), "event created_at mismatch");
     });
 
     it("rejects re-init with same (maker, uuid) PDA", async () => {
@@ -197,8 +243,6 @@ describe("RFQ", () => {
         await fund(maker);
 
         const u = uuidBytes();
-        const baseMint = Keypair.generate().publicKey;
-        const quoteMint = Keypair.generate().publicKey;
 
         const [rfqAddr, bump] = rfqPda(maker.publicKey, u);
         const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
@@ -227,8 +271,8 @@ describe("RFQ", () => {
             .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                 new anchor.BN(1_000_000_000),
                 new anchor.BN(1_000_000_000),
-                1000, 1, 1, 1, 1, null)
-            .accounts({ maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, })
+                1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+            .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
             .signers([maker])
             .rpc();
 
@@ -239,8 +283,8 @@ describe("RFQ", () => {
                 .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                     new anchor.BN(1_000_000_000),
                     new anchor.BN(1_000_000_000),
-                    1000, 1, 1, 1, 1, null)
-                .accounts({ maker: maker.publicKey, config: configPda, usdcMint })
+                    1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, tokenProgram: TOKEN_PROGRAM_ID })
                 .signers([maker])
                 .rpc();
         } catch {
@@ -249,13 +293,241 @@ describe("RFQ", () => {
         assert(failed, "re-initialization with same (maker, uuid) should fail");
     });
 
+    it("rejects a transfer-fee base mint under the default Reject policy", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+
+        const u = uuidBytes();
+        const baseMint = await createTransferFeeMint(provider.connection, admin, 100, BigInt(1_000_000));
+
+        const [rfqAddr] = rfqPda(maker.publicKey, u);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey)
+            .then(a => mintTo(provider.connection, admin, usdcMint, a.address, admin, 1_000_000));
+
+        let failed = false;
+        try {
+            await program.methods
+                .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
+                    new anchor.BN(1_000_000_000),
+                    new anchor.BN(1_000_000_000),
+                    1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({
+                    maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount,
+                    quoteMint, tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+        } catch (e) {
+            failed = true;
+            expect(String(e)).to.contain("TransferFeeMintRejected");
+        }
+        assert(failed, "init_rfq should reject a base mint with a transfer fee");
+    });
+
+    it("rejects partial fills combined with auto-best selection", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+
+        const u = uuidBytes();
+        const [rfqAddr] = rfqPda(maker.publicKey, u);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey);
+
+        let failed = false;
+        try {
+            await program.methods
+                .initRfq(Array.from(u) as any, baseMint, quoteMint,
+                    new anchor.BN(1_000_000),
+                    new anchor.BN(1_000_000_000),
+                    new anchor.BN(1_000_000_000),
+                    1000, 1, 1, 1, 1, null, true, true, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
+                .signers([maker])
+                .rpc();
+        } catch (e) {
+            failed = true;
+            expect(String(e)).to.contain("InvalidSelectionMode");
+        }
+        assert(failed, "init_rfq should reject partial fills with auto-best selection");
+    });
+
+    it("rejects a buy RFQ without a max quote amount", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+
+        const u = uuidBytes();
+        const [rfqAddr] = rfqPda(maker.publicKey, u);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey);
+
+        let failed = false;
+        try {
+            await program.methods
+                .initRfq(Array.from(u) as any, baseMint, quoteMint,
+                    new anchor.BN(1_000_000),
+                    new anchor.BN(1_000_000_000),
+                    new anchor.BN(1),
+                    1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { buy: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
+                .signers([maker])
+                .rpc();
+        } catch (e) {
+            failed = true;
+            expect(String(e)).to.contain("InvalidMaxQuoteAmount");
+        }
+        assert(failed, "init_rfq should require a max quote amount when buying");
+    });
+
+    it("bounds a buy RFQ by its max quote amount only", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+
+        const u = uuidBytes();
+        const [rfqAddr] = rfqPda(maker.publicKey, u);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey);
+
+        // no quote floor is needed when buying
+        await program.methods
+            .initRfq(Array.from(u) as any, baseMint, quoteMint,
+                new anchor.BN(1_000_000),
+                new anchor.BN(1_000_000_000),
+                new anchor.BN(0),
+                1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { buy: {} }, new anchor.BN(1_000_000), { base: {} }, new anchor.BN(0), false, null)
+            .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
+            .signers([maker])
+            .rpc();
+
+        const updateMaxQuoteAmount = (amount: number) => program.methods
+            .updateRfq(null, null, null, null, null, null, null, null, null, null, null, null, new anchor.BN(amount), null)
+            .accounts({ maker: maker.publicKey, rfq: rfqAddr })
+            .signers([maker])
+            .rpc();
+
+        let failed = false;
+        try {
+            await updateMaxQuoteAmount(0);
+        } catch (e) {
+            failed = true;
+            expect(String(e)).to.contain("InvalidMaxQuoteAmount");
+        }
+        assert(failed, "update_rfq should keep a max quote amount on buy RFQs");
+
+        await updateMaxQuoteAmount(2_000_000);
+        const rfq = await program.account.rfq.fetch(rfqAddr);
+        assert(rfq.maxQuoteAmount.eq(new anchor.BN(2_000_000)), "max quote amount should be updated");
+        assert(rfq.minQuoteAmount.isZero(), "buy RFQs carry no quote floor");
+    });
+
+    it("rejects a sell-side quote-size RFQ", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+
+        const u = uuidBytes();
+        const [rfqAddr] = rfqPda(maker.publicKey, u);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey);
+
+        let failed = false;
+        try {
+            await program.methods
+                .initRfq(Array.from(u) as any, baseMint, quoteMint,
+                    new anchor.BN(1_000_000),
+                    new anchor.BN(1_000_000_000),
+                    new anchor.BN(1),
+                    1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { quote: {} }, new anchor.BN(1_000_000), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
+                .signers([maker])
+                .rpc();
+        } catch (e) {
+            failed = true;
+            expect(String(e)).to.contain("InvalidSizeMode");
+        }
+        assert(failed, "quote-size RFQs must be buy-side");
+    });
+
+    it("updates the quote size of a quote-size RFQ only", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+
+        const initRfq = async (sizeMode: object, quoteSize: number) => {
+            const u = uuidBytes();
+            const [rfqAddr] = rfqPda(maker.publicKey, u);
+            const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+            await program.methods
+                .initRfq(Array.from(u) as any, baseMint, quoteMint,
+                    new anchor.BN(1_000_000),
+                    new anchor.BN(1_000_000_000),
+                    new anchor.BN(0),
+                    1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { buy: {} }, new anchor.BN(1_000_000), sizeMode, new anchor.BN(quoteSize), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
+                .signers([maker])
+                .rpc();
+            return rfqAddr;
+        };
+        const updateQuoteSize = (rfqAddr: PublicKey, quoteSize: number) => program.methods
+            .updateRfq(null, null, null, null, null, null, null, null, null, null, null, null, null, new anchor.BN(quoteSize))
+            .accounts({ maker: maker.publicKey, rfq: rfqAddr })
+            .signers([maker])
+            .rpc();
+
+        const quoteSized = await initRfq({ quote: {} }, 1_000_000);
+        const baseSized = await initRfq({ base: {} }, 0);
+        for (const [rfqAddr, quoteSize, expected] of [
+            [quoteSized, 0, "InvalidQuoteSize"],
+            [baseSized, 2_000_000, "InvalidSizeMode"],
+        ] as const) {
+            let err: any;
+            try { await updateQuoteSize(rfqAddr, quoteSize); } catch (e) { err = e; }
+            expect(String(err)).to.contain(expected);
+        }
+
+        await updateQuoteSize(quoteSized, 2_000_000);
+        const rfq = await program.account.rfq.fetch(quoteSized);
+        assert(rfq.quoteSize.eq(new anchor.BN(2_000_000)), "quote size should be updated");
+    });
+
+    it("rejects a firm RFQ with partial fills", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+
+        const u = uuidBytes();
+        const [rfqAddr] = rfqPda(maker.publicKey, u);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey);
+
+        let failed = false;
+        try {
+            await program.methods
+                .initRfq(Array.from(u) as any, baseMint, quoteMint,
+                    new anchor.BN(1_000_000),
+                    new anchor.BN(1_000_000_000),
+                    new anchor.BN(1),
+                    1000, 1, 1, 1, 1, null, true, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), true, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
+                .signers([maker])
+                .rpc();
+        } catch (e) {
+            failed = true;
+            expect(String(e)).to.contain("InvalidFirmMode");
+        }
+        assert(failed, "firm RFQs are single-winner");
+    });
+
     it("rejects RFQ with 0 bond/fee/base/quote", async () => {
         const maker = Keypair.generate();
         await fund(maker);
 
         const u = uuidBytes();
-        const baseMint = Keypair.generate().publicKey;
-        const quoteMint = Keypair.generate().publicKey;
 
         const [rfqAddr, bump] = rfqPda(maker.publicKey, u);
         const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
@@ -286,8 +558,8 @@ describe("RFQ", () => {
                 .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(0),
                     new anchor.BN(1_000_000_000),
                     new anchor.BN(1_000_000_000),
-                    1000, 1, 1, 1, 1, null)
-                .accounts({ maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount })
+                    1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
                 .signers([maker])
                 .rpc();
         } catch {
@@ -301,8 +573,8 @@ describe("RFQ", () => {
                 .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                     new anchor.BN(0),
                     new anchor.BN(1_000_000_000),
-                    1000, 1, 1, 1, 1, null)
-                .accounts({ maker: maker.publicKey, config: configPda, usdcMint })
+                    1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, tokenProgram: TOKEN_PROGRAM_ID })
                 .signers([maker])
                 .rpc();
         } catch {
@@ -316,8 +588,8 @@ describe("RFQ", () => {
                 .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                     new anchor.BN(1_000_000_000),
                     new anchor.BN(0),
-                    1000, 1, 1, 1, 1, null)
-                .accounts({ maker: maker.publicKey, config: configPda, usdcMint })
+                    1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, tokenProgram: TOKEN_PROGRAM_ID })
                 .signers([maker])
                 .rpc();
         } catch {
@@ -331,8 +603,8 @@ describe("RFQ", () => {
                 .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                     new anchor.BN(1_000_000_000),
                     new anchor.BN(1_000_000_000),
-                    10001, 1, 1, 1, 1, null)
-                .accounts({ maker: maker.publicKey, config: configPda, usdcMint })
+                    10001, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, tokenProgram: TOKEN_PROGRAM_ID })
                 .signers([maker])
                 .rpc();
         } catch {
@@ -351,8 +623,6 @@ describe("RFQ", () => {
         const [pdaB] = rfqPda(makerB.publicKey, u);
         assert(!pdaA.equals(pdaB), "PDAs should differ across makers for same uuid");
 
-        const baseMint = Keypair.generate().publicKey;
-        const quoteMint = Keypair.generate().publicKey;
 
         const bondsEscrowRfq1 = getAssociatedTokenAddressSync(usdcMint, pdaA, true);
         const bondsEscrowRfq2 = getAssociatedTokenAddressSync(usdcMint, pdaB, true);
@@ -400,8 +670,8 @@ describe("RFQ", () => {
             .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                 new anchor.BN(1_000_000_000),
                 new anchor.BN(1_000_000_000),
-                1000, 1, 1, 1, 1, null)
-            .accounts({ maker: makerA.publicKey, config: configPda, usdcMint, bondsEscrow: bondsEscrowRfq1, makerPaymentAccount: makerAPaymentAccount })
+                1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+            .accounts({ baseMint, quoteMint, maker: makerA.publicKey, config: configPda, usdcMint, bondsEscrow: bondsEscrowRfq1, makerPaymentAccount: makerAPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
             .signers([makerA])
             .rpc();
 
@@ -409,8 +679,8 @@ describe("RFQ", () => {
             .initRfq(Array.from(u) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                 new anchor.BN(1_000_000_000),
                 new anchor.BN(1_000_000_000),
-                1000, 1, 1, 1, 1, null)
-            .accounts({ maker: makerB.publicKey, config: configPda, usdcMint, bondsEscrow: bondsEscrowRfq2, makerPaymentAccount: makerBPaymentAccount })
+                1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+            .accounts({ baseMint, quoteMint, maker: makerB.publicKey, config: configPda, usdcMint, bondsEscrow: bondsEscrowRfq2, makerPaymentAccount: makerBPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
             .signers([makerB])
             .rpc();
 
@@ -430,8 +700,6 @@ describe("RFQ", () => {
         const maker = Keypair.generate();
         await fund(maker);
 
-        const baseMint = Keypair.generate().publicKey;
-        const quoteMint = Keypair.generate().publicKey;
 
         // uuid 1
         const u1 = uuidBytes();
@@ -463,8 +731,8 @@ describe("RFQ", () => {
             .initRfq(Array.from(u1) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                 new anchor.BN(1_000_000_000),
                 new anchor.BN(1_000_000_000),
-                1000, 1, 1, 1, 1, null)
-            .accounts({ maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow: bondsEscrowRfq1, makerPaymentAccount })
+                1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+            .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow: bondsEscrowRfq1, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
             .signers([maker])
             .rpc();
 
@@ -479,8 +747,8 @@ describe("RFQ", () => {
             .initRfq(Array.from(u2) as any, baseMint, quoteMint, new anchor.BN(1_000_000),
                 new anchor.BN(1_000_000_000),
                 new anchor.BN(1_000_000_000),
-                1000, 1, 1, 1, 1, null)
-            .accounts({ maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow: bondsEscrowRfq2, makerPaymentAccount })
+                1000, 1, 1, 1, 1, null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+            .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow: bondsEscrowRfq2, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
             .signers([maker])
             .rpc();
 
@@ -510,8 +778,6 @@ describe("RFQ", () => {
         const u = uuidBytes();
         const [rfqAddr, bump] = rfqPda(maker.publicKey, u);
 
-        const baseMint = Keypair.generate().publicKey;
-        const quoteMint = Keypair.generate().publicKey;
 
         const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
         const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
@@ -555,9 +821,20 @@ describe("RFQ", () => {
                 revealTTL,
                 selectionTTL,
                 fundingTTL,
-                facilitator.publicKey
+                facilitator.publicKey,
+                false,
+                false,
+                false,
+                { firstPrice: {} },
+                { sell: {} },
+                new anchor.BN(0),
+                { base: {} },
+                new anchor.BN(0),
+                false,
+                null
             )
             .accounts({
+                baseMint, quoteMint,
                 maker: maker.publicKey,
                 config: configPda,
                 usdcMint, bondsEscrow,
@@ -582,10 +859,16 @@ describe("RFQ", () => {
                 selectionTTL + 1,
                 null, //skip funding TTL update
                 null,
+                null,
+                null,
+                null,
             )
             .accounts({
                 maker: maker.publicKey,
                 rfq: rfqAddr,
+                // new mints are checked against the transfer-fee policy
+                baseMint: quoteMint,
+                quoteMint: baseMint,
             })
             .signers([maker])
             .rpc();
@@ -629,6 +912,9 @@ describe("RFQ", () => {
                 null,
                 null, //skip funding TTL update
                 { set: [facilitator2.publicKey] },
+                null,
+                null,
+                null,
             )
             .accounts({
                 maker: maker.publicKey,
@@ -654,6 +940,9 @@ describe("RFQ", () => {
                 null,
                 null, //skip funding TTL update
                 { clear: {} },
+                null,
+                null,
+                null,
             )
             .accounts({
                 maker: maker.publicKey,
@@ -665,6 +954,37 @@ describe("RFQ", () => {
         rfq = await program.account.rfq.fetch(rfqAddr);
         assert(!rfq.facilitator, "facilitator should be None after clearing");
 
+        // restrict to an allowlist: the event carries only the Merkle root
+        const root = Array.from(allowlistRoot([Keypair.generate().publicKey, Keypair.generate().publicKey]));
+        const updateAllowlist = (update: object) => program.methods
+            .updateRfq(null, null, null, null, null, null, null, null, null, null, null, update, null, null)
+            .accounts({ maker: maker.publicKey, rfq: rfqAddr })
+            .signers([maker])
+            .rpc();
+        const allowSig = await updateAllowlist({ set: [root] });
+        const allowEvent = (await cpiEvents(program, allowSig)).find(e => e.name === "rfqAllowlistUpdated");
+        expect(allowEvent.data.takerAllowlistRoot).to.deep.equal(root);
+        rfq = await program.account.rfq.fetch(rfqAddr);
+        expect(rfq.takerAllowlistRoot).to.deep.equal(root);
+
+        await updateAllowlist({ clear: {} });
+        rfq = await program.account.rfq.fetch(rfqAddr);
+        assert(!rfq.takerAllowlistRoot, "allowlist should be None after clearing");
+
+        // a new mint is held to the same transfer-fee policy as at init_rfq
+        const feeMint = await createTransferFeeMint(provider.connection, admin, 100, BigInt(1_000_000));
+        const updateBaseMint = (mintAccount: PublicKey | null) => program.methods
+            .updateRfq(feeMint, null, null, null, null, null, null, null, null, null, null, null, null, null)
+            .accounts({ maker: maker.publicKey, rfq: rfqAddr, baseMint: mintAccount, quoteMint: null })
+            .signers([maker])
+            .rpc();
+        for (const [mintAccount, expected] of [[null, "InvalidBaseMint"], [feeMint, "TransferFeeMintRejected"]] as const) {
+            let err: any;
+            try { await updateBaseMint(mintAccount); } catch (e) { err = e; }
+            expect(String(err)).to.contain(expected);
+        }
+        rfq = await program.account.rfq.fetch(rfqAddr);
+        assert(rfq.baseMint.equals(quoteMint), "base mint should be unchanged");
     });
 
     it("opens RFQ", async () => {
@@ -681,8 +1001,6 @@ describe("RFQ", () => {
         const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
         const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
 
-        const baseMint = Keypair.generate().publicKey;
-        const quoteMint = Keypair.generate().publicKey;
 
         console.log("maker:", maker.publicKey.toBase58());
         console.log("rfqAddr:", rfqAddr.toBase58());
@@ -726,9 +1044,20 @@ describe("RFQ", () => {
                 revealTTL,
                 selectionTTL,
                 fundingTTL,
-                facilitator.publicKey
+                facilitator.publicKey,
+                false,
+                false,
+                false,
+                { firstPrice: {} },
+                { sell: {} },
+                new anchor.BN(0),
+                { base: {} },
+                new anchor.BN(0),
+                false,
+                null
             )
             .accounts({
+                baseMint, quoteMint,
                 maker: maker.publicKey,
                 config: configPda,
                 usdcMint, bondsEscrow,
@@ -769,6 +1098,11 @@ describe("RFQ", () => {
         assert.strictEqual(rfq.commitTtlSecs, commitTTL);
         assert.strictEqual(rfq.revealTtlSecs, revealTTL);
         assert.strictEqual(rfq.selectionTtlSecs, selectionTTL);
+        // stamped with the current config generation, so its exit decrements this config's live count
+        assert.ok(rfq.configGeneration.eq((await program.account.config.fetch(configPda)).generation), "config generation mismatch");
+        const [configGenerationPda] = PublicKey.findProgramAddressSync([Buffer.from("config_generation")], program.programId);
+        const configGeneration = await program.account.configGeneration.fetch(configGenerationPda);
+        assert.ok(rfq.openNonce.eq(configGeneration.rfqOpenCount.subn(1)), "open nonce should be the last one handed out");
         assert.strictEqual(rfq.fundTtlSecs, fundingTTL);
         assert(rfq.bondsEscrow.equals(bondsEscrow), "bonds_escrow mismatch");
         assert(rfq.makerPaymentAccount.equals(makerPaymentAccount), "maker_payment_ata mismatch");
@@ -849,11 +1183,16 @@ describe("RFQ", () => {
                     revealTTL + 1,
                     selectionTTL + 1,
                     null, //skip funding TTL update
-                    null
+                    null,
+                    null,
+                    null,
+                    null,
                 )
                 .accounts({
                     maker: maker.publicKey,
                     rfq: rfqAddr,
+                    baseMint: quoteMint,
+                    quoteMint: baseMint,
                 })
                 .signers([maker])
                 .rpc();
@@ -879,6 +1218,314 @@ describe("RFQ", () => {
         assert(failed, "cancel on opened RFQ should fail");
     });
 
+    it("blocks init/open while paused and lets the pauser resume", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+        const u = uuidBytes();
+        const [rfqAddr] = rfqPda(maker.publicKey, u);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+        const makerPaymentAccount = await getOrCreateAssociatedTokenAccount(
+            provider.connection, admin, usdcMint, maker.publicKey
+        ).then(a => a.address);
+        await mintTo(provider.connection, admin, usdcMint, makerPaymentAccount, admin, 1_000_000);
+
+        const initRfq = () => program.methods
+            .initRfq(
+                Array.from(u), baseMint, quoteMint,
+                new anchor.BN(1_000_000), new anchor.BN(1_000_000_000), new anchor.BN(1_000_000_000),
+                1000, 60, 60, 60, 60,
+                null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0),
+                false,
+                null
+            )
+            .accounts({
+                baseMint, quoteMint,
+                maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([maker])
+            .rpc();
+        const openRfq = () => program.methods
+            .openRfq()
+            .accounts({
+                maker: maker.publicKey, rfq: rfqAddr, config: configPda, bondsEscrow, makerPaymentAccount, usdcMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([maker])
+            .rpc();
+        const setPause = async (paused: number) => program.methods
+            .setPause(paused)
+            .accounts({ pauser: admin.publicKey, config: configPda, ...await setPauseAccounts(program, configPda, paused) })
+            .signers([admin])
+            .rpc();
+        const setRfqPause = (paused: boolean) => program.methods
+            .setRfqPause(paused)
+            .accounts({ pauser: admin.publicKey, config: configPda, rfq: rfqAddr })
+            .signers([admin])
+            .rpc();
+
+        // PAUSE_INIT_RFQ (1 << 0)
+        const pauseSig = await setPause(1);
+        const [paused] = await cpiEvents(program, pauseSig);
+        expect(paused.name).to.equal("pauseUpdated");
+        expect(paused.data.paused).to.equal(1);
+        let err: any;
+        try { await initRfq(); } catch (e) { err = e; }
+        expect(String(err)).to.contain("ProtocolPaused");
+
+        // unknown flags are rejected, and only the pauser may pause
+        err = undefined;
+        try { await setPause(0x80); } catch (e) { err = e; }
+        expect(String(err)).to.contain("InvalidPauseFlags");
+        err = undefined;
+        try {
+            await program.methods.setPause(0)
+                .accounts({ pauser: maker.publicKey, config: configPda })
+                .signers([maker])
+                .rpc();
+        } catch (e) { err = e; }
+        expect(String(err)).to.contain("Unauthorized");
+
+        await setPause(0);
+        await initRfq();
+
+        // a paused RFQ cannot be opened until the pauser resumes it
+        await setRfqPause(true);
+        expect((await program.account.rfq.fetch(rfqAddr)).paused).to.equal(true);
+        err = undefined;
+        try { await openRfq(); } catch (e) { err = e; }
+        expect(String(err)).to.contain("RfqPaused");
+
+        await setRfqPause(false);
+        await openRfq();
+        const opened = await program.account.rfq.fetch(rfqAddr);
+        expect(opened.state).to.have.property('open');
+        // paused before it opened: the pause could not overlap its selection window
+        expect(opened.pausedSince).to.equal(null);
+        expect(opened.selectionWindowPaused).to.equal(false);
+    });
+
+    it("keeps every exit path open under PAUSE_ALL", async () => {
+        await waitForLiquidityGuardReady();
+        const BOND = 1_000_000, BASE = 1_000_000_000, QUOTE = 2_000_000, FEE_BPS = 1000;
+        const commitTTL = 8, revealTTL = 4, selectionTTL = 10, fundingTTL = 6;
+
+        const maker = Keypair.generate();
+        const [takerB, takerC, takerD] = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+        await Promise.all([maker, takerB, takerC, takerD].map(kp => fund(kp)));
+        const makerPaymentAccount = await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey)
+            .then(a => a.address);
+        await mintTo(provider.connection, admin, usdcMint, makerPaymentAccount, admin, 4 * BOND);
+        const makerBaseAccount = await getOrCreateAssociatedTokenAccount(provider.connection, admin, baseMint, maker.publicKey)
+            .then(a => a.address);
+        await mintTo(provider.connection, admin, baseMint, makerBaseAccount, admin, 2 * BASE);
+        for (const taker of [takerB, takerC, takerD]) {
+            await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, taker.publicKey)
+                .then(a => mintTo(provider.connection, admin, usdcMint, a.address, admin, BOND));
+            await getOrCreateAssociatedTokenAccount(provider.connection, admin, quoteMint, taker.publicKey)
+                .then(a => mintTo(provider.connection, admin, quoteMint, a.address, admin, 2 * QUOTE));
+        }
+
+        const quotePdaOf = (rfqAddr: PublicKey, taker: Keypair) => PublicKey.findProgramAddressSync(
+            [Buffer.from("quote"), rfqAddr.toBuffer(), taker.publicKey.toBuffer()], program.programId)[0];
+        const settlementPdaOf = (rfqAddr: PublicKey, taker: Keypair) => PublicKey.findProgramAddressSync(
+            [Buffer.from("settlement"), rfqAddr.toBuffer(), quotePdaOf(rfqAddr, taker).toBuffer()], program.programId)[0];
+        const commitGuardPdaOf = (commitHash: Buffer) => PublicKey.findProgramAddressSync(
+            [Buffer.from("commit-guard"), commitHash], program.programId)[0];
+
+        // A expires unrevealed, B is ignored, C settles and D is left incomplete
+        const openRfq = async () => {
+            const u = uuidBytes();
+            const [rfqAddr] = rfqPda(maker.publicKey, u);
+            const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
+            await program.methods
+                .initRfq(Array.from(u), baseMint, quoteMint,
+                    new anchor.BN(BOND), new anchor.BN(BASE), new anchor.BN(QUOTE),
+                    FEE_BPS, commitTTL, revealTTL, selectionTTL, fundingTTL,
+                    null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0), false, null)
+                .accounts({ baseMint, quoteMint, maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount, tokenProgram: TOKEN_PROGRAM_ID })
+                .signers([maker])
+                .rpc();
+            await program.methods.openRfq()
+                .accounts({ maker: maker.publicKey, rfq: rfqAddr, config: configPda, bondsEscrow, makerPaymentAccount, usdcMint, tokenProgram: TOKEN_PROGRAM_ID })
+                .signers([maker])
+                .rpc();
+            return { rfqAddr, bondsEscrow };
+        };
+        const [rfqA, rfqB, rfqC, rfqD] = [await openRfq(), await openRfq(), await openRfq(), await openRfq()];
+
+        const commit = async (taker: Keypair, rfqAddr: PublicKey) => {
+            const salt = nacl.sign.detached(Buffer.from(rfqAddr.toBytes()), taker.secretKey);
+            const response = await checkLiquidity({
+                rfq: rfqAddr.toBase58(),
+                program_id: program.programId.toBase58(),
+                rfq_open_nonce: (await program.account.rfq.fetch(rfqAddr)).openNonce.toString(),
+                taker: taker.publicKey.toBase58(),
+                salt: Buffer.from(salt).toString("hex"),
+                quote_mint: quoteMint.toBase58(),
+                quote_amount: new anchor.BN(QUOTE).toString(),
+                bond_amount_usdc: new anchor.BN(BOND).toString(),
+                taker_fee_bps: new anchor.BN(FEE_BPS).toString(),
+            });
+            if ("error" in response) throw new Error(`Liquidity Guard error: ${response.error}`);
+            const commitHash = Buffer.from(response.commit_hash, "hex");
+            const proof = Buffer.from(response.liquidity_proof, "hex");
+            const commitIx = await program.methods
+                .commitQuote(Array.from(commitHash), Array.from(proof), null, null, null, [])
+                .accounts({
+                    taker: taker.publicKey, rfq: rfqAddr, usdcMint, config: configPda,
+                    instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+                    takerPaymentAccount: getAssociatedTokenAddressSync(usdcMint, taker.publicKey),
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .instruction();
+            const tx = new anchor.web3.Transaction().add(
+                Ed25519Program.createInstructionWithPublicKey({
+                    publicKey: liquidityGuard.toBytes(),
+                    message: Buffer.from(response.attestation, "hex"),
+                    signature: proof,
+                }),
+                commitIx,
+            );
+            await provider.sendAndConfirm(tx, [taker]);
+            return { salt, commitHash };
+        };
+        const commits = [
+            [takerB, rfqB.rfqAddr, await commit(takerB, rfqB.rfqAddr)],
+            [takerC, rfqC.rfqAddr, await commit(takerC, rfqC.rfqAddr)],
+            [takerD, rfqD.rfqAddr, await commit(takerD, rfqD.rfqAddr)],
+        ] as const;
+
+        const deadlines = async (rfqAddr: PublicKey) => {
+            const rfq = await program.account.rfq.fetch(rfqAddr);
+            const commitDeadline = rfq.openedAt.toNumber() + commitTTL;
+            const revealDeadline = commitDeadline + revealTTL;
+            return { commitDeadline, revealDeadline, fundingDeadline: revealDeadline + selectionTTL + fundingTTL };
+        };
+        await waitForChainTime(provider.connection, (await deadlines(rfqD.rfqAddr)).commitDeadline, "commit deadline");
+        for (const [taker, rfqAddr, { salt }] of commits) {
+            await program.methods.revealQuote(Array.from(salt), new anchor.BN(QUOTE))
+                .accounts({ rfq: rfqAddr, quote: quotePdaOf(rfqAddr, taker), taker: taker.publicKey, revealer: taker.publicKey, config: configPda })
+                .signers([taker])
+                .rpc();
+        }
+
+        await waitForChainTime(provider.connection, (await deadlines(rfqD.rfqAddr)).revealDeadline, "reveal deadline");
+        for (const [taker, rfq] of [[takerC, rfqC], [takerD, rfqD]] as const) {
+            await program.methods.selectQuote()
+                .accounts({
+                    maker: maker.publicKey, rfq: rfq.rfqAddr, quote: quotePdaOf(rfq.rfqAddr, taker),
+                    baseMint, quoteMint, vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfq.rfqAddr, true),
+                    makerBaseAccount, config: configPda,
+                    baseTokenProgram: TOKEN_PROGRAM_ID, quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+        }
+
+        // a per-RFQ pause inside B's selection window is remembered once lifted
+        for (const paused of [true, false]) {
+            await program.methods.setRfqPause(paused)
+                .accounts({ pauser: admin.publicKey, config: configPda, rfq: rfqB.rfqAddr })
+                .signers([admin])
+                .rpc();
+        }
+        const rfqBResumed = await program.account.rfq.fetch(rfqB.rfqAddr);
+        expect(rfqBResumed.pausedSince).to.equal(null);
+        expect(rfqBResumed.selectionWindowPaused).to.equal(true);
+
+        // PAUSE_ALL lands inside B's selection window, before the maker picked a quote
+        const setPause = async (paused: number) => program.methods
+            .setPause(paused)
+            .accounts({ pauser: admin.publicKey, config: configPda, ...await setPauseAccounts(program, configPda, paused) })
+            .signers([admin])
+            .rpc();
+        await setPause(15);
+        try {
+            // complete_settlement, within C's funding deadline
+            const completeIx = await program.methods.completeSettlement()
+                .accounts({
+                    taker: takerC.publicKey, config: configPda, treasuryWallet: treasury, rfq: rfqC.rfqAddr,
+                    settlement: settlementPdaOf(rfqC.rfqAddr, takerC),
+                    usdcMint, baseMint, quoteMint,
+                    takerPaymentAccount: getAssociatedTokenAddressSync(usdcMint, takerC.publicKey),
+                    makerPaymentAccount,
+                    vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqC.rfqAddr, true),
+                    takerBaseAccount: getAssociatedTokenAddressSync(baseMint, takerC.publicKey),
+                    makerQuoteAccount: getAssociatedTokenAddressSync(quoteMint, maker.publicKey),
+                    takerQuoteAccount: getAssociatedTokenAddressSync(quoteMint, takerC.publicKey),
+                    treasuryAta: getAssociatedTokenAddressSync(usdcMint, treasury),
+                    treasuryQuoteAta: getAssociatedTokenAddressSync(quoteMint, treasury),
+                    feeEscrow: getAssociatedTokenAddressSync(quoteMint, rfqC.rfqAddr, true),
+                    bondsEscrow: rfqC.bondsEscrow,
+                    tokenProgram: TOKEN_PROGRAM_ID, baseTokenProgram: TOKEN_PROGRAM_ID, quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .remainingAccounts([
+                    { pubkey: quotePdaOf(rfqC.rfqAddr, takerC), isSigner: false, isWritable: true },
+                    { pubkey: slashedBondsTrackerPda(rfqC.rfqAddr)[0], isSigner: false, isWritable: true },
+                    { pubkey: commitGuardPdaOf(commits[1][2].commitHash), isSigner: false, isWritable: true },
+                ])
+                .instruction();
+            await provider.sendAndConfirm(
+                new anchor.web3.Transaction().add(ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }), completeIx),
+                [takerC]);
+            expect((await program.account.rfq.fetch(rfqC.rfqAddr)).state).to.have.property("settled");
+
+            // close_expired: A never saw a reveal
+            await program.methods.closeExpired()
+                .accounts({
+                    keeper: maker.publicKey, maker: maker.publicKey, rfq: rfqA.rfqAddr, config: configPda, usdcMint,
+                    bondsEscrow: rfqA.bondsEscrow, treasuryWallet: treasury, makerPaymentAccount,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+            expect((await program.account.rfq.fetch(rfqA.rfqAddr)).state).to.have.property("expired");
+
+            // close_incomplete: D's taker never funded
+            await waitForChainTime(provider.connection,
+                (await program.account.settlement.fetch(settlementPdaOf(rfqD.rfqAddr, takerD))).fundingDeadline.toNumber(),
+                "funding deadline");
+            await program.methods.closeIncomplete()
+                .accounts({
+                    keeper: maker.publicKey, maker: maker.publicKey, config: configPda, rfq: rfqD.rfqAddr,
+                    settlement: settlementPdaOf(rfqD.rfqAddr, takerD), settlementRentPayer: maker.publicKey,
+                    baseMint, vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqD.rfqAddr, true), makerBaseAccount,
+                    usdcMint, bondsEscrow: rfqD.bondsEscrow, makerPaymentAccount, treasuryWallet: treasury,
+                    tokenProgram: TOKEN_PROGRAM_ID, baseTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+            expect((await program.account.rfq.fetch(rfqD.rfqAddr)).state).to.have.property("incomplete");
+
+            // refund_quote_bonds: B ends Ignored, but the maker could not select, so keeps the bond
+            await waitForChainTime(provider.connection, (await deadlines(rfqB.rfqAddr)).fundingDeadline, "funding deadline");
+            const makerBefore = await provider.connection.getTokenAccountBalance(makerPaymentAccount).then(b => new anchor.BN(b.value.amount));
+            await program.methods.refundQuoteBonds()
+                .accounts({
+                    taker: takerB.publicKey, config: configPda, rfq: rfqB.rfqAddr, usdcMint, bondsEscrow: rfqB.bondsEscrow,
+                    takerPaymentAccount: getAssociatedTokenAddressSync(usdcMint, takerB.publicKey),
+                    treasuryWallet: treasury, makerPaymentAccount,
+                    ...await selectionPauseProof(program, rfqB.rfqAddr),
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([takerB])
+                .rpc();
+            const [rfqAfter, tracker, makerAfter] = await Promise.all([
+                program.account.rfq.fetch(rfqB.rfqAddr),
+                program.account.slashedBondsTracker.fetch(slashedBondsTrackerPda(rfqB.rfqAddr)[0]),
+                provider.connection.getTokenAccountBalance(makerPaymentAccount).then(b => new anchor.BN(b.value.amount)),
+            ]);
+            expect(rfqAfter.state).to.have.property("ignored");
+            assert(tracker.amount.isZero(), "nothing is seized from the maker while selection was paused");
+            assert(makerAfter.sub(makerBefore).eq(new anchor.BN(BOND)), "maker bond should be refunded");
+        } finally {
+            await setPause(0);
+        }
+    });
+
     it("should close RFQ", async () => {
         const maker = Keypair.generate();
         await fund(maker);
@@ -889,8 +1536,6 @@ describe("RFQ", () => {
         const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqAddr, true);
         const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
 
-        const baseMint = Keypair.generate().publicKey;
-        const quoteMint = Keypair.generate().publicKey;
 
         console.log("maker:", maker.publicKey.toBase58());
         console.log("rfqAddr:", rfqAddr.toBase58());
@@ -932,9 +1577,20 @@ describe("RFQ", () => {
                 revealTTL,
                 selectionTTL,
                 fundingTTL,
+                null,
+                false,
+                false,
+                false,
+                { firstPrice: {} },
+                { sell: {} },
+                new anchor.BN(0),
+                { base: {} },
+                new anchor.BN(0),
+                false,
                 null
             )
             .accounts({
+                baseMint, quoteMint,
                 maker: maker.publicKey,
                 config: configPda,
                 usdcMint, bondsEscrow,
@@ -13,8 +13,10 @@ import {
 } from "@solana/spl-token";
 import { v4 as uuidv4, parse as uuidParse } from "uuid";
 import assert from "assert";
-import { waitForChainTime } from "./utils/time";
+import { getChainUnixTime, waitForChainTime } from "./utils/time";
 import { uuidBytes } from "./1_rfq.spec";
+import { createSecp256r1Instruction, generateSecp256r1Keypair, signSecp256r1 } from "./utils/secp256r1";
+import { checkLiquidity, liquidityGuard, liquidityGuardNetwork, waitForLiquidityGuardReady } from "./utils/liquidityGuard";
 
 anchor.setProvider(anchor.AnchorProvider.env());
 const provider = anchor.getProvider() as anchor.AnchorProvider;
@@ -41,7 +43,6 @@ const rfqPda = (maker: PublicKey, u16: Uint8Array) =>
         program.programId
     );
 
-const liquidityGuardURL = "https://liquidity-guard-devnet-skip-c644b6411603.herokuapp.com";
 const toNum = (v: any) => (typeof v === "number" ? v : new anchor.BN(v).toNumber());
 
 async function getAndLogBalance(
@@ -75,9 +76,6 @@ describe("QUOTE", () => {
 
     const commitTTL = 10, revealTTL = 10, selectionTTL = 10, fundingTTL = 10;
 
-    const liquidityGuard = new PublicKey("5gfPFweV3zJovznZqBra3rv5tWJ5EHVzQY1PqvNA4HGg");
-
-
     before(async () => {
         await waitForLiquidityGuardReady();
         await fund(admin);
@@ -109,7 +107,7 @@ describe("QUOTE", () => {
         if (needInit) {
             const treasury = Keypair.generate().publicKey;
             await program.methods
-                .initConfig(usdcMint, treasury, liquidityGuard, null)
+                .initConfig(usdcMint, treasury, liquidityGuard, null, null, null, 0, null) // no timelock
                 .accounts({ admin: admin.publicKey })
                 .signers([admin])
                 .rpc();
@@ -159,9 +157,20 @@ describe("QUOTE", () => {
                     revealTTL,
                     selectionTTL,
                     fundingTTL,
+                    null,
+                    false,
+                    false,
+                    false,
+                    { firstPrice: {} },
+                    { sell: {} },
+                    new anchor.BN(0),
+                    { base: {} },
+                    new anchor.BN(0),
+                    false,
                     null
                 )
                 .accounts({
+                    baseMint, quoteMint,
                     maker: maker.publicKey,
                     config: configPda,
                     usdcMint,
@@ -188,6 +197,7 @@ describe("QUOTE", () => {
                 bondsEscrow,
                 makerPaymentAccount,
                 usdcMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
             })
             .signers([maker])
             .rpc();
@@ -205,7 +215,7 @@ describe("QUOTE", () => {
 
     after(async () => {
         await program.methods
-            .closeConfig()
+            .forceCloseConfig() // some RFQs are left live
             .accounts({ admin: admin.publicKey, config: configPda })
             .signers([admin])
             .rpc();
@@ -229,6 +239,8 @@ describe("QUOTE", () => {
 
         const payload = {
             rfq: rfqPDA.toBase58(),
+            program_id: program.programId.toBase58(),
+            rfq_open_nonce: (await program.account.rfq.fetch(rfqPDA)).openNonce.toString(),
             taker: taker.publicKey.toBase58(),
             salt: Buffer.from(salt).toString("hex"),
             quote_mint: quoteMint.toBase58(),
@@ -237,13 +249,7 @@ describe("QUOTE", () => {
             taker_fee_bps: new anchor.BN(1_000).toString(),
         };
 
-        const response = await fetchJson<CheckResult>(`${liquidityGuardURL}/check`, {
-            method: "POST",
-            headers: {
-                "Content-Type": "application/json",
-            },
-            body: JSON.stringify(payload),
-        });
+        const response = await checkLiquidity(payload);
 
         if ("error" in response) {
             throw new Error(`Liquidity Guard error: ${response.error}`);
@@ -258,9 +264,25 @@ describe("QUOTE", () => {
             assert(response.service_pubkey === liquidityGuard.toBase58(), `unexpected service pubkey ${response.service_pubkey}`);
             assert(response.commit_hash.length > 0, `empty commit_hash`);
             assert(response.liquidity_proof.length > 0, `empty liquidity_proof`);
-            assert(response.network === 'Devnet', `unexpected network: ${response.network}`);
+            assert(response.network === liquidityGuardNetwork, `unexpected network: ${response.network}`);
             assert(response.skip_fund_checks === true, `unexpected skip_fund_checks: ${response.skip_fund_checks}`);
             assert(response.timestamp > 0, `invalid timestamp: ${response.timestamp}`);
+
+            // domain || version || program_id || rfq || taker || commit_hash || max_notional || valid_until
+            const attestation = Buffer.from(response.attestation, "hex");
+            const domain = Buffer.from("settlement-engine:liquidity-guard");
+            assert(attestation.length === domain.length + 1 + 32 * 4 + 16, `unexpected attestation length ${attestation.length}`);
+            assert(attestation.subarray(0, domain.length).equals(domain), "unexpected attestation domain");
+            let offset = domain.length;
+            assert(attestation[offset] === 1, `unexpected attestation version ${attestation[offset]}`);
+            offset += 1;
+            const field = () => attestation.subarray(offset, (offset += 32));
+            assert(new PublicKey(field()).equals(program.programId), "attestation not bound to this program");
+            assert(new PublicKey(field()).equals(rfqPDA), "attestation not bound to the rfq");
+            assert(new PublicKey(field()).equals(taker.publicKey), "attestation not bound to the taker");
+            assert(field().toString("hex") === response.commit_hash, "attestation not bound to the commit hash");
+            assert(new anchor.BN(attestation.subarray(offset, offset + 8), "le").gten(1_000_000_001), "max notional below the quote");
+            assert(Number(attestation.readBigInt64LE(offset + 8)) > response.timestamp, "attestation already expired");
         }
     });
 
@@ -283,6 +305,8 @@ describe("QUOTE", () => {
 
         const payload = {
             rfq: rfqPDA.toBase58(),
+            program_id: program.programId.toBase58(),
+            rfq_open_nonce: (await program.account.rfq.fetch(rfqPDA)).openNonce.toString(),
             taker: taker.publicKey.toBase58(),
             salt: Buffer.from(salt).toString("hex"),
             quote_mint: quoteMint.toBase58(),
@@ -291,13 +315,7 @@ describe("QUOTE", () => {
             taker_fee_bps: new anchor.BN(1_000).toString(),
         };
 
-        const response = await fetchJson<CheckResult>(`${liquidityGuardURL}/check`, {
-            method: "POST",
-            headers: {
-                "Content-Type": "application/json",
-            },
-            body: JSON.stringify(payload),
-        });
+        const response = await checkLiquidity(payload);
 
         if ("error" in response) {
             throw new Error(`Liquidity Guard error: ${response.error}`);
@@ -337,13 +355,14 @@ describe("QUOTE", () => {
 
         const commit_hash = Buffer.from(response.commit_hash, "hex");
         const liquidity_proof = Buffer.from(response.liquidity_proof, "hex");
+        const attestation = Buffer.from(response.attestation, "hex");
         if (commit_hash.length !== 32) throw new Error("commit_hash must be 32 bytes");
         if (liquidity_proof.length !== 64) throw new Error("liquidity_proof sig must be 64 bytes");
 
         // Create Ed25519 verification instruction using the helper
         const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
             publicKey: liquidityGuard.toBytes(),
-            message: commit_hash,
+            message: attestation,
             signature: liquidity_proof,
         });
 
@@ -356,7 +375,7 @@ describe("QUOTE", () => {
         console.log('OFFSETS:', { sigOffset, pubkeyOffset, msgOffset, msgSize });
 
         const commitQuoteIx1 = await program.methods
-            .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), facilitator.publicKey)
+            .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), facilitator.publicKey, null, null, [])
             .accounts({
                 taker: taker.publicKey,
                 rfq: rfqPDA,
@@ -364,6 +383,7 @@ describe("QUOTE", () => {
                 config: configPda,
                 instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
                 takerPaymentAccount: takerPaymentAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
             })
             .instruction();
 
@@ -395,6 +415,7 @@ describe("QUOTE", () => {
         assert(quote.rfq.equals(rfqPDA));
         assert.deepStrictEqual(quote.commitHash, Array.from(commit_hash));
         assert.deepStrictEqual(quote.liquidityProof, Array.from(liquidity_proof));
+        assert(quote.maxNotional.gten(1_000_000_001), "quote maxNotional should cover the quote");
         assert.ok(quote.committedAt.toNumber() > 0);
         assert(!quote.revealedAt, "revealedAt should be None before reveal");
         assert(quote.maxFundingDeadline.toNumber() > 0, "quote maxFundingDeadline should be set");
@@ -462,7 +483,7 @@ describe("QUOTE", () => {
         );
 
         const commitQuoteIx2 = await program.methods
-            .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), null)
+            .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), null, null, null, [])
             .accounts({
                 taker: taker2.publicKey,
                 config: configPda,
@@ -470,6 +491,7 @@ describe("QUOTE", () => {
                 usdcMint: usdcMint,
                 instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
                 takerPaymentAccount: taker2PaymentAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
             }).instruction();
 
         const tx2 = new anchor.web3.Transaction();
@@ -486,7 +508,7 @@ describe("QUOTE", () => {
         console.log("Testing that same taker cannot commit twice...");
         failed = false;
         const commitQuoteIx3 = await program.methods
-            .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), null)
+            .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), null, null, null, [])
             .accounts({
                 taker: taker.publicKey,
                 config: configPda,
@@ -494,6 +516,7 @@ describe("QUOTE", () => {
                 usdcMint: usdcMint,
                 instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
                 takerPaymentAccount: takerPaymentAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
             }).instruction();
 
         const tx3 = new anchor.web3.Transaction();
@@ -518,6 +541,8 @@ describe("QUOTE", () => {
 
         const payload = {
             rfq: rfqPDA.toBase58(),
+            program_id: program.programId.toBase58(),
+            rfq_open_nonce: (await program.account.rfq.fetch(rfqPDA)).openNonce.toString(),
             taker: taker.publicKey.toBase58(),
             salt: Buffer.from(salt).toString("hex"),
             quote_mint: quoteMint.toBase58(),
@@ -525,13 +550,7 @@ describe("QUOTE", () => {
             bond_amount_usdc: new anchor.BN(1_000_000).toString(),
             taker_fee_bps: new anchor.BN(1_000).toString(),
         };
-        const response = await fetchJson<CheckResult>(`${liquidityGuardURL}/check`, {
-            method: "POST",
-            headers: {
-                "Content-Type": "application/json",
-            },
-            body: JSON.stringify(payload),
-        });
+        const response = await checkLiquidity(payload);
 
         if ("error" in response) {
             throw new Error(`Liquidity Guard error: ${response.error}`);
@@ -564,6 +583,7 @@ describe("QUOTE", () => {
 
         const commit_hash = Buffer.from(response.commit_hash, "hex");
         const liquidity_proof = Buffer.from(response.liquidity_proof, "hex");
+        const attestation = Buffer.from(response.attestation, "hex");
         if (commit_hash.length !== 32) throw new Error("commit_hash must be 32 bytes");
         if (liquidity_proof.length !== 64) throw new Error("liquidity_proof sig must be 64 bytes");
         liquidity_proof[0] ^= 0xFF; // invalidate proof
@@ -572,12 +592,12 @@ describe("QUOTE", () => {
         // Create Ed25519 verification instruction using the helper
         const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
             publicKey: liquidityGuard.toBytes(),
-            message: commit_hash,
+            message: attestation,
             signature: liquidity_proof,
         });
 
         const commitQuoteIx1 = await program.methods
-            .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), null)
+            .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), null, null, null, [])
             .accounts({
                 taker: taker.publicKey,
                 config: configPda,
@@ -585,6 +605,7 @@ describe("QUOTE", () => {
                 usdcMint: usdcMint,
                 instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
                 takerPaymentAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
             })
             .instruction();
 
@@ -671,7 +692,7 @@ describe("QUOTE", () => {
         try {
             await program.methods
                 .revealQuote(Array.from(salt), new anchor.BN(1_000_000_001))
-                .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, config: configPda })
+                .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, revealer: taker.publicKey, config: configPda })
                 .signers([taker])
                 .rpc();
         } catch {
@@ -685,7 +706,7 @@ describe("QUOTE", () => {
             fakeSalt[0] ^= 0xFF; // invalidate salt
             await program.methods
                 .revealQuote(Array.from(fakeSalt), new anchor.BN(1_000_000_001))
-                .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, config: configPda })
+                .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, revealer: taker.publicKey, config: configPda })
                 .signers([taker])
                 .rpc();
         } catch {
@@ -702,9 +723,24 @@ describe("QUOTE", () => {
         await waitForChainTime(provider.connection, commitDeadline, "commit deadline");
         console.log("Reveal period begins (past commit deadline)...");
 
+        // no reveal delegate was set at commit, so only the taker may reveal
+        const stranger = Keypair.generate();
+        failed = false;
+        try {
+            await program.methods
+                .revealQuote(Array.from(salt), new anchor.BN(1_000_000_001))
+                .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, revealer: stranger.publicKey, config: configPda })
+                .signers([stranger])
+                .rpc();
+        } catch (e) {
+            failed = true;
+            assert(String(e).includes("Unauthorized"), `unexpected error: ${e}`);
+        }
+        assert(failed, "revealQuote should fail for a signer that is neither the taker nor its delegate");
+
         await program.methods
             .revealQuote(Array.from(salt), new anchor.BN(1_000_000_001))
-            .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, config: configPda })
+            .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, revealer: taker.publicKey, config: configPda })
             .signers([taker])
             .rpc();
 
@@ -720,12 +756,15 @@ describe("QUOTE", () => {
         assert(quote.facilitator.equals(facilitator.publicKey), "quote facilitator mismatch");
         assert.ok(rfq.state.revealed);
         assert.strictEqual(rfq.revealedCount, 1, "rfq revealedCount should be 1");
+        assert(rfq.bestQuote.equals(quotePda), "rfq bestQuote should be the only reveal");
+        assert.ok(rfq.bestQuoteAmount.eq(new anchor.BN(1_000_000_001)), "rfq bestQuoteAmount mismatch");
+        assert.ok(rfq.secondBestQuoteAmount.eqn(0), "no second best with a single reveal");
 
         failed = false;
         try {
             await program.methods
                 .revealQuote(Array.from(salt), new anchor.BN(1_000_000_001))
-                .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, config: configPda })
+                .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, revealer: taker.publicKey, config: configPda })
                 .signers([taker])
                 .rpc();
         } catch {
@@ -741,7 +780,7 @@ describe("QUOTE", () => {
             program.programId
         );
         const [settlementPda, bumpSettlement] = PublicKey.findProgramAddressSync(
-            [Buffer.from("settlement"), rfqPDA.toBuffer()],
+            [Buffer.from("settlement"), rfqPDA.toBuffer(), quotePda.toBuffer()],
             program.programId
         );
 
@@ -779,6 +818,8 @@ describe("QUOTE", () => {
                     quoteMint,
                     vaultBaseAta: vaultBaseATA,
                     makerBaseAccount,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([maker])
                 .rpc();
@@ -807,6 +848,8 @@ describe("QUOTE", () => {
                 vaultBaseAta: vaultBaseATA,
                 makerBaseAccount,
                 config: configPda,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+                quoteTokenProgram: TOKEN_PROGRAM_ID,
             })
             .signers([maker])
             .rpc();
@@ -873,6 +916,8 @@ describe("QUOTE", () => {
                     quoteMint,
                     vaultBaseAta: vaultBaseATA,
                     makerBaseAccount,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([maker])
                 .rpc();
@@ -881,71 +926,269 @@ describe("QUOTE", () => {
         }
         assert(failed, "selectQuote should fail because already selected and settlement already created");
     });
-});
 
-export interface CheckResponse {
-    rfq: string;
-    salt: string;
-    taker: string;
-    usdc_mint: string;
-    quote_mint: string;
-    quote_amount: string;
-    bond_amount_usdc: string;
-    taker_fee_bps: string;
-    service_pubkey: string;
-    commit_hash: string;
-    liquidity_proof: string;
-    network: string;
-    skip_fund_checks: boolean;
-    timestamp: number;
-}
+    it("accepts a commit signed by a registered guard key", async () => {
+        const [guardKeyPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("guard_key"), liquidityGuard.toBuffer()],
+            program.programId
+        );
+        const setConfigGuard = async (guard: PublicKey) => {
+            await program.methods
+                .queueConfigUpdate(null, null, guard, null, null, null)
+                .accounts({ authority: admin.publicKey, config: configPda })
+                .signers([admin])
+                .rpc();
+            await program.methods
+                .executeConfigUpdate()
+                .accounts({ executor: provider.wallet.publicKey, config: configPda })
+                .rpc();
+        };
 
-export interface ErrorResponse {
-    error: string;
-}
+        // register the guard key, then point the config at another key so the new
+        // RFQ snapshots a key the guard service does not sign with
+        // valid_from must not precede now + timelock (0 here); leave room for clock drift
+        const validFrom = (await getChainUnixTime(provider.connection)) + 5;
+        await program.methods
+            .addGuardKey(liquidityGuard, new anchor.BN(validFrom), null, null)
+            .accounts({ guardManager: admin.publicKey, config: configPda })
+            .signers([admin])
+            .rpc();
+        await setConfigGuard(Keypair.generate().publicKey);
+
+        const rotatedMaker = Keypair.generate();
+        const taker = Keypair.generate();
+        await Promise.all([fund(rotatedMaker), fund(taker)]);
+        const u = uuidBytes();
+        const [rotatedRfq] = rfqPda(rotatedMaker.publicKey, u);
+        const rotatedEscrow = getAssociatedTokenAddressSync(usdcMint, rotatedRfq, true);
+        const [rotatedMakerPayment, takerPaymentAccount] = await Promise.all([rotatedMaker, taker].map(kp =>
+            getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, kp.publicKey)
+                .then(async a => {
+                    await mintTo(provider.connection, admin, usdcMint, a.address, admin, 1_000_000);
+                    return a.address;
+                })));
+        await program.methods
+            .initRfq(
+                Array.from(u), baseMint, quoteMint,
+                new anchor.BN(1_000_000), new anchor.BN(1_000_000_000), new anchor.BN(1_000_000_000),
+                1000, commitTTL, revealTTL, selectionTTL, fundingTTL,
+                null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0),
+                false,
+                null
+            )
+            .accounts({
+                baseMint, quoteMint,
+                maker: rotatedMaker.publicKey, config: configPda, usdcMint,
+                bondsEscrow: rotatedEscrow, makerPaymentAccount: rotatedMakerPayment,
+                systemProgram: SystemProgram.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([rotatedMaker])
+            .rpc();
+        await program.methods.openRfq()
+            .accounts({
+                maker: rotatedMaker.publicKey, rfq: rotatedRfq, config: configPda,
+                bondsEscrow: rotatedEscrow, makerPaymentAccount: rotatedMakerPayment, usdcMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([rotatedMaker])
+            .rpc();
+        await setConfigGuard(liquidityGuard); // later specs expect the original key
+        assert(!(await program.account.rfq.fetch(rotatedRfq)).liquidityGuard.equals(liquidityGuard));
+
+        const salt = nacl.sign.detached(Buffer.from(rotatedRfq.toBytes()), taker.secretKey);
+        const response = await checkLiquidity({
+            rfq: rotatedRfq.toBase58(),
+            program_id: program.programId.toBase58(),
+            rfq_open_nonce: (await program.account.rfq.fetch(rotatedRfq)).openNonce.toString(),
+            taker: taker.publicKey.toBase58(),
+            salt: Buffer.from(salt).toString("hex"),
+            quote_mint: quoteMint.toBase58(),
+            quote_amount: new anchor.BN(1_000_000_001).toString(),
+            bond_amount_usdc: new anchor.BN(1_000_000).toString(),
+            taker_fee_bps: new anchor.BN(1_000).toString(),
+        });
+        if ("error" in response) {
+            throw new Error(`Liquidity Guard error: ${response.error}`);
+        }
+        const commitHash = Buffer.from(response.commit_hash, "hex");
+        const liquidityProof = Buffer.from(response.liquidity_proof, "hex");
+        const attestation = Buffer.from(response.attestation, "hex");
+        const commit = async (guardKey: PublicKey | null) => {
+            const commitIx = await program.methods
+                .commitQuote(Array.from(commitHash), Array.from(liquidityProof), null, null, null, [])
+                .accounts({
+                    taker: taker.publicKey,
+                    rfq: rotatedRfq,
+                    usdcMint,
+                    config: configPda,
+                    guardKey,
+                    instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+                    takerPaymentAccount,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .instruction();
+            const tx = new anchor.web3.Transaction().add(
+                Ed25519Program.createInstructionWithPublicKey({
+                    publicKey: liquidityGuard.toBytes(),
+                    message: attestation,
+                    signature: liquidityProof,
+                }),
+                commitIx,
+            );
+            return provider.sendAndConfirm(tx, [taker]);
+        };
 
-export type CheckResult = CheckResponse | ErrorResponse;
+        await waitForChainTime(provider.connection, validFrom, "guard key valid_from");
+        let failed = false;
+        try { await commit(null); } catch (e) {
+            failed = true;
+            assert(String(e).includes("UnauthorizedSigner"), `unexpected error: ${e}`);
+        }
+        assert(failed, "commit should fail without the guard key registry entry");
 
-export async function fetchJson<T>(url: string, init?: RequestInit): Promise<T> {
-    const res = await fetch(url, init);
+        await commit(guardKeyPda);
+        assert.ok((await program.account.rfq.fetch(rotatedRfq)).state.committed);
 
-    if (!res.ok) {
-        const errBody = await res.json().catch(() => ({}));
-        throw new Error(
-            `HTTP ${res.status}: ${JSON.stringify(errBody, null, 2)}`
-        );
-    }
+        // a valid key cannot be removed; revoking it first ends its window
+        failed = false;
+        try {
+            await program.methods.removeGuardKey()
+                .accounts({ guardManager: admin.publicKey, config: configPda, guardKey: guardKeyPda, rentPayer: admin.publicKey })
+                .signers([admin])
+                .rpc();
+        } catch { failed = true; }
+        assert(failed, "removeGuardKey should fail while the key is valid");
 
-    return res.json() as Promise<T>;
-}
+        await program.methods.revokeGuardKey(null)
+            .accounts({ guardManager: admin.publicKey, config: configPda, guardKey: guardKeyPda })
+            .signers([admin])
+            .rpc();
+        const revoked = await program.account.guardKey.fetch(guardKeyPda);
+        await waitForChainTime(provider.connection, revoked.validUntil!.toNumber(), "guard key expiry");
+        await program.methods.removeGuardKey()
+            .accounts({ guardManager: admin.publicKey, config: configPda, guardKey: guardKeyPda, rentPayer: admin.publicKey })
+            .signers([admin])
+            .rpc();
+        let removed = false;
+        try { await program.account.guardKey.fetch(guardKeyPda); } catch { removed = true; }
+        assert(removed, "guard key PDA should be closed");
+    });
 
-export function sleep(ms: number): Promise<void> {
-    return new Promise(resolve => setTimeout(resolve, ms));
-}
+    it("accepts a commit signed by a secp256r1 liquidity guard", async () => {
+        const p256 = generateSecp256r1Keypair();
+        const setConfigGuard = async (guard: PublicKey, keyType: object) => {
+            await program.methods
+                .queueConfigUpdate(null, null, guard, null, null, keyType)
+                .accounts({ authority: admin.publicKey, config: configPda })
+                .signers([admin])
+                .rpc();
+            await program.methods
+                .executeConfigUpdate()
+                .accounts({ executor: provider.wallet.publicKey, config: configPda })
+                .rpc();
+        };
+        await setConfigGuard(p256.key, { secp256r1: {} });
+        const cfg = await program.account.config.fetch(configPda);
+        assert(cfg.liquidityGuard.equals(p256.key));
+        assert("secp256r1" in cfg.liquidityGuardKeyType);
 
-export async function waitForLiquidityGuardReady(maxWaitMs = 10_000, pollMs = 500) {
-    const start = Date.now();
-    let lastError: unknown;
-    console.log(`Waiting for Liquidity Guard to be reachable (timeout ${maxWaitMs}ms)...`);
+        const p256Maker = Keypair.generate();
+        const taker = Keypair.generate();
+        await Promise.all([fund(p256Maker), fund(taker)]);
+        const u = uuidBytes();
+        const [p256Rfq] = rfqPda(p256Maker.publicKey, u);
+        const p256Escrow = getAssociatedTokenAddressSync(usdcMint, p256Rfq, true);
+        const [p256MakerPayment, takerPaymentAccount] = await Promise.all([p256Maker, taker].map(kp =>
+            getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, kp.publicKey)
+                .then(async a => {
+                    await mintTo(provider.connection, admin, usdcMint, a.address, admin, 1_000_000);
+                    return a.address;
+                })));
+        await program.methods
+            .initRfq(
+                Array.from(u), baseMint, quoteMint,
+                new anchor.BN(1_000_000), new anchor.BN(1_000_000_000), new anchor.BN(1_000_000_000),
+                1000, commitTTL, revealTTL, selectionTTL, fundingTTL,
+                null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0),
+                false,
+                null
+            )
+            .accounts({
+                baseMint, quoteMint,
+                maker: p256Maker.publicKey, config: configPda, usdcMint,
+                bondsEscrow: p256Escrow, makerPaymentAccount: p256MakerPayment,
+                systemProgram: SystemProgram.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([p256Maker])
+            .rpc();
+        await program.methods.openRfq()
+            .accounts({
+                maker: p256Maker.publicKey, rfq: p256Rfq, config: configPda,
+                bondsEscrow: p256Escrow, makerPaymentAccount: p256MakerPayment, usdcMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([p256Maker])
+            .rpc();
+        await setConfigGuard(liquidityGuard, { ed25519: {} }); // later specs expect the original key
+        const snapshot = await program.account.rfq.fetch(p256Rfq);
+        assert(snapshot.liquidityGuard.equals(p256.key));
+        assert("secp256r1" in snapshot.liquidityGuardKeyType);
+
+        // the guard service supplies the commit hash and attestation; the P-256 key signs it
+        const salt = nacl.sign.detached(Buffer.from(p256Rfq.toBytes()), taker.secretKey);
+        const response = await checkLiquidity({
+            rfq: p256Rfq.toBase58(),
+            program_id: program.programId.toBase58(),
+            rfq_open_nonce: (await program.account.rfq.fetch(p256Rfq)).openNonce.toString(),
+            taker: taker.publicKey.toBase58(),
+            salt: Buffer.from(salt).toString("hex"),
+            quote_mint: quoteMint.toBase58(),
+            quote_amount: new anchor.BN(1_000_000_001).toString(),
+            bond_amount_usdc: new anchor.BN(1_000_000).toString(),
+            taker_fee_bps: new anchor.BN(1_000).toString(),
+        });
+        if ("error" in response) {
+            throw new Error(`Liquidity Guard error: ${response.error}`);
+        }
+        const commitHash = Buffer.from(response.commit_hash, "hex");
+        const attestation = Buffer.from(response.attestation, "hex");
+        const commit = async (verifyIx: anchor.web3.TransactionInstruction, proof: Buffer) => {
+            const commitIx = await program.methods
+                .commitQuote(Array.from(commitHash), Array.from(proof), null, null, null, [])
+                .accounts({
+                    taker: taker.publicKey,
+                    rfq: p256Rfq,
+                    usdcMint,
+                    config: configPda,
+                    instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+                    takerPaymentAccount,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .instruction();
+            return provider.sendAndConfirm(new anchor.web3.Transaction().add(verifyIx, commitIx), [taker]);
+        };
 
-    while (Date.now() - start < maxWaitMs) {
-        const controller = new AbortController();
-        const timer = setTimeout(() => controller.abort(), Math.min(pollMs, 2_000));
+        // an ed25519 proof does not satisfy an RFQ whose guard is a P-256 key
+        const ed25519Proof = Buffer.from(response.liquidity_proof, "hex");
+        let failed = false;
         try {
-            const res = await fetch(`${liquidityGuardURL}/health`, { signal: controller.signal });
-            if (res.ok || res.status === 404) {
-                console.log("Liquidity Guard is reachable");
-                return;
-            }
-            lastError = new Error(`HTTP ${res.status}`);
-        } catch (err) {
-            lastError = err;
-        } finally {
-            clearTimeout(timer);
+            await commit(Ed25519Program.createInstructionWithPublicKey({
+                publicKey: liquidityGuard.toBytes(),
+                message: attestation,
+                signature: ed25519Proof,
+            }), ed25519Proof);
+        } catch (e) {
+            failed = true;
+            assert(String(e).includes("UnauthorizedSigner"), `unexpected error: ${e}`);
         }
-        await sleep(pollMs);
-    }
+        assert(failed, "commit should fail with the ed25519 guard");
 
-    const suffix = lastError ? ` (last error: ${String(lastError)})` : "";
-    throw new Error(`Liquidity Guard not ready after ${maxWaitMs}ms${suffix}`);
-}
+        const p256Proof = signSecp256r1(p256, attestation);
+        await commit(createSecp256r1Instruction(p256.publicKey, attestation, p256Proof), p256Proof);
+        assert.ok((await program.account.rfq.fetch(p256Rfq)).state.committed);
+    });
+});
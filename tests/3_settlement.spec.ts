@@ -1,28 +1,34 @@
 import * as anchor from "@coral-xyz/anchor";
-import nacl from "tweetnacl";
 import { Program } from "@coral-xyz/anchor";
 import { SettlementEngine } from "../target/types/settlement_engine";
+import { TestTransferHook } from "../target/types/test_transfer_hook";
 import { ComputeBudgetProgram, Ed25519Program, Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
 import {
+    approveChecked,
     createMint,
     getAssociatedTokenAddressSync,
     mintTo,
+    transfer,
     TOKEN_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID,
     ASSOCIATED_TOKEN_PROGRAM_ID,
     getOrCreateAssociatedTokenAccount,
 } from "@solana/spl-token";
 import assert from "assert";
-import { CheckResult, fetchJson, sleep, waitForLiquidityGuardReady } from "./2_quote.spec";
+import { liquidityGuard, provideLiquidityGuardAttestation, waitForLiquidityGuardReady } from "./utils/liquidityGuard";
 import { waitForChainTime } from "./utils/time";
 import { slashedBondsTrackerPda, uuidBytes } from "./1_rfq.spec";
 import { expectedSlashedAmount } from "./utils/slashing";
+import { createTransferFeeMint, grossUp } from "./utils/transferFee";
+import { cpiEvents } from "./utils/events";
+import { allowlistProof, allowlistRoot } from "./utils/allowlist";
+import { createTransferHookMint, hookCounterPda, transferHookAccounts } from "./utils/transferHook";
 
 anchor.setProvider(anchor.AnchorProvider.env());
 const provider = anchor.getProvider() as anchor.AnchorProvider;
 const program = anchor.workspace.SettlementEngine as Program<SettlementEngine>;
+const hookProgram = anchor.workspace.TestTransferHook as Program<TestTransferHook>;
 
-const liquidityGuardURL = "https://liquidity-guard-devnet-skip-c644b6411603.herokuapp.com";
-const liquidityGuard = new PublicKey("5gfPFweV3zJovznZqBra3rv5tWJ5EHVzQY1PqvNA4HGg");
 const DEFAULT_QUOTE_AMOUNT = 1_000_000_001;
 const DEFAULT_BASE_AMOUNT = 1_000_000_000;
 const DEFAULT_BOND_AMOUNT = 1_000_000;
@@ -55,18 +61,33 @@ const rfqPda = (maker: PublicKey, u16: Uint8Array) =>
         program.programId
     );
 
-const settlementPda = (rfqPDA: PublicKey) => PublicKey.findProgramAddressSync(
-    [Buffer.from("settlement"), rfqPDA.toBuffer()],
+const quotePdaOf = (rfqPDA: PublicKey, taker: PublicKey) => PublicKey.findProgramAddressSync(
+    [Buffer.from("quote"), rfqPDA.toBuffer(), taker.toBuffer()],
+    program.programId
+)[0];
+
+/** Commit guards are keyed by commit hash; complete_settlement closes the winner's */
+const commitGuardPdaOf = async (quotePDA: PublicKey) => {
+    const { commitHash } = await program.account.quote.fetch(quotePDA);
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("commit-guard"), Buffer.from(commitHash)],
+        program.programId
+    )[0];
+};
+
+/** Settlement, fees and reward trackers are keyed by (rfq, quote of the winning taker) */
+const settlementPda = (rfqPDA: PublicKey, taker: PublicKey) => PublicKey.findProgramAddressSync(
+    [Buffer.from("settlement"), rfqPDA.toBuffer(), quotePdaOf(rfqPDA, taker).toBuffer()],
     program.programId
 );
 
-const feesTrackerPda = (rfqPDA: PublicKey) => PublicKey.findProgramAddressSync(
-    [Buffer.from("fees_tracker"), rfqPDA.toBuffer()],
+const feesTrackerPda = (rfqPDA: PublicKey, taker: PublicKey) => PublicKey.findProgramAddressSync(
+    [Buffer.from("fees_tracker"), rfqPDA.toBuffer(), quotePdaOf(rfqPDA, taker).toBuffer()],
     program.programId
 );
 
-const facilitatorRewardTrackerPda = (rfqPDA: PublicKey, facilitator: PublicKey) => PublicKey.findProgramAddressSync(
-    [Buffer.from("facilitator_reward"), rfqPDA.toBuffer(), facilitator.toBuffer()],
+const facilitatorRewardTrackerPda = (rfqPDA: PublicKey, taker: PublicKey, facilitator: PublicKey) => PublicKey.findProgramAddressSync(
+    [Buffer.from("facilitator_reward"), rfqPDA.toBuffer(), quotePdaOf(rfqPDA, taker).toBuffer(), facilitator.toBuffer()],
     program.programId
 );
 
@@ -76,61 +97,27 @@ const getAndLogBalance = async (label: string, owner: string, tokenAccount: Publ
     return balance;
 };
 
-const provideLiquidityGuardAttestation = async (taker: anchor.web3.Keypair,
-    rfqPDA: anchor.web3.PublicKey,
-    quoteMint: anchor.web3.PublicKey,
-    quoteAmount = DEFAULT_QUOTE_AMOUNT,
-    bondAmount = DEFAULT_BOND_AMOUNT,
-    feeAmount = DEFAULT_FEE_AMOUNT) => {
-    const rfqAddr = Buffer.from(rfqPDA.toBytes());
-    const salt = nacl.sign.detached(rfqAddr, taker.secretKey);
-    console.log("salt:", Buffer.from(salt).toString("hex"));
-
-    const payload = {
-        rfq: rfqPDA.toBase58(),
-        taker: taker.publicKey.toBase58(),
-        salt: Buffer.from(salt).toString("hex"),
-        quote_mint: quoteMint.toBase58(),
-        quote_amount: new anchor.BN(quoteAmount).toString(),
-        bond_amount_usdc: new anchor.BN(bondAmount).toString(),
-        taker_fee_bps: new anchor.BN(feeAmount).toString(),
-    };
-
-    const response = await fetchJson<CheckResult>(`${liquidityGuardURL}/check`, {
-        method: "POST",
-        headers: {
-            "Content-Type": "application/json",
-        },
-        body: JSON.stringify(payload),
-    });
-    if ("error" in response) {
-        throw new Error(`Liquidity Guard error: ${response.error}`);
-    } else {
-        return [
-            salt,
-            Buffer.from(response.commit_hash, "hex"),
-            Buffer.from(response.liquidity_proof, "hex"),
-        ];
-    }
-};
-
 const commitQuote = async (
     commit_hash: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
     liquidity_proof: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
+    attestation: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
     taker: Keypair,
     rfqPDA: PublicKey,
     usdcMint: PublicKey,
     configPda: PublicKey,
     takerPaymentAccount: PublicKey,
-    facilitator: PublicKey | null = null) => {
+    facilitator: PublicKey | null = null,
+    separateVerifyIx = false,
+    revealDelegate: PublicKey | null = null,
+    allowlistProof: Buffer[] = []) => {
     // Create Ed25519 verification instruction using the helper
     const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
         publicKey: liquidityGuard.toBytes(),
-        message: commit_hash,
+        message: attestation,
         signature: liquidity_proof,
     });
     const commitQuoteIx1 = await program.methods
-        .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), facilitator)
+        .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), facilitator, separateVerifyIx ? 0 : null, revealDelegate, allowlistProof.map(p => Array.from(p)))
         .accounts({
             taker: taker.publicKey,
             rfq: rfqPDA,
@@ -138,12 +125,17 @@ const commitQuote = async (
             config: configPda,
             instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
             takerPaymentAccount: takerPaymentAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
         })
         .instruction();
 
     const tx = new anchor.web3.Transaction();
-    // Add ONLY these two instructions, in this exact order:
+    // Verify ix first, then commit_quote
     tx.add(ed25519Ix);
+    if (separateVerifyIx) {
+        // commit_quote then locates the verify ix through its index argument
+        tx.add(ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }));
+    }
     tx.add(commitQuoteIx1);
 
     // Send and confirm
@@ -192,7 +184,7 @@ describe("COMPLETE_SETTLEMENT", () => {
         let failed = false;
         try {
             await program.methods
-                .initConfig(usdcMint, treasury.publicKey, liquidityGuard, FACILITATOR_FEE_BPS)
+                .initConfig(usdcMint, treasury.publicKey, liquidityGuard, FACILITATOR_FEE_BPS, { grossUp: {} }, null, null, null)
                 .accounts({ admin: admin.publicKey })
                 .signers([admin])
                 .rpc();
@@ -217,7 +209,7 @@ describe("COMPLETE_SETTLEMENT", () => {
         // console.log("Final on-chain accounts data:", JSON.stringify(data, null, 2));
         console.log("Closing config...");
         await program.methods
-            .closeConfig()
+            .forceCloseConfig() // some RFQs are left live
             .accounts({ admin: admin.publicKey, config: configPda })
             .signers([admin])
             .rpc();
@@ -236,15 +228,17 @@ describe("COMPLETE_SETTLEMENT", () => {
 
         const u = uuidBytes();
         const [rfqPDA, rfqBump] = rfqPda(maker.publicKey, u);
-        const [settlementPDA, bumpSettlement] = settlementPda(rfqPDA);
-        const [feesTrackerPDA, bumpFeesTracker] = feesTrackerPda(rfqPDA);
+        const [settlementPDA, bumpSettlement] = settlementPda(rfqPDA, taker.publicKey);
+        const [feesTrackerPDA, bumpFeesTracker] = feesTrackerPda(rfqPDA, taker.publicKey);
         const [slashedBondsTrackerPDA, bumpslashedBondsTracker] = slashedBondsTrackerPda(rfqPDA);
         const [facilitatorRewardTrackerPDA, facilitatorRewardTrackerBump] = facilitatorRewardTrackerPda(
             rfqPDA,
+            taker.publicKey,
             facilitator.publicKey
         );
         const [otherFacilitatorRewardTrackerPDA] = facilitatorRewardTrackerPda(
             rfqPDA,
+            taker.publicKey,
             otherFacilitator.publicKey
         );
 
@@ -359,9 +353,20 @@ describe("COMPLETE_SETTLEMENT", () => {
                     revealTTL,
                     selectionTTL,
                     fundingTTL,
-                    facilitator.publicKey
+                    facilitator.publicKey,
+                    false,
+                    false,
+                    false,
+                    { firstPrice: {} },
+                    { sell: {} },
+                    new anchor.BN(0),
+                    { base: {} },
+                    new anchor.BN(0),
+                    false,
+                    null
                 )
                 .accounts({
+                    baseMint, quoteMint,
                     maker: maker.publicKey,
                     config: configPda,
                     usdcMint,
@@ -393,6 +398,7 @@ describe("COMPLETE_SETTLEMENT", () => {
                     bondsEscrow,
                     makerPaymentAccount,
                     usdcMint,
+                    tokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([maker])
                 .rpc();
@@ -406,10 +412,11 @@ describe("COMPLETE_SETTLEMENT", () => {
             getAndLogBalance("After opening RFQ", "RFQ Bonds Escrow", bondsEscrow),
         ]);
 
-        const [saltQ1, commit_hashQ1, liquidity_proofQ1] = await provideLiquidityGuardAttestation(taker, rfqPDA, quoteMint);
+        const [saltQ1, commit_hashQ1, liquidity_proofQ1, attestationQ1] = await provideLiquidityGuardAttestation(program, taker, rfqPDA, quoteMint);
         await commitQuote(
             commit_hashQ1,
             liquidity_proofQ1,
+            attestationQ1,
             taker,
             rfqPDA,
             usdcMint,
@@ -418,10 +425,11 @@ describe("COMPLETE_SETTLEMENT", () => {
             facilitator.publicKey);
 
         // taker2 will commit an invalid quote (smaller quote amount)
-        const [saltQ2, commit_hashQ2, liquidity_proofQ2] = await provideLiquidityGuardAttestation(taker2, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
+        const [saltQ2, commit_hashQ2, liquidity_proofQ2, attestationQ2] = await provideLiquidityGuardAttestation(program, taker2, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
         await commitQuote(
             commit_hashQ2,
             liquidity_proofQ2,
+            attestationQ2,
             taker2,
             rfqPDA,
             usdcMint,
@@ -472,7 +480,7 @@ describe("COMPLETE_SETTLEMENT", () => {
 
         await program.methods
             .revealQuote(Array.from(saltQ1), new anchor.BN(DEFAULT_QUOTE_AMOUNT))
-            .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, config: configPda })
+            .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, revealer: taker.publicKey, config: configPda })
             .signers([taker])
             .rpc();
 
@@ -480,7 +488,7 @@ describe("COMPLETE_SETTLEMENT", () => {
         try {
             await program.methods
                 .revealQuote(Array.from(saltQ2), new anchor.BN(DEFAULT_QUOTE_AMOUNT / 10))
-                .accounts({ rfq: rfqPDA, quote: quote2Pda, taker: taker2.publicKey, config: configPda })
+                .accounts({ rfq: rfqPDA, quote: quote2Pda, taker: taker2.publicKey, revealer: taker2.publicKey, config: configPda })
                 .signers([taker2])
                 .rpc();
         } catch { failed = true; }
@@ -510,6 +518,8 @@ describe("COMPLETE_SETTLEMENT", () => {
                 vaultBaseAta: baseVault,
                 makerBaseAccount,
                 config: configPda,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+                quoteTokenProgram: TOKEN_PROGRAM_ID,
             })
             .signers([maker])
             .rpc();
@@ -544,6 +554,9 @@ describe("COMPLETE_SETTLEMENT", () => {
                 treasuryQuoteAta,
                 feeEscrow,
                 bondsEscrow,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+                quoteTokenProgram: TOKEN_PROGRAM_ID,
             })
             .remainingAccounts([{
                 pubkey: quotePda,
@@ -553,6 +566,10 @@ describe("COMPLETE_SETTLEMENT", () => {
                 pubkey: slashedBondsTrackerPDA,
                 isSigner: false,
                 isWritable: true,
+            }, {
+                pubkey: commitGuardPda,
+                isSigner: false,
+                isWritable: true,
             }])
             .instruction();
 
@@ -561,12 +578,13 @@ describe("COMPLETE_SETTLEMENT", () => {
         completeTx.add(completeSettlementIx);
         await provider.sendAndConfirm(completeTx, [taker]);
 
-        const [rfq, settlement, feesTracker, slashedBondsTracker, quote, quote2] = await Promise.all([
+        const [rfq, settlement, feesTracker, slashedBondsTracker, quoteInfo, commitGuardInfo, quote2] = await Promise.all([
             program.account.rfq.fetch(rfqPDA),
             program.account.settlement.fetch(settlementPDA),
             program.account.feesTracker.fetch(feesTrackerPDA),
             program.account.slashedBondsTracker.fetch(slashedBondsTrackerPDA),
-            program.account.quote.fetch(quotePda),
+            provider.connection.getAccountInfo(quotePda),
+            provider.connection.getAccountInfo(commitGuardPda),
             program.account.quote.fetch(quote2Pda),
         ]);
 
@@ -599,7 +617,10 @@ describe("COMPLETE_SETTLEMENT", () => {
         assert(slashedBondsTracker.seizedAt.eq(rfq.completedAt), "seizedAt in slashedBondsTracker and completedAt in Rfq should be equal");
         assert(slashedBondsTracker.usdcMint.equals(usdcMint), "usdcMint mismatch in slashedBondsTracker");
         assert(slashedBondsTracker.treasuryWallet.equals(treasury.publicKey), "treasury mismatch in slashedBondsTracker");
-        assert(quote.bondsRefundedAt.eq(settlement.completedAt), "quote bondsRefundedAt and settlement completedAt should be equal");
+        assert.strictEqual(quoteInfo, null, "winning quote should be closed on completion");
+        assert.strictEqual(commitGuardInfo, null, "winning commit guard should be closed on completion");
+        assert(settlement.facilitator.equals(facilitator.publicKey), "settlement should record the facilitator");
+        assert(settlement.facilitatorReward.eq(facilitatorFee), "settlement should record the escrowed facilitator reward");
         assert(quote2.bondsRefundedAt === null || quote2.bondsRefundedAt === undefined, "quote2 bondsRefundedAt should be None");
         const [
             makerUsdcBalance,
@@ -648,6 +669,50 @@ describe("COMPLETE_SETTLEMENT", () => {
             "fee escrow should contain facilitator fee in quote tokens"
         );
 
+        // the unrevealed quote was slashed; anyone may close it for taker2
+        await program.methods.closeQuote()
+            .accounts({ taker: taker2.publicKey, config: configPda, rfq: rfqPDA, slashedBondsTracker: slashedBondsTrackerPDA })
+            .rpc();
+
+        const closeRfq = (rewardAccounts: anchor.web3.AccountMeta[] = []) => program.methods.closeRfq()
+            .accounts({
+                maker: maker.publicKey,
+                config: configPda,
+                rfq: rfqPDA,
+                usdcMint,
+                makerPaymentAccount,
+                bondsEscrow,
+                slashedBondsTracker: slashedBondsTrackerPDA,
+                baseMint,
+                vaultBaseAta: baseVault,
+                baseVaultPayer: maker.publicKey,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+                makerBaseAccount,
+                quoteMint,
+                vaultQuoteAta: feeEscrow,
+                quoteVaultPayer: maker.publicKey,
+                quoteTokenProgram: TOKEN_PROGRAM_ID,
+                makerQuoteAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts([
+                { pubkey: settlementPDA, isSigner: false, isWritable: true },
+                { pubkey: feesTrackerPDA, isSigner: false, isWritable: true },
+                { pubkey: maker.publicKey, isSigner: false, isWritable: true },
+                { pubkey: taker.publicKey, isSigner: false, isWritable: true },
+                ...rewardAccounts,
+            ])
+            .rpc();
+
+        // the fee escrow still owes the facilitator its reward
+        let closeBeforeWithdrawErr: any;
+        try {
+            await closeRfq();
+        } catch (e) {
+            closeBeforeWithdrawErr = e;
+        }
+        assert.ok(String(closeBeforeWithdrawErr).includes("RewardNotWithdrawn"), "closeRfq() should fail before the reward is withdrawn");
+
         let withdrawUnselectedFailed = false;
         try {
             await program.methods.withdrawReward()
@@ -656,11 +721,12 @@ describe("COMPLETE_SETTLEMENT", () => {
                     config: configPda,
                     rfq: rfqPDA,
                     settlement: settlementPDA,
-                    quote: quote2Pda,
                     quoteMint,
                     feeEscrow,
                     facilitatorAta: facilitatorQuoteAta,
-                    facilitatorRewardTracker: facilitatorRewardTrackerPDA,
+                    // tracker bound to the unselected quote
+                    facilitatorRewardTracker: facilitatorRewardTrackerPda(rfqPDA, taker2.publicKey, facilitator.publicKey)[0],
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([facilitator])
                 .rpc();
@@ -677,11 +743,11 @@ describe("COMPLETE_SETTLEMENT", () => {
                     config: configPda,
                     rfq: rfqPDA,
                     settlement: settlementPDA,
-                    quote: quotePda,
                     quoteMint,
                     feeEscrow,
                     facilitatorAta: otherFacilitatorQuoteAta,
                     facilitatorRewardTracker: otherFacilitatorRewardTrackerPDA,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([otherFacilitator])
                 .rpc();
@@ -696,9 +762,9 @@ describe("COMPLETE_SETTLEMENT", () => {
                 config: configPda,
                 rfq: rfqPDA,
                 settlement: settlementPDA,
-                quote: quotePda,
                 quoteMint,
                 feeEscrow,
+                quoteTokenProgram: TOKEN_PROGRAM_ID,
             })
             .signers([facilitator])
             .rpc();
@@ -740,11 +806,11 @@ describe("COMPLETE_SETTLEMENT", () => {
                     config: configPda,
                     rfq: rfqPDA,
                     settlement: settlementPDA,
-                    quote: quotePda,
                     quoteMint,
                     feeEscrow,
                     facilitatorAta: facilitatorQuoteAta,
                     facilitatorRewardTracker: facilitatorRewardTrackerPDA,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([facilitator])
                 .rpc();
@@ -752,16 +818,60 @@ describe("COMPLETE_SETTLEMENT", () => {
             withdrawFailed = true;
         }
         assert(withdrawFailed, "facilitator should not be able to withdraw twice");
+
+        // one unit each sent into the bonds escrow and base vault must not keep them open
+        await transfer(provider.connection, taker, takerPaymentAccount, bondsEscrow, taker, 1);
+        await transfer(provider.connection, taker, takerBaseAccount, baseVault, taker, 1);
+        const [makerUsdcBeforeClose, makerBaseBeforeClose] = await Promise.all([
+            getAndLogBalance("Before close RFQ", "Maker USDC", makerPaymentAccount),
+            getAndLogBalance("Before close RFQ", "Maker Base", makerBaseAccount),
+        ]);
+
+        // nothing is owed any more: the RFQ and what it owns can be closed, leftovers go to the maker
+        const takerLamportsBefore = await provider.connection.getBalance(taker.publicKey);
+        const facilitatorLamportsBefore = await provider.connection.getBalance(facilitator.publicKey);
+        const closeSig = await closeRfq([
+            { pubkey: facilitatorRewardTrackerPDA, isSigner: false, isWritable: true },
+            { pubkey: facilitator.publicKey, isSigner: false, isWritable: true },
+        ]);
+
+        for (const address of [rfqPDA, bondsEscrow, slashedBondsTrackerPDA, baseVault, feeEscrow, settlementPDA, feesTrackerPDA, facilitatorRewardTrackerPDA, quote2Pda]) {
+            assert.strictEqual(await provider.connection.getAccountInfo(address), null, `${address.toBase58()} should be closed`);
+        }
+        assert((await provider.connection.getBalance(taker.publicKey)) > takerLamportsBefore, "fees tracker rent should go back to the taker");
+        assert((await provider.connection.getBalance(facilitator.publicKey)) > facilitatorLamportsBefore, "reward tracker rent should go back to the facilitator");
+        assert.ok(
+            (await getAndLogBalance("After close RFQ", "Maker USDC", makerPaymentAccount)).eq(makerUsdcBeforeClose.addn(1)),
+            "bonds escrow leftover should be swept to the maker"
+        );
+        assert.ok(
+            (await getAndLogBalance("After close RFQ", "Maker Base", makerBaseAccount)).eq(makerBaseBeforeClose.addn(1)),
+            "base vault leftover should be swept to the maker"
+        );
+        const [reclaimed] = await cpiEvents(program, closeSig);
+        assert.strictEqual(reclaimed.name, "rfqReclaimed");
+        assert.strictEqual(reclaimed.data.settlementsClosed, 1);
     });
 
+    type SettlementMints = {
+        baseMint: PublicKey,
+        quoteMint: PublicKey,
+        baseTokenProgram: PublicKey,
+        quoteTokenProgram: PublicKey,
+    };
+
     /**
-     * Run a full RFQ→settlement lifecycle with the given quoteAmount and takerFeeBps,
-     * then return the on-chain feesTracker and treasury quote balance.
+     * Run a single-taker RFQ from init through select_quote with the given quoteAmount and takerFeeBps.
+     * `hookAccounts` are the transfer-hook accounts passed in remaining_accounts on every base/quote leg.
      */
-    const runSettlementWithFeeParams = async (
+    const runSelectionWithFeeParams = async (
         quoteAmount: number,
         takerFeeBps: number,
+        mints: SettlementMints = { baseMint, quoteMint, baseTokenProgram: TOKEN_PROGRAM_ID, quoteTokenProgram: TOKEN_PROGRAM_ID },
+        extraFunding = { base: 0, quote: 0 }, // headroom for transfer fees
+        hookAccounts: anchor.web3.AccountMeta[] = [],
     ) => {
+        const { baseMint: base, quoteMint: quote, baseTokenProgram, quoteTokenProgram } = mints;
         const maker = Keypair.generate();
         const taker = Keypair.generate();
         await Promise.all([fund(maker), fund(taker)]);
@@ -769,44 +879,55 @@ describe("COMPLETE_SETTLEMENT", () => {
         const expectedTotalFee = computeFee(quoteAmount, takerFeeBps);
         const u = uuidBytes();
         const [rfqPDA] = rfqPda(maker.publicKey, u);
-        const [settlementPDA] = settlementPda(rfqPDA);
-        const [feesTrackerPDA] = feesTrackerPda(rfqPDA);
+        const [settlementPDA] = settlementPda(rfqPDA, taker.publicKey);
+        const [feesTrackerPDA] = feesTrackerPda(rfqPDA, taker.publicKey);
         const [slashedBondsTrackerPDA] = slashedBondsTrackerPda(rfqPDA);
 
         const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
-        const makerBaseAccount = getAssociatedTokenAddressSync(baseMint, maker.publicKey);
-        const makerQuoteAccount = getAssociatedTokenAddressSync(quoteMint, maker.publicKey);
+        const makerBaseAccount = getAssociatedTokenAddressSync(base, maker.publicKey, false, baseTokenProgram);
+        const makerQuoteAccount = getAssociatedTokenAddressSync(quote, maker.publicKey, false, quoteTokenProgram);
         const takerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, taker.publicKey);
-        const takerBaseAccount = getAssociatedTokenAddressSync(baseMint, taker.publicKey);
-        const takerQuoteAccount = getAssociatedTokenAddressSync(quoteMint, taker.publicKey);
+        const takerBaseAccount = getAssociatedTokenAddressSync(base, taker.publicKey, false, baseTokenProgram);
+        const takerQuoteAccount = getAssociatedTokenAddressSync(quote, taker.publicKey, false, quoteTokenProgram);
         const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqPDA, true);
-        const baseVault = getAssociatedTokenAddressSync(baseMint, rfqPDA, true);
-        const feeEscrow = getAssociatedTokenAddressSync(quoteMint, rfqPDA, true);
+        const baseVault = getAssociatedTokenAddressSync(base, rfqPDA, true, baseTokenProgram);
+        const feeEscrow = getAssociatedTokenAddressSync(quote, rfqPDA, true, quoteTokenProgram);
         const treasuryPaymentAccount = getAssociatedTokenAddressSync(usdcMint, treasury.publicKey);
-        const treasuryQuoteAta = getAssociatedTokenAddressSync(quoteMint, treasury.publicKey);
+        const treasuryQuoteAta = getAssociatedTokenAddressSync(quote, treasury.publicKey, false, quoteTokenProgram);
 
         // Mint USDC for bonds + base for maker + quote for taker (quote_amount + fees)
         await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey)
             .then(a => mintTo(provider.connection, admin, usdcMint, a.address, admin, DEFAULT_BOND_AMOUNT));
-        await getOrCreateAssociatedTokenAccount(provider.connection, admin, baseMint, maker.publicKey)
-            .then(a => mintTo(provider.connection, admin, baseMint, a.address, admin, DEFAULT_BASE_AMOUNT));
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, base, maker.publicKey, false, undefined, undefined, baseTokenProgram)
+            .then(a => mintTo(provider.connection, admin, base, a.address, admin, DEFAULT_BASE_AMOUNT + extraFunding.base, [], undefined, baseTokenProgram));
         await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, taker.publicKey)
             .then(a => mintTo(provider.connection, admin, usdcMint, a.address, admin, DEFAULT_BOND_AMOUNT));
-        await getOrCreateAssociatedTokenAccount(provider.connection, admin, quoteMint, taker.publicKey)
-            .then(a => mintTo(provider.connection, admin, quoteMint, a.address, admin, quoteAmount + expectedTotalFee));
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, quote, taker.publicKey, false, undefined, undefined, quoteTokenProgram)
+            .then(a => mintTo(provider.connection, admin, quote, a.address, admin, quoteAmount + expectedTotalFee + extraFunding.quote, [], undefined, quoteTokenProgram));
 
         // INIT RFQ
         await program.methods
             .initRfq(
-                Array.from(u), baseMint, quoteMint,
+                Array.from(u), base, quote,
                 new anchor.BN(DEFAULT_BOND_AMOUNT),
                 new anchor.BN(DEFAULT_BASE_AMOUNT),
                 new anchor.BN(1),
                 takerFeeBps,
                 commitTTL, revealTTL, selectionTTL, fundingTTL,
                 null, // no facilitator — all fees go to treasury
+                false, // single winner
+                false,
+                false,
+                { firstPrice: {} },
+                { sell: {} },
+                new anchor.BN(0),
+                { base: {} },
+                new anchor.BN(0),
+                false,
+                null,
             )
             .accounts({
+                baseMint: base, quoteMint: quote,
                 maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow,
                 makerPaymentAccount,
                 systemProgram: SystemProgram.programId,
@@ -821,15 +942,16 @@ describe("COMPLETE_SETTLEMENT", () => {
             .accounts({
                 maker: maker.publicKey, rfq: rfqPDA, config: configPda,
                 bondsEscrow, makerPaymentAccount, usdcMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
             })
             .signers([maker])
             .rpc();
 
         // COMMIT QUOTE
-        const [salt, commitHash, liquidityProof] = await provideLiquidityGuardAttestation(
-            taker, rfqPDA, quoteMint, quoteAmount, DEFAULT_BOND_AMOUNT, takerFeeBps,
+        const [salt, commitHash, liquidityProof, attestation] = await provideLiquidityGuardAttestation(
+            program, taker, rfqPDA, quote, quoteAmount, DEFAULT_BOND_AMOUNT, takerFeeBps,
         );
-        await commitQuote(commitHash, liquidityProof, taker, rfqPDA, usdcMint, configPda, takerPaymentAccount);
+        await commitQuote(commitHash, liquidityProof, attestation, taker, rfqPDA, usdcMint, configPda, takerPaymentAccount);
 
         const [quotePda] = PublicKey.findProgramAddressSync(
             [Buffer.from("quote"), rfqPDA.toBuffer(), taker.publicKey.toBuffer()],
@@ -846,7 +968,7 @@ describe("COMPLETE_SETTLEMENT", () => {
         // REVEAL QUOTE
         await program.methods
             .revealQuote(Array.from(salt), new anchor.BN(quoteAmount))
-            .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, config: configPda })
+            .accounts({ rfq: rfqPDA, quote: quotePda, taker: taker.publicKey, revealer: taker.publicKey, config: configPda })
             .signers([taker])
             .rpc();
 
@@ -857,28 +979,61 @@ describe("COMPLETE_SETTLEMENT", () => {
         await program.methods.selectQuote()
             .accounts({
                 maker: maker.publicKey, rfq: rfqPDA, quote: quotePda,
-                baseMint, quoteMint, vaultBaseAta: baseVault, makerBaseAccount, config: configPda,
+                baseMint: base, quoteMint: quote, vaultBaseAta: baseVault, makerBaseAccount, config: configPda,
+                baseTokenProgram,
+                quoteTokenProgram,
             })
+            .remainingAccounts(hookAccounts)
             .signers([maker])
             .rpc();
 
+        return {
+            maker, taker, rfqPDA, quotePda, settlementPDA, feesTrackerPDA, slashedBondsTrackerPDA,
+            makerPaymentAccount, makerBaseAccount, makerQuoteAccount, takerPaymentAccount, takerBaseAccount, takerQuoteAccount,
+            bondsEscrow, baseVault, feeEscrow, treasuryPaymentAccount, treasuryQuoteAta, expectedTotalFee,
+        };
+    };
+
+    /**
+     * Run a full RFQ→settlement lifecycle with the given quoteAmount and takerFeeBps,
+     * then return the on-chain feesTracker and treasury quote balance.
+     */
+    const runSettlementWithFeeParams = async (
+        quoteAmount: number,
+        takerFeeBps: number,
+        mints: SettlementMints = { baseMint, quoteMint, baseTokenProgram: TOKEN_PROGRAM_ID, quoteTokenProgram: TOKEN_PROGRAM_ID },
+        extraFunding = { base: 0, quote: 0 }, // headroom for transfer fees
+        hookAccounts: anchor.web3.AccountMeta[] = [],
+    ) => {
+        const { baseMint: base, quoteMint: quote, baseTokenProgram, quoteTokenProgram } = mints;
+        const {
+            taker, rfqPDA, quotePda, settlementPDA, feesTrackerPDA, slashedBondsTrackerPDA,
+            makerPaymentAccount, makerQuoteAccount, takerPaymentAccount, takerBaseAccount, takerQuoteAccount,
+            bondsEscrow, baseVault, feeEscrow, treasuryPaymentAccount, treasuryQuoteAta, expectedTotalFee,
+        } = await runSelectionWithFeeParams(quoteAmount, takerFeeBps, mints, extraFunding, hookAccounts);
+
         // COMPLETE SETTLEMENT
         const completeIx = await program.methods.completeSettlement()
             .accounts({
                 taker: taker.publicKey, config: configPda,
                 treasuryWallet: treasury.publicKey,
                 rfq: rfqPDA, settlement: settlementPDA,
-                usdcMint, baseMint, quoteMint,
+                usdcMint, baseMint: base, quoteMint: quote,
                 takerPaymentAccount, makerPaymentAccount,
                 vaultBaseAta: baseVault, takerBaseAccount,
                 makerQuoteAccount, takerQuoteAccount,
                 feesTracker: feesTrackerPDA,
                 treasuryAta: treasuryPaymentAccount,
                 treasuryQuoteAta, feeEscrow, bondsEscrow,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                baseTokenProgram,
+                quoteTokenProgram,
             })
             .remainingAccounts([
                 { pubkey: quotePda, isSigner: false, isWritable: true },
                 { pubkey: slashedBondsTrackerPDA, isSigner: false, isWritable: true },
+                { pubkey: await commitGuardPdaOf(quotePda), isSigner: false, isWritable: true },
+                ...hookAccounts,
             ])
             .instruction();
 
@@ -888,8 +1043,14 @@ describe("COMPLETE_SETTLEMENT", () => {
         await provider.sendAndConfirm(tx, [taker]);
 
         const feesTracker = await program.account.feesTracker.fetch(feesTrackerPDA);
+        const [makerQuoteBalance, takerBaseBalance] = await Promise.all([
+            getAndLogBalance("After complete settlement", "Maker Quote", makerQuoteAccount),
+            getAndLogBalance("After complete settlement", "Taker Base", takerBaseAccount),
+        ]);
+
+        const treasuryQuoteBalance = await getAndLogBalance("After complete settlement", "Treasury Quote", treasuryQuoteAta);
 
-        return { feesTracker, expectedTotalFee };
+        return { feesTracker, expectedTotalFee, makerQuoteBalance, takerBaseBalance, treasuryQuoteBalance };
     };
 
     describe("on-chain fee uplift (floor + min 1)", () => {
@@ -941,4 +1102,788 @@ describe("COMPLETE_SETTLEMENT", () => {
         });
     });
 
+    describe("token-2022 mints", () => {
+        it("settles with Token-2022 base and quote mints (USDC bonds stay on SPL Token)", async () => {
+            const [base2022, quote2022] = await Promise.all(
+                [9, 9].map(d => createMint(
+                    provider.connection,
+                    admin,
+                    admin.publicKey,
+                    null,
+                    d,
+                    undefined,
+                    undefined,
+                    TOKEN_2022_PROGRAM_ID,
+                )));
+
+            const { feesTracker, expectedTotalFee, makerQuoteBalance, takerBaseBalance } =
+                await runSettlementWithFeeParams(1_000_000, 100, {
+                    baseMint: base2022,
+                    quoteMint: quote2022,
+                    baseTokenProgram: TOKEN_2022_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_2022_PROGRAM_ID,
+                });
+
+            assert.ok(feesTracker.quoteMint.equals(quote2022), "quoteMint mismatch in feesTracker");
+            assert.ok(feesTracker.amount.eq(new anchor.BN(expectedTotalFee)), "fee mismatch for Token-2022 quote mint");
+            assert.ok(makerQuoteBalance.eq(new anchor.BN(1_000_000)), "maker should receive quote amount in Token-2022 quote");
+            assert.ok(takerBaseBalance.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "taker should receive base amount in Token-2022 base");
+        });
+
+        it("grosses up transfer-fee mints so receivers get the agreed amounts", async () => {
+            const feeBps = 100; // 1%
+            const maxFee = 1_000_000_000;
+            const [baseFee, quoteFee] = await Promise.all([
+                createTransferFeeMint(provider.connection, admin, feeBps, BigInt(maxFee)),
+                createTransferFeeMint(provider.connection, admin, feeBps, BigInt(maxFee)),
+            ]);
+            const quoteAmount = 1_000_000;
+
+            const { feesTracker, expectedTotalFee, makerQuoteBalance, takerBaseBalance, treasuryQuoteBalance } =
+                await runSettlementWithFeeParams(quoteAmount, 100, {
+                    baseMint: baseFee,
+                    quoteMint: quoteFee,
+                    baseTokenProgram: TOKEN_2022_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_2022_PROGRAM_ID,
+                }, {
+                    base: grossUp(grossUp(DEFAULT_BASE_AMOUNT, feeBps, maxFee), feeBps, maxFee) - DEFAULT_BASE_AMOUNT,
+                    quote: grossUp(quoteAmount, feeBps, maxFee) - quoteAmount
+                        + grossUp(computeFee(quoteAmount, 100), feeBps, maxFee) - computeFee(quoteAmount, 100),
+                });
+
+            assert.ok(feesTracker.amount.eq(new anchor.BN(expectedTotalFee)), "fee tracker records the net fee");
+            assert.ok(makerQuoteBalance.eq(new anchor.BN(quoteAmount)), "maker should net the quote amount");
+            assert.ok(takerBaseBalance.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "taker should net the base amount");
+            assert.ok(treasuryQuoteBalance.eq(new anchor.BN(expectedTotalFee)), "treasury should net the fee");
+        });
+
+        it("forwards transfer-hook accounts through select_quote, complete_settlement and close_incomplete", async () => {
+            const [baseHook, quoteHook] = await Promise.all([
+                createTransferHookMint(provider.connection, admin, hookProgram),
+                createTransferHookMint(provider.connection, admin, hookProgram),
+            ]);
+            const mints = {
+                baseMint: baseHook,
+                quoteMint: quoteHook,
+                baseTokenProgram: TOKEN_2022_PROGRAM_ID,
+                quoteTokenProgram: TOKEN_2022_PROGRAM_ID,
+            };
+            // hook accounts sit next to the quote / slashed-bonds-tracker / commit-guard accounts
+            const hookAccounts = [...transferHookAccounts(hookProgram, baseHook), ...transferHookAccounts(hookProgram, quoteHook)];
+            const hookTransfers = (mint: PublicKey) =>
+                hookProgram.account.counter.fetch(hookCounterPda(hookProgram, mint)).then(c => c.transfers.toNumber());
+
+            // select_quote deposits base; complete_settlement delivers base, pays the maker and the treasury fee
+            const quoteAmount = 1_000_000;
+            const { makerQuoteBalance, takerBaseBalance } =
+                await runSettlementWithFeeParams(quoteAmount, 100, mints, { base: 0, quote: 0 }, hookAccounts);
+            assert.ok(makerQuoteBalance.eq(new anchor.BN(quoteAmount)), "maker should receive the hooked quote mint");
+            assert.ok(takerBaseBalance.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "taker should receive the hooked base mint");
+            assert.strictEqual(await hookTransfers(baseHook), 2, "baseHook hook transfers");
+            assert.strictEqual(await hookTransfers(quoteHook), 2, "quoteHook hook transfers");
+
+            // the taker never funds: close_incomplete refunds the deposited base through the hook
+            const { maker, rfqPDA, settlementPDA, slashedBondsTrackerPDA, makerPaymentAccount, makerBaseAccount, bondsEscrow, baseVault } =
+                await runSelectionWithFeeParams(quoteAmount, 100, mints, { base: 0, quote: 0 }, hookAccounts);
+            const settlement = await program.account.settlement.fetch(settlementPDA);
+            await waitForChainTime(provider.connection, settlement.fundingDeadline.toNumber() + 1, "funding deadline");
+            await program.methods.closeIncomplete()
+                .accounts({
+                    keeper: maker.publicKey,
+                    maker: maker.publicKey,
+                    config: configPda,
+                    rfq: rfqPDA,
+                    settlement: settlementPDA,
+                    settlementRentPayer: maker.publicKey,
+                    baseMint: baseHook,
+                    vaultBaseAta: baseVault,
+                    makerBaseAccount,
+                    usdcMint,
+                    bondsEscrow,
+                    makerPaymentAccount,
+                    treasuryWallet: treasury.publicKey,
+                    slashedBondsTracker: slashedBondsTrackerPDA,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    baseTokenProgram: TOKEN_2022_PROGRAM_ID,
+                })
+                .remainingAccounts(hookAccounts)
+                .signers([maker])
+                .rpc();
+
+            const makerBaseBalance = await getAndLogBalance("After close incomplete", "Maker Base", makerBaseAccount);
+            assert.ok(makerBaseBalance.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "maker should get the hooked base back");
+            assert.strictEqual(await hookTransfers(baseHook), 4, "baseHook hook transfers");
+            assert.strictEqual(await hookTransfers(quoteHook), 2, "quoteHook hook transfers");
+        });
+    });
+
+    // Opens an RFQ, has every taker commit and reveal its quote, then waits for the reveal deadline.
+    // In quote-size mode `quoteAmounts` are the base amounts the takers reveal.
+    const runAuctionToReveal = async (
+        quoteAmounts: number[],
+        takerQuoteFunding: number[],
+        takerFeeBps: number,
+        {
+            allowPartialFills = false,
+            autoSelectBest = false,
+            requireBestQuote = false,
+            pricingMode = { firstPrice: {} } as object,
+            side = { sell: {} } as object,
+            maxQuoteAmount = 0,
+            makerQuoteFunding = 0, // Buy: the maker deposits quote instead of base
+            sizeMode = { base: {} } as object,
+            quoteSize = 0,
+            firm = false, // the maker's base goes into the vault at open
+            separateVerifyIx = false, // put a compute-budget ix between each verify ix and commit_quote
+            revealDelegate = null as Keypair | null, // reveals on every taker's behalf
+            privateRfq = false, // only the takers are allowlisted
+        } = {},
+    ) => {
+        const buying = "buy" in side;
+        const quoteSized = "quote" in sizeMode;
+        const maker = Keypair.generate();
+        const takers = quoteAmounts.map(() => Keypair.generate());
+        await Promise.all([fund(maker), ...takers.map(t => fund(t))]);
+
+        const u = uuidBytes();
+        const [rfqPDA] = rfqPda(maker.publicKey, u);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqPDA, true);
+
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey)
+            .then(a => mintTo(provider.connection, admin, usdcMint, a.address, admin, DEFAULT_BOND_AMOUNT));
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, baseMint, maker.publicKey)
+            .then(a => mintTo(provider.connection, admin, baseMint, a.address, admin, buying ? 1 : DEFAULT_BASE_AMOUNT));
+        // finalize_auction expects the maker's quote ATA to exist on both sides
+        const makerQuoteAta = await getOrCreateAssociatedTokenAccount(provider.connection, admin, quoteMint, maker.publicKey);
+        if (buying) {
+            await mintTo(provider.connection, admin, quoteMint, makerQuoteAta.address, admin, makerQuoteFunding);
+        }
+        for (const [i, taker] of takers.entries()) {
+            await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, taker.publicKey)
+                .then(a => mintTo(provider.connection, admin, usdcMint, a.address, admin, DEFAULT_BOND_AMOUNT));
+            await getOrCreateAssociatedTokenAccount(provider.connection, admin, quoteMint, taker.publicKey)
+                .then(a => mintTo(provider.connection, admin, quoteMint, a.address, admin, takerQuoteFunding[i]));
+            if (buying) {
+                await getOrCreateAssociatedTokenAccount(provider.connection, admin, baseMint, taker.publicKey)
+                    .then(a => mintTo(provider.connection, admin, baseMint, a.address, admin,
+                        quoteSized ? quoteAmounts[i] : DEFAULT_BASE_AMOUNT));
+            }
+        }
+
+        await program.methods
+            .initRfq(
+                Array.from(u), baseMint, quoteMint,
+                new anchor.BN(DEFAULT_BOND_AMOUNT),
+                new anchor.BN(DEFAULT_BASE_AMOUNT),
+                new anchor.BN(1),
+                takerFeeBps,
+                commitTTL, revealTTL, selectionTTL, fundingTTL,
+                null,
+                allowPartialFills,
+                autoSelectBest,
+                requireBestQuote,
+                pricingMode,
+                side,
+                new anchor.BN(maxQuoteAmount),
+                sizeMode,
+                new anchor.BN(quoteSize),
+                firm,
+                privateRfq ? Array.from(allowlistRoot(takers.map(t => t.publicKey))) : null,
+            )
+            .accounts({
+                baseMint, quoteMint,
+                maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow,
+                makerPaymentAccount,
+                systemProgram: SystemProgram.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([maker])
+            .rpc();
+
+        await program.methods.openRfq()
+            .accounts({
+                maker: maker.publicKey, rfq: rfqPDA, config: configPda,
+                bondsEscrow, makerPaymentAccount, usdcMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                ...(firm ? {
+                    baseMint,
+                    makerBaseAccount: getAssociatedTokenAddressSync(baseMint, maker.publicKey),
+                    vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqPDA, true),
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                } : {}),
+            })
+            .signers([maker])
+            .rpc();
+
+        const salts = [];
+        for (const [i, taker] of takers.entries()) {
+            const [salt, commitHash, liquidityProof, attestation] = await provideLiquidityGuardAttestation(
+                program, taker, rfqPDA, quoteSized ? baseMint : quoteMint, quoteAmounts[i], DEFAULT_BOND_AMOUNT, takerFeeBps,
+            );
+            const takerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, taker.publicKey);
+            const takerKeys = takers.map(t => t.publicKey);
+            const proof = privateRfq ? allowlistProof(takerKeys, taker.publicKey) : [];
+            if (privateRfq && i === 0) {
+                // another taker's proof does not admit this one
+                let failed = false;
+                try {
+                    await commitQuote(commitHash, liquidityProof, attestation, taker, rfqPDA, usdcMint, configPda, takerPaymentAccount,
+                        null, separateVerifyIx, revealDelegate?.publicKey ?? null, allowlistProof(takerKeys, takers[1].publicKey));
+                } catch (e) {
+                    failed = true;
+                    assert.ok(String(e).includes("TakerNotAllowlisted"), String(e));
+                }
+                assert(failed, "commit_quote should reject a proof for another taker");
+            }
+            await commitQuote(commitHash, liquidityProof, attestation, taker, rfqPDA, usdcMint, configPda, takerPaymentAccount,
+                null, separateVerifyIx, revealDelegate?.publicKey ?? null, proof);
+            salts.push(salt);
+        }
+
+        const rfqAfterCommit = await program.account.rfq.fetch(rfqPDA);
+        const commitDeadline = rfqAfterCommit.openedAt!.toNumber() + rfqAfterCommit.commitTtlSecs;
+        const revealDeadline = commitDeadline + rfqAfterCommit.revealTtlSecs;
+        await waitForChainTime(provider.connection, commitDeadline, "commit deadline");
+
+        for (const [i, taker] of takers.entries()) {
+            await program.methods
+                .revealQuote(Array.from(salts[i]), new anchor.BN(quoteAmounts[i]))
+                .accounts({
+                    rfq: rfqPDA, quote: quotePdaOf(rfqPDA, taker.publicKey), taker: taker.publicKey,
+                    revealer: (revealDelegate ?? taker).publicKey, config: configPda,
+                })
+                .signers([revealDelegate ?? taker])
+                .rpc();
+        }
+        await waitForChainTime(provider.connection, revealDeadline, "reveal deadline");
+
+        return { maker, takers, rfqPDA, makerPaymentAccount, bondsEscrow };
+    };
+
+    // Completes the settlement of one winning taker (SPL Token base/quote mints, either side).
+    const completeFor = async (rfqPDA: PublicKey, maker: Keypair, taker: Keypair) => {
+        const completeIx = await program.methods.completeSettlement()
+            .accounts({
+                taker: taker.publicKey, config: configPda,
+                treasuryWallet: treasury.publicKey,
+                rfq: rfqPDA, settlement: settlementPda(rfqPDA, taker.publicKey)[0],
+                usdcMint, baseMint, quoteMint,
+                takerPaymentAccount: getAssociatedTokenAddressSync(usdcMint, taker.publicKey),
+                makerPaymentAccount: getAssociatedTokenAddressSync(usdcMint, maker.publicKey),
+                vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqPDA, true),
+                takerBaseAccount: getAssociatedTokenAddressSync(baseMint, taker.publicKey),
+                makerBaseAccount: getAssociatedTokenAddressSync(baseMint, maker.publicKey),
+                makerQuoteAccount: getAssociatedTokenAddressSync(quoteMint, maker.publicKey),
+                takerQuoteAccount: getAssociatedTokenAddressSync(quoteMint, taker.publicKey),
+                feesTracker: feesTrackerPda(rfqPDA, taker.publicKey)[0],
+                treasuryAta: getAssociatedTokenAddressSync(usdcMint, treasury.publicKey),
+                treasuryQuoteAta: getAssociatedTokenAddressSync(quoteMint, treasury.publicKey),
+                feeEscrow: getAssociatedTokenAddressSync(quoteMint, rfqPDA, true),
+                bondsEscrow: getAssociatedTokenAddressSync(usdcMint, rfqPDA, true),
+                tokenProgram: TOKEN_PROGRAM_ID,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+                quoteTokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts([
+                { pubkey: quotePdaOf(rfqPDA, taker.publicKey), isSigner: false, isWritable: true },
+                { pubkey: slashedBondsTrackerPda(rfqPDA)[0], isSigner: false, isWritable: true },
+                { pubkey: await commitGuardPdaOf(quotePdaOf(rfqPDA, taker.publicKey)), isSigner: false, isWritable: true },
+            ])
+            .instruction();
+        const tx = new anchor.web3.Transaction();
+        tx.add(ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }));
+        tx.add(completeIx);
+        return provider.sendAndConfirm(tx, [taker]);
+    };
+
+    describe("partial fills", () => {
+        it("splits the base amount across two winners with their own settlements", async () => {
+            const quoteAmounts = [1_000_000, 1_200_000];
+            const allocations = [DEFAULT_BASE_AMOUNT * 3 / 4, DEFAULT_BASE_AMOUNT / 4];
+            // pro-rated, rounded up in favour of the maker
+            const proratedQuote = quoteAmounts.map((q, i) => Math.ceil(q * allocations[i] / DEFAULT_BASE_AMOUNT));
+            const takerFeeBps = 100;
+
+            const { maker, takers, rfqPDA, makerPaymentAccount } = await runAuctionToReveal(
+                quoteAmounts,
+                proratedQuote.map(q => q + computeFee(q, takerFeeBps)),
+                takerFeeBps,
+                { allowPartialFills: true },
+            );
+            const makerBaseAccount = getAssociatedTokenAddressSync(baseMint, maker.publicKey);
+            const makerQuoteAccount = getAssociatedTokenAddressSync(quoteMint, maker.publicKey);
+
+            // Anyone may create the fee escrow ATA ahead of selection; its rent is not the maker's to reclaim
+            const stranger = Keypair.generate();
+            await fund(stranger);
+            await getOrCreateAssociatedTokenAccount(provider.connection, stranger, quoteMint, rfqPDA, true);
+
+            // SELECT QUOTES: [quote_i, settlement_i] pairs in allocation order
+            await program.methods.selectQuotes(allocations.map(a => new anchor.BN(a)))
+                .accounts({
+                    maker: maker.publicKey, rfq: rfqPDA, config: configPda,
+                    baseMint, quoteMint,
+                    vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqPDA, true),
+                    makerBaseAccount,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .remainingAccounts(takers.flatMap(t => [
+                    { pubkey: quotePdaOf(rfqPDA, t.publicKey), isSigner: false, isWritable: true },
+                    { pubkey: settlementPda(rfqPDA, t.publicKey)[0], isSigner: false, isWritable: true },
+                ]))
+                .signers([maker])
+                .rpc();
+
+            const rfqSelected = await program.account.rfq.fetch(rfqPDA);
+            assert.ok("selected" in rfqSelected.state, "rfq should be Selected");
+            assert.equal(rfqSelected.selectedCount, 2);
+            assert.equal(rfqSelected.selectedQuote, null, "multi-winner RFQs have no single selected quote");
+            assert.ok(rfqSelected.baseVaultPayer.equals(maker.publicKey), "select_quotes created the base vault");
+            assert.equal(rfqSelected.quoteVaultPayer, null, "a pre-existing fee escrow has no recorded payer");
+
+            for (const [i, taker] of takers.entries()) {
+                const settlement = await program.account.settlement.fetch(settlementPda(rfqPDA, taker.publicKey)[0]);
+                assert.ok(settlement.baseAmount.eq(new anchor.BN(allocations[i])), "settlement base allocation");
+                assert.ok(settlement.quoteAmount.eq(new anchor.BN(proratedQuote[i])), "settlement pro-rated quote");
+
+                await completeFor(rfqPDA, maker, taker);
+
+                const rfqAfter = await program.account.rfq.fetch(rfqPDA);
+                assert.equal(rfqAfter.settledCount, i + 1);
+                if (i < takers.length - 1) {
+                    assert.ok("selected" in rfqAfter.state, "rfq stays Selected until every winner settles");
+                }
+            }
+
+            const rfqFinal = await program.account.rfq.fetch(rfqPDA);
+            assert.ok("settled" in rfqFinal.state, "rfq should be Settled once all winners settled");
+
+            const [makerQuoteBalance, makerBondBalance, ...takerBaseBalances] = await Promise.all([
+                getAndLogBalance("After partial fills", "Maker Quote", makerQuoteAccount),
+                getAndLogBalance("After partial fills", "Maker Payment", makerPaymentAccount),
+                ...takers.map(t => getAndLogBalance("After partial fills", "Taker Base",
+                    getAssociatedTokenAddressSync(baseMint, t.publicKey))),
+            ]);
+            assert.ok(makerQuoteBalance.eq(new anchor.BN(proratedQuote[0] + proratedQuote[1])), "maker receives both pro-rated legs");
+            assert.ok(makerBondBalance.eq(new anchor.BN(DEFAULT_BOND_AMOUNT)), "maker bond refunded on the last settlement");
+            takerBaseBalances.forEach((b, i) =>
+                assert.ok(b.eq(new anchor.BN(allocations[i])), `taker ${i} receives its base allocation`));
+        });
+    });
+
+    describe("verify instruction index", () => {
+        it("commits with other instructions between the verify ix and commit_quote", async () => {
+            const quoteAmounts = [1_000_000, 1_200_000];
+            const takerFeeBps = 100;
+            const { takers, rfqPDA } = await runAuctionToReveal(
+                quoteAmounts,
+                quoteAmounts.map(q => q + computeFee(q, takerFeeBps)),
+                takerFeeBps,
+                { separateVerifyIx: true },
+            );
+            const rfq = await program.account.rfq.fetch(rfqPDA);
+            assert.strictEqual(rfq.committedCount, takers.length, "every commit should land");
+            assert.strictEqual(rfq.revealedCount, takers.length, "every commit should reveal");
+        });
+    });
+
+    describe("reveal delegate", () => {
+        it("lets the reveal delegate reveal on the takers' behalf", async () => {
+            const quoteAmounts = [1_000_000, 1_200_000];
+            const takerFeeBps = 100;
+            const relayer = Keypair.generate();
+            await fund(relayer);
+            const { takers, rfqPDA } = await runAuctionToReveal(
+                quoteAmounts,
+                quoteAmounts.map(q => q + computeFee(q, takerFeeBps)),
+                takerFeeBps,
+                { revealDelegate: relayer },
+            );
+            for (const [i, taker] of takers.entries()) {
+                const quote = await program.account.quote.fetch(quotePdaOf(rfqPDA, taker.publicKey));
+                assert.ok(quote.revealDelegate.equals(relayer.publicKey), "reveal delegate recorded at commit");
+                assert.ok(quote.quoteAmount.eq(new anchor.BN(quoteAmounts[i])), "delegated reveal binds the taker's commit");
+            }
+            assert.strictEqual((await program.account.rfq.fetch(rfqPDA)).revealedCount, takers.length);
+        });
+    });
+
+    describe("private RFQ", () => {
+        it("only accepts commits from allowlisted takers", async () => {
+            const quoteAmounts = [1_000_000, 1_200_000, 1_100_000];
+            const takerFeeBps = 100;
+            const { takers, rfqPDA } = await runAuctionToReveal(
+                quoteAmounts,
+                quoteAmounts.map(q => q + computeFee(q, takerFeeBps)),
+                takerFeeBps,
+                { privateRfq: true },
+            );
+            const rfq = await program.account.rfq.fetch(rfqPDA);
+            assert.deepStrictEqual(rfq.takerAllowlistRoot, Array.from(allowlistRoot(takers.map(t => t.publicKey))));
+            assert.strictEqual(rfq.committedCount, takers.length, "every allowlisted taker commits");
+        });
+    });
+
+    describe("auto-best selection", () => {
+        it("lets anyone finalize the auction on the best revealed quote", async () => {
+            const quoteAmounts = [1_000_000, 1_500_000, 1_200_000];
+            const takerFeeBps = 100;
+            const { maker, takers, rfqPDA } = await runAuctionToReveal(
+                quoteAmounts,
+                quoteAmounts.map(q => q + computeFee(q, takerFeeBps)),
+                takerFeeBps,
+                { autoSelectBest: true },
+            );
+            const best = takers[1];
+            const makerBaseAccount = getAssociatedTokenAddressSync(baseMint, maker.publicKey);
+            const vaultBaseAta = getAssociatedTokenAddressSync(baseMint, rfqPDA, true);
+            const quoteAccounts = takers.map(t => ({
+                pubkey: quotePdaOf(rfqPDA, t.publicKey), isSigner: false, isWritable: false,
+            }));
+
+            // The maker cannot pick manually
+            let failed = false;
+            try {
+                await program.methods.selectQuote()
+                    .accounts({
+                        maker: maker.publicKey, rfq: rfqPDA, quote: quotePdaOf(rfqPDA, takers[0].publicKey),
+                        baseMint, quoteMint, vaultBaseAta, makerBaseAccount, config: configPda,
+                        baseTokenProgram: TOKEN_PROGRAM_ID,
+                        quoteTokenProgram: TOKEN_PROGRAM_ID,
+                    })
+                    .signers([maker])
+                    .rpc();
+            } catch (e) {
+                failed = true;
+                assert.ok(String(e).includes("AutoSelectEnabled"), String(e));
+            }
+            assert(failed, "select_quote should be rejected on auto-best RFQs");
+
+            // The maker lets the RFQ PDA pull the base deposit
+            await approveChecked(provider.connection, maker, baseMint, makerBaseAccount, rfqPDA, maker,
+                DEFAULT_BASE_AMOUNT, 9);
+
+            const cranker = Keypair.generate();
+            await fund(cranker);
+            const finalize = (quote: PublicKey, remaining: typeof quoteAccounts) => program.methods.finalizeAuction()
+                .accounts({
+                    payer: cranker.publicKey, maker: maker.publicKey, rfq: rfqPDA, quote,
+                    config: configPda, baseMint, quoteMint, vaultBaseAta, makerBaseAccount,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .remainingAccounts(remaining)
+                .signers([cranker])
+                .rpc();
+
+            // Leaving out a revealed quote is rejected
+            failed = false;
+            try {
+                await finalize(quotePdaOf(rfqPDA, takers[0].publicKey), [quoteAccounts[0], quoteAccounts[2]]);
+            } catch (e) {
+                failed = true;
+                assert.ok(String(e).includes("IncompleteQuoteSet"), String(e));
+            }
+            assert(failed, "finalize_auction should require every revealed quote");
+
+            // So is a quote that is not the best
+            failed = false;
+            try {
+                await finalize(quotePdaOf(rfqPDA, takers[2].publicKey), quoteAccounts);
+            } catch (e) {
+                failed = true;
+                assert.ok(String(e).includes("NotBestQuote"), String(e));
+            }
+            assert(failed, "finalize_auction should only select the best quote");
+
+            await finalize(quotePdaOf(rfqPDA, best.publicKey), quoteAccounts);
+
+            const rfq = await program.account.rfq.fetch(rfqPDA);
+            assert.ok("selected" in rfq.state, "rfq should be Selected");
+            assert.ok(rfq.selectedQuote.equals(quotePdaOf(rfqPDA, best.publicKey)), "best quote selected");
+            assert.ok(rfq.baseVaultPayer.equals(cranker.publicKey), "the cranker paid for the base vault");
+            assert.ok(rfq.quoteVaultPayer.equals(cranker.publicKey), "the cranker paid for the quote vault");
+            assert.ok(rfq.bestQuote.equals(quotePdaOf(rfqPDA, best.publicKey)), "reveal tracking agrees with finalize");
+            assert.ok(rfq.bestQuoteAmount.eq(new anchor.BN(1_500_000)), "best quote amount");
+            assert.ok(rfq.secondBestQuoteAmount.eq(new anchor.BN(1_200_000)), "second best quote amount");
+
+            await completeFor(rfqPDA, maker, best);
+            const takerBaseBalance = await getAndLogBalance("After auto-best", "Taker Base",
+                getAssociatedTokenAddressSync(baseMint, best.publicKey));
+            assert.ok(takerBaseBalance.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "best taker receives the base");
+        });
+    });
+
+    describe("must-pick-best selection", () => {
+        it("rejects a maker selection below the best revealed price", async () => {
+            const quoteAmounts = [1_000_000, 1_100_000];
+            const takerFeeBps = 100;
+            const { maker, takers, rfqPDA } = await runAuctionToReveal(
+                quoteAmounts,
+                quoteAmounts.map(q => q + computeFee(q, takerFeeBps)),
+                takerFeeBps,
+                { requireBestQuote: true },
+            );
+
+            const rfq = await program.account.rfq.fetch(rfqPDA);
+            assert.ok(rfq.bestQuote.equals(quotePdaOf(rfqPDA, takers[1].publicKey)), "best quote tracked on reveal");
+            assert.ok(rfq.bestQuoteAmount.eq(new anchor.BN(1_100_000)), "best quote amount");
+            assert.ok(rfq.secondBestQuoteAmount.eq(new anchor.BN(1_000_000)), "second best quote amount");
+
+            const select = (taker: Keypair) => program.methods.selectQuote()
+                .accounts({
+                    maker: maker.publicKey, rfq: rfqPDA, quote: quotePdaOf(rfqPDA, taker.publicKey),
+                    baseMint, quoteMint,
+                    vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqPDA, true),
+                    makerBaseAccount: getAssociatedTokenAddressSync(baseMint, maker.publicKey),
+                    config: configPda,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+
+            let failed = false;
+            try {
+                await select(takers[0]);
+            } catch (e) {
+                failed = true;
+                assert.ok(String(e).includes("NotBestQuote"), String(e));
+            }
+            assert(failed, "select_quote should reject a worse quote");
+
+            await select(takers[1]);
+            const selected = await program.account.rfq.fetch(rfqPDA);
+            assert.ok(selected.selectedQuote.equals(quotePdaOf(rfqPDA, takers[1].publicKey)), "best quote selected");
+        });
+    });
+
+    describe("second-price settlement", () => {
+        it("settles the best quote at the second-highest revealed amount", async () => {
+            const quoteAmounts = [1_000_000, 1_100_000];
+            const takerFeeBps = 100;
+            const { maker, takers, rfqPDA } = await runAuctionToReveal(
+                quoteAmounts,
+                quoteAmounts.map(q => q + computeFee(q, takerFeeBps)),
+                takerFeeBps,
+                { pricingMode: { secondPrice: {} } },
+            );
+            const winner = takers[1];
+            const makerQuoteAccount = getAssociatedTokenAddressSync(quoteMint, maker.publicKey);
+            const takerQuoteAccount = getAssociatedTokenAddressSync(quoteMint, winner.publicKey);
+
+            const select = (taker: Keypair) => program.methods.selectQuote()
+                .accounts({
+                    maker: maker.publicKey, rfq: rfqPDA, quote: quotePdaOf(rfqPDA, taker.publicKey),
+                    baseMint, quoteMint,
+                    vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqPDA, true),
+                    makerBaseAccount: getAssociatedTokenAddressSync(baseMint, maker.publicKey),
+                    config: configPda,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+
+            // Only a quote at the best price can clear under second-price
+            let failed = false;
+            try {
+                await select(takers[0]);
+            } catch (e) {
+                failed = true;
+                assert.ok(String(e).includes("NotBestQuote"), String(e));
+            }
+            assert(failed, "second-price RFQs should only clear on the best quote");
+
+            await select(winner);
+            const settlement = await program.account.settlement.fetch(settlementPda(rfqPDA, winner.publicKey)[0]);
+            assert.ok(settlement.quoteAmount.eq(new anchor.BN(quoteAmounts[0])), "clearing price is the second-best quote");
+
+            const takerQuoteBefore = await getAndLogBalance("Before second-price settlement", "Taker Quote", takerQuoteAccount);
+            await completeFor(rfqPDA, maker, winner);
+
+            const clearingFee = computeFee(quoteAmounts[0], takerFeeBps);
+            const feesTracker = await program.account.feesTracker.fetch(feesTrackerPda(rfqPDA, winner.publicKey)[0]);
+            const [makerQuoteBalance, takerQuoteAfter] = await Promise.all([
+                getAndLogBalance("After second-price settlement", "Maker Quote", makerQuoteAccount),
+                getAndLogBalance("After second-price settlement", "Taker Quote", takerQuoteAccount),
+            ]);
+            assert.ok(makerQuoteBalance.eq(new anchor.BN(quoteAmounts[0])), "maker receives the clearing price");
+            assert.ok(feesTracker.amount.eq(new anchor.BN(clearingFee)), "fee is computed on the clearing price");
+            assert.ok(takerQuoteBefore.sub(takerQuoteAfter).eq(new anchor.BN(quoteAmounts[0] + clearingFee)),
+                "taker pays the clearing price plus fee");
+        });
+    });
+
+    describe("buy-side RFQs", () => {
+        it("lets the maker buy base from the lowest quote", async () => {
+            const quoteAmounts = [1_000_000, 900_000];
+            const takerFeeBps = 100;
+            const { maker, takers, rfqPDA } = await runAuctionToReveal(
+                quoteAmounts,
+                quoteAmounts.map(q => computeFee(q, takerFeeBps)), // takers only pay the fee in quote
+                takerFeeBps,
+                { side: { buy: {} }, maxQuoteAmount: 1_100_000, makerQuoteFunding: 900_000 },
+            );
+            const winner = takers[1];
+            const makerBaseAccount = getAssociatedTokenAddressSync(baseMint, maker.publicKey);
+            const makerQuoteAccount = getAssociatedTokenAddressSync(quoteMint, maker.publicKey);
+            const takerQuoteAccount = getAssociatedTokenAddressSync(quoteMint, winner.publicKey);
+
+            const rfq = await program.account.rfq.fetch(rfqPDA);
+            assert.ok(rfq.bestQuote.equals(quotePdaOf(rfqPDA, winner.publicKey)), "lowest quote is best when buying");
+            assert.ok(rfq.bestQuoteAmount.eq(new anchor.BN(900_000)), "best quote amount");
+            assert.ok(rfq.secondBestQuoteAmount.eq(new anchor.BN(1_000_000)), "second best quote amount");
+
+            // Selection moves the maker's quote into the RFQ quote vault
+            await program.methods.selectQuote()
+                .accounts({
+                    maker: maker.publicKey, rfq: rfqPDA, quote: quotePdaOf(rfqPDA, winner.publicKey),
+                    baseMint, quoteMint,
+                    vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqPDA, true),
+                    makerBaseAccount,
+                    config: configPda,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+            const settlement = await program.account.settlement.fetch(settlementPda(rfqPDA, winner.publicKey)[0]);
+            assert.ok("buy" in settlement.side, "settlement side should be Buy");
+            assert.ok(settlement.vaultQuoteAmount.eq(new anchor.BN(900_000)), "quote vault holds the quote amount");
+            assert.ok(settlement.vaultBaseAmount.eqn(0), "no base in the vault when buying");
+
+            await completeFor(rfqPDA, maker, winner);
+
+            const fee = computeFee(900_000, takerFeeBps);
+            const feesTracker = await program.account.feesTracker.fetch(feesTrackerPda(rfqPDA, winner.publicKey)[0]);
+            const [makerBaseBalance, makerQuoteBalance, takerQuoteBalance] = await Promise.all([
+                getAndLogBalance("After buy settlement", "Maker Base", makerBaseAccount),
+                getAndLogBalance("After buy settlement", "Maker Quote", makerQuoteAccount),
+                getAndLogBalance("After buy settlement", "Taker Quote", takerQuoteAccount),
+            ]);
+            assert.ok(makerBaseBalance.eq(new anchor.BN(1 + DEFAULT_BASE_AMOUNT)), "maker receives the base amount");
+            assert.ok(makerQuoteBalance.eqn(0), "maker paid the quote amount");
+            assert.ok(takerQuoteBalance.eq(new anchor.BN(900_000)), "taker receives the quote amount and paid the fee");
+            assert.ok(feesTracker.amount.eq(new anchor.BN(fee)), "fee is computed on the quote amount");
+
+            const rfqFinal = await program.account.rfq.fetch(rfqPDA);
+            assert.ok("settled" in rfqFinal.state, "rfq should be Settled");
+        });
+    });
+
+    describe("quote-size RFQs", () => {
+        it("spends a fixed quote size on the largest base amount", async () => {
+            const baseAmounts = [DEFAULT_BASE_AMOUNT, DEFAULT_BASE_AMOUNT + 500];
+            const quoteSize = 800_000;
+            const takerFeeBps = 100;
+            const { maker, takers, rfqPDA } = await runAuctionToReveal(
+                baseAmounts,
+                baseAmounts.map(() => computeFee(quoteSize, takerFeeBps)),
+                takerFeeBps,
+                {
+                    side: { buy: {} },
+                    makerQuoteFunding: quoteSize,
+                    sizeMode: { quote: {} },
+                    quoteSize,
+                },
+            );
+            const winner = takers[1];
+            const makerBaseAccount = getAssociatedTokenAddressSync(baseMint, maker.publicKey);
+            const takerQuoteAccount = getAssociatedTokenAddressSync(quoteMint, winner.publicKey);
+
+            const rfq = await program.account.rfq.fetch(rfqPDA);
+            assert.ok(rfq.bestQuote.equals(quotePdaOf(rfqPDA, winner.publicKey)), "largest base is best");
+            assert.ok(rfq.bestQuoteAmount.eq(new anchor.BN(baseAmounts[1])), "best base amount");
+            const quote = await program.account.quote.fetch(quotePdaOf(rfqPDA, winner.publicKey));
+            assert.ok(quote.baseAmount.eq(new anchor.BN(baseAmounts[1])), "quote stores the revealed base");
+            assert.ok(quote.quoteAmount.eq(new anchor.BN(quoteSize)), "quote amount is the fixed size");
+
+            await program.methods.selectQuote()
+                .accounts({
+                    maker: maker.publicKey, rfq: rfqPDA, quote: quotePdaOf(rfqPDA, winner.publicKey),
+                    baseMint, quoteMint,
+                    vaultBaseAta: getAssociatedTokenAddressSync(baseMint, rfqPDA, true),
+                    makerBaseAccount,
+                    config: configPda,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+            const settlement = await program.account.settlement.fetch(settlementPda(rfqPDA, winner.publicKey)[0]);
+            assert.ok(settlement.baseAmount.eq(new anchor.BN(baseAmounts[1])), "settles the revealed base");
+            assert.ok(settlement.quoteAmount.eq(new anchor.BN(quoteSize)), "settles the fixed quote size");
+
+            await completeFor(rfqPDA, maker, winner);
+
+            const [makerBaseBalance, takerQuoteBalance] = await Promise.all([
+                getAndLogBalance("After quote-size settlement", "Maker Base", makerBaseAccount),
+                getAndLogBalance("After quote-size settlement", "Taker Quote", takerQuoteAccount),
+            ]);
+            assert.ok(makerBaseBalance.eq(new anchor.BN(1 + baseAmounts[1])), "maker receives the revealed base");
+            assert.ok(takerQuoteBalance.eq(new anchor.BN(quoteSize)), "taker receives the quote size");
+        });
+    });
+
+    describe("firm RFQs", () => {
+        it("selects against base pre-funded at open", async () => {
+            const quoteAmounts = [1_000_000];
+            const takerFeeBps = 100;
+            const { maker, takers, rfqPDA } = await runAuctionToReveal(
+                quoteAmounts,
+                quoteAmounts.map(q => q + computeFee(q, takerFeeBps)),
+                takerFeeBps,
+                { firm: true },
+            );
+            const winner = takers[0];
+            const makerBaseAccount = getAssociatedTokenAddressSync(baseMint, maker.publicKey);
+            const vaultBaseAta = getAssociatedTokenAddressSync(baseMint, rfqPDA, true);
+
+            const [vaultBeforeSelect, makerBaseBeforeSelect] = await Promise.all([
+                getAndLogBalance("Before firm selection", "RFQ Base Vault", vaultBaseAta),
+                getAndLogBalance("Before firm selection", "Maker Base", makerBaseAccount),
+            ]);
+            assert.ok(vaultBeforeSelect.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "vault funded at open");
+            assert.ok(makerBaseBeforeSelect.isZero(), "maker base already in the vault");
+
+            // Selection does not move base again
+            await program.methods.selectQuote()
+                .accounts({
+                    maker: maker.publicKey, rfq: rfqPDA, quote: quotePdaOf(rfqPDA, winner.publicKey),
+                    baseMint, quoteMint, vaultBaseAta, makerBaseAccount,
+                    config: configPda,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
+                    quoteTokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([maker])
+                .rpc();
+            const [rfq, settlement] = await Promise.all([
+                program.account.rfq.fetch(rfqPDA),
+                program.account.settlement.fetch(settlementPda(rfqPDA, winner.publicKey)[0]),
+            ]);
+            assert.ok(rfq.prefundedBaseAmount.isZero(), "pre-funded base now belongs to the settlement");
+            assert.ok(settlement.vaultBaseAmount.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "settlement vault amount");
+            const vaultAfterSelect = await getAndLogBalance("After firm selection", "RFQ Base Vault", vaultBaseAta);
+            assert.ok(vaultAfterSelect.eq(vaultBeforeSelect), "no second deposit");
+
+            const completeSig = await completeFor(rfqPDA, maker, winner);
+            const takerBase = await getAndLogBalance("After firm settlement", "Taker Base",
+                getAssociatedTokenAddressSync(baseMint, winner.publicKey));
+            assert.ok(takerBase.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "taker receives the pre-funded base");
+
+            const events = await cpiEvents(program, completeSig);
+            const completed = events.find(e => e.name === "settlementCompleted");
+            assert.ok(completed, "SettlementCompleted should be emitted");
+            assert.ok(completed.data.settlement.equals(settlementPda(rfqPDA, winner.publicKey)[0]), "event settlement");
+            assert.ok(completed.data.quoteAmount.eq(new anchor.BN(quoteAmounts[0])), "event quote amount");
+            const closed = events.find(e => e.name === "rfqClosed");
+            assert.ok(closed && "settled" in closed.data.state, "RfqClosed should report Settled");
+        });
+    });
+
 });
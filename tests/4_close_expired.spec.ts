@@ -1,5 +1,4 @@
 import * as anchor from "@coral-xyz/anchor";
-import nacl from "tweetnacl";
 import { Program } from "@coral-xyz/anchor";
 import { SettlementEngine } from "../target/types/settlement_engine";
 import { Ed25519Program, Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
@@ -12,7 +11,7 @@ import {
     getOrCreateAssociatedTokenAccount,
 } from "@solana/spl-token";
 import assert from "assert";
-import { CheckResult, fetchJson, sleep, waitForLiquidityGuardReady } from "./2_quote.spec";
+import { liquidityGuard, provideLiquidityGuardAttestation, waitForLiquidityGuardReady } from "./utils/liquidityGuard";
 import { waitForChainTime } from "./utils/time";
 import { slashedBondsTrackerPda, uuidBytes } from "./1_rfq.spec";
 import { expectedSlashedAmount } from "./utils/slashing";
@@ -21,8 +20,6 @@ anchor.setProvider(anchor.AnchorProvider.env());
 const provider = anchor.getProvider() as anchor.AnchorProvider;
 const program = anchor.workspace.SettlementEngine as Program<SettlementEngine>;
 
-const liquidityGuardURL = "https://liquidity-guard-devnet-skip-c644b6411603.herokuapp.com";
-const liquidityGuard = new PublicKey("5gfPFweV3zJovznZqBra3rv5tWJ5EHVzQY1PqvNA4HGg");
 const DEFAULT_QUOTE_AMOUNT = 1_000_000_001;
 const DEFAULT_BASE_AMOUNT = 1_000_000_000;
 const DEFAULT_BOND_AMOUNT = 1_000_000;
@@ -55,47 +52,10 @@ const getAndLogBalance = async (label: string, owner: string, tokenAccount: Publ
     return balance;
 };
 
-const provideLiquidityGuardAttestation = async (taker: anchor.web3.Keypair,
-    rfqPDA: anchor.web3.PublicKey,
-    quoteMint: anchor.web3.PublicKey,
-    quoteAmount = DEFAULT_QUOTE_AMOUNT,
-    bondAmount = DEFAULT_BOND_AMOUNT,
-    feeAmount = DEFAULT_FEE_AMOUNT) => {
-    const rfqAddr = Buffer.from(rfqPDA.toBytes());
-    const salt = nacl.sign.detached(rfqAddr, taker.secretKey);
-    console.log("salt:", Buffer.from(salt).toString("hex"));
-
-    const payload = {
-        rfq: rfqPDA.toBase58(),
-        taker: taker.publicKey.toBase58(),
-        salt: Buffer.from(salt).toString("hex"),
-        quote_mint: quoteMint.toBase58(),
-        quote_amount: new anchor.BN(quoteAmount).toString(),
-        bond_amount_usdc: new anchor.BN(bondAmount).toString(),
-        taker_fee_bps: new anchor.BN(feeAmount).toString(),
-    };
-
-    const response = await fetchJson<CheckResult>(`${liquidityGuardURL}/check`, {
-        method: "POST",
-        headers: {
-            "Content-Type": "application/json",
-        },
-        body: JSON.stringify(payload),
-    });
-    if ("error" in response) {
-        throw new Error(`Liquidity Guard error: ${response.error}`);
-    } else {
-        return [
-            salt,
-            Buffer.from(response.commit_hash, "hex"),
-            Buffer.from(response.liquidity_proof, "hex"),
-        ];
-    }
-};
-
 const commitQuote = async (
     commit_hash: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
     liquidity_proof: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
+    attestation: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
     taker: Keypair,
     rfqPDA: PublicKey,
     usdcMint: PublicKey,
@@ -105,11 +65,11 @@ const commitQuote = async (
     // Create Ed25519 verification instruction using the helper
     const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
         publicKey: liquidityGuard.toBytes(),
-        message: commit_hash,
+        message: attestation,
         signature: liquidity_proof,
     });
     const commitQuoteIx1 = await program.methods
-        .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), facilitator)
+        .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), facilitator, null, null, [])
         .accounts({
             taker: taker.publicKey,
             rfq: rfqPDA,
@@ -117,6 +77,7 @@ const commitQuote = async (
             config: configPda,
             instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
             takerPaymentAccount: takerPaymentAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
         })
         .instruction();
 
@@ -166,7 +127,7 @@ describe("CLOSE_EXPIRED_RFQ", () => {
         let failed = false;
         try {
             await program.methods
-                .initConfig(usdcMint, treasury.publicKey, liquidityGuard, null)
+                .initConfig(usdcMint, treasury.publicKey, liquidityGuard, null, null, null, null, null)
                 .accounts({ admin: admin.publicKey })
                 .signers([admin])
                 .rpc();
@@ -206,6 +167,22 @@ describe("CLOSE_EXPIRED_RFQ", () => {
         const [rfqPDA, rfqBump] = rfqPda(maker.publicKey, u);
         const [slashedBondsTrackerPDA, bumpslashedBondsTracker] = slashedBondsTrackerPda(rfqPDA);
 
+        // a third party cranks the expiry and earns a bounty out of the slashed bonds
+        const keeper = Keypair.generate();
+        await fund(keeper);
+        const keeperPaymentAccount = await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            admin,
+            usdcMint,
+            keeper.publicKey
+        ).then(a => a.address);
+        const KEEPER_BOUNTY_BPS = 500;
+        await program.methods
+            .updateConfig(null, KEEPER_BOUNTY_BPS)
+            .accounts({ authority: admin.publicKey, config: configPda })
+            .signers([admin])
+            .rpc();
+
         // create token accounts & mint usdc, base and quote.
         const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
         const takerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, taker.publicKey);
@@ -309,9 +286,20 @@ describe("CLOSE_EXPIRED_RFQ", () => {
                     revealTTL,
                     selectionTTL,
                     fundingTTL,
+                    null,
+                    false,
+                    false,
+                    false,
+                    { firstPrice: {} },
+                    { sell: {} },
+                    new anchor.BN(0),
+                    { base: {} },
+                    new anchor.BN(0),
+                    false,
                     null
                 )
                 .accounts({
+                    baseMint, quoteMint,
                     maker: maker.publicKey,
                     config: configPda,
                     usdcMint,
@@ -343,6 +331,7 @@ describe("CLOSE_EXPIRED_RFQ", () => {
                     bondsEscrow,
                     makerPaymentAccount,
                     usdcMint,
+                    tokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([maker])
                 .rpc();
@@ -360,10 +349,11 @@ describe("CLOSE_EXPIRED_RFQ", () => {
         ]);
 
         // taker will commit a valid quote but won't reveal
-        const [_saltQ1, commit_hashQ1, liquidity_proofQ1] = await provideLiquidityGuardAttestation(taker, rfqPDA, quoteMint);
+        const [_saltQ1, commit_hashQ1, liquidity_proofQ1, attestationQ1] = await provideLiquidityGuardAttestation(program, taker, rfqPDA, quoteMint);
         await commitQuote(
             commit_hashQ1,
             liquidity_proofQ1,
+            attestationQ1,
             taker,
             rfqPDA,
             usdcMint,
@@ -371,10 +361,11 @@ describe("CLOSE_EXPIRED_RFQ", () => {
             takerPaymentAccount);
 
         // taker2 will commit an invalid quote (smaller quote amount)
-        const [_saltQ2, commit_hashQ2, liquidity_proofQ2] = await provideLiquidityGuardAttestation(taker2, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
+        const [_saltQ2, commit_hashQ2, liquidity_proofQ2, attestationQ2] = await provideLiquidityGuardAttestation(program, taker2, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
         await commitQuote(
             commit_hashQ2,
             liquidity_proofQ2,
+            attestationQ2,
             taker2,
             rfqPDA,
             usdcMint,
@@ -382,10 +373,11 @@ describe("CLOSE_EXPIRED_RFQ", () => {
             taker2PaymentAccount);
 
         // taker3 will commit a valid quote but won't reveal
-        const [_saltQ3, commit_hashQ3, liquidity_proofQ3] = await provideLiquidityGuardAttestation(taker3, rfqPDA, quoteMint);
+        const [_saltQ3, commit_hashQ3, liquidity_proofQ3, attestationQ3] = await provideLiquidityGuardAttestation(program, taker3, rfqPDA, quoteMint);
         await commitQuote(
             commit_hashQ3,
             liquidity_proofQ3,
+            attestationQ3,
             taker3,
             rfqPDA,
             usdcMint,
@@ -393,10 +385,11 @@ describe("CLOSE_EXPIRED_RFQ", () => {
             taker3PaymentAccount);
 
         // taker4 will commit an invalid quote (smaller quote amount)
-        const [_saltQ4, commit_hashQ4, liquidity_proofQ4] = await provideLiquidityGuardAttestation(taker4, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
+        const [_saltQ4, commit_hashQ4, liquidity_proofQ4, attestationQ4] = await provideLiquidityGuardAttestation(program, taker4, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
         await commitQuote(
             commit_hashQ4,
             liquidity_proofQ4,
+            attestationQ4,
             taker4,
             rfqPDA,
             usdcMint,
@@ -424,6 +417,7 @@ describe("CLOSE_EXPIRED_RFQ", () => {
 
         await program.methods.closeExpired()
             .accounts({
+                keeper: keeper.publicKey,
                 maker: maker.publicKey,
                 rfq: rfqPDA,
                 config: configPda,
@@ -431,8 +425,10 @@ describe("CLOSE_EXPIRED_RFQ", () => {
                 bondsEscrow,
                 treasuryWallet: treasury.publicKey,
                 makerPaymentAccount,
+                keeperPaymentAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
             })
-            .signers([maker])
+            .signers([keeper])
             .rpc();
 
         const [
@@ -442,7 +438,8 @@ describe("CLOSE_EXPIRED_RFQ", () => {
             taker3PaymentAccountBalance,
             taker4PaymentAccountBalance,
             bondsEscrowBalance,
-            treasuryPaymentAccountBalance
+            treasuryPaymentAccountBalance,
+            keeperPaymentAccountBalance,
         ]
             = await Promise.all([
                 getAndLogBalance("After Rfq Expiration", "Maker USDC", makerPaymentAccount),
@@ -452,6 +449,7 @@ describe("CLOSE_EXPIRED_RFQ", () => {
                 getAndLogBalance("After Rfq Expiration", "Taker4 USDC", taker4PaymentAccount),
                 getAndLogBalance("After Rfq Expiration", "RFQ Bonds Vault", bondsEscrow),
                 getAndLogBalance("After Rfq Expiration", "Treasury USCD", treasuryPaymentAccount),
+                getAndLogBalance("After Rfq Expiration", "Keeper USDC", keeperPaymentAccount),
             ]);
 
         const [rfq, slashedBondsTracker] = await Promise.all([
@@ -474,7 +472,180 @@ describe("CLOSE_EXPIRED_RFQ", () => {
         assert(taker3PaymentAccountBalance.isZero(), "taker3 balance mismatch");
         assert(taker4PaymentAccountBalance.isZero(), "taker4 balance mismatch");
         assert(bondsEscrowBalance.isZero(), "bonds escrow should be empty");
-        assert(treasuryPaymentAccountBalance.eq(slashedBondsTracker.amount), "treasury payment balance should be equalt to slashed bonds tracker amount");
+        assert.strictEqual(rfq.keeperBountyBps, KEEPER_BOUNTY_BPS, "keeper bounty should be snapshotted on the rfq");
+        const expectedBounty = expectedSlashed.muln(KEEPER_BOUNTY_BPS).divn(10_000);
+        assert(!expectedBounty.isZero(), "keeper bounty should be non-zero");
+        assert(slashedBondsTracker.keeperBounty.eq(expectedBounty), "keeper bounty mismatch in slashedBondsTracker");
+        assert(keeperPaymentAccountBalance.eq(expectedBounty), "keeper should receive the bounty");
+        assert(treasuryPaymentAccountBalance.eq(slashedBondsTracker.amount.sub(expectedBounty)), "treasury should receive the slashed amount net of the keeper bounty");
+    });
+
+    it("lets a committed taker expire the RFQ when the maker stays silent", async () => {
+        const [maker, taker] = [Keypair.generate(), Keypair.generate()];
+        await Promise.all([fund(maker), fund(taker)]);
+
+        const u = uuidBytes();
+        const [rfqPDA] = rfqPda(maker.publicKey, u);
+        const [slashedBondsTrackerPDA] = slashedBondsTrackerPda(rfqPDA);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        const takerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, taker.publicKey);
+        const treasuryPaymentAccount = getAssociatedTokenAddressSync(usdcMint, treasury.publicKey);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqPDA, true);
+
+        await Promise.all([maker, taker].map(kp =>
+            getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, kp.publicKey)
+                .then(a => mintTo(provider.connection, admin, usdcMint, a.address, admin, DEFAULT_BOND_AMOUNT))));
+
+        await program.methods
+            .initRfq(
+                Array.from(u), baseMint, quoteMint,
+                new anchor.BN(DEFAULT_BOND_AMOUNT),
+                new anchor.BN(DEFAULT_BASE_AMOUNT),
+                new anchor.BN(1_000_000_000),
+                DEFAULT_FEE_AMOUNT,
+                commitTTL, revealTTL, selectionTTL, fundingTTL,
+                null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0),
+                false,
+                null,
+            )
+            .accounts({
+                baseMint, quoteMint,
+                maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount,
+                systemProgram: SystemProgram.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([maker])
+            .rpc();
+        await program.methods.openRfq()
+            .accounts({
+                maker: maker.publicKey, rfq: rfqPDA, config: configPda,
+                bondsEscrow, makerPaymentAccount, usdcMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([maker])
+            .rpc();
+
+        const [_salt, commitHash, liquidityProof, attestation] = await provideLiquidityGuardAttestation(program, taker, rfqPDA, quoteMint);
+        await commitQuote(commitHash, liquidityProof, attestation, taker, rfqPDA, usdcMint, configPda, takerPaymentAccount);
+
+        const rfqCommitted = await program.account.rfq.fetch(rfqPDA);
+        const revealDeadline = rfqCommitted.openedAt!.toNumber() + rfqCommitted.commitTtlSecs + rfqCommitted.revealTtlSecs;
+        await waitForChainTime(provider.connection, revealDeadline, "reveal deadline");
+
+        const treasuryBefore = await provider.connection.getTokenAccountBalance(treasuryPaymentAccount)
+            .then(b => new anchor.BN(b.value.amount))
+            .catch(() => new anchor.BN(0));
+
+        // the maker never shows up; the taker expires the RFQ on its own
+        await program.methods.closeExpired()
+            .accounts({
+                keeper: taker.publicKey,
+                maker: maker.publicKey, rfq: rfqPDA, config: configPda, usdcMint, bondsEscrow,
+                treasuryWallet: treasury.publicKey, makerPaymentAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([taker])
+            .rpc();
+
+        const [rfq, slashedBondsTracker, makerBalance, bondsEscrowBalance, treasuryAfter] = await Promise.all([
+            program.account.rfq.fetch(rfqPDA),
+            program.account.slashedBondsTracker.fetch(slashedBondsTrackerPDA),
+            getAndLogBalance("After taker expiry", "Maker USDC", makerPaymentAccount),
+            getAndLogBalance("After taker expiry", "RFQ Bonds Vault", bondsEscrow),
+            getAndLogBalance("After taker expiry", "Treasury USDC", treasuryPaymentAccount),
+        ]);
+        assert.ok(rfq.state.expired, "rfq state should be expired");
+        assert(slashedBondsTracker.amount.eq(expectedSlashedAmount(rfq, false)), "unrevealed bond should be slashed");
+        assert(slashedBondsTracker.keeperBounty.isZero(), "no bounty without a keeper payment account");
+        assert(treasuryAfter.sub(treasuryBefore).eq(slashedBondsTracker.amount), "treasury should receive the slashed bond");
+        assert(makerBalance.eq(new anchor.BN(DEFAULT_BOND_AMOUNT)), "maker bond should be refunded");
+        assert(bondsEscrowBalance.isZero(), "bonds escrow should be empty");
+    });
+
+    it("returns a firm RFQ's pre-funded base on expiry", async () => {
+        const maker = Keypair.generate();
+        await fund(maker);
+
+        const u = uuidBytes();
+        const [rfqPDA] = rfqPda(maker.publicKey, u);
+        const makerPaymentAccount = getAssociatedTokenAddressSync(usdcMint, maker.publicKey);
+        const makerBaseAccount = getAssociatedTokenAddressSync(baseMint, maker.publicKey);
+        const vaultBaseAta = getAssociatedTokenAddressSync(baseMint, rfqPDA, true);
+        const bondsEscrow = getAssociatedTokenAddressSync(usdcMint, rfqPDA, true);
+
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, maker.publicKey)
+            .then(a => mintTo(provider.connection, admin, usdcMint, a.address, admin, DEFAULT_BOND_AMOUNT));
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, baseMint, maker.publicKey)
+            .then(a => mintTo(provider.connection, admin, baseMint, a.address, admin, DEFAULT_BASE_AMOUNT));
+
+        await program.methods
+            .initRfq(
+                Array.from(u), baseMint, quoteMint,
+                new anchor.BN(DEFAULT_BOND_AMOUNT),
+                new anchor.BN(DEFAULT_BASE_AMOUNT),
+                new anchor.BN(1_000_000_000),
+                DEFAULT_FEE_AMOUNT,
+                commitTTL, revealTTL, selectionTTL, fundingTTL,
+                null, false, false, false, { firstPrice: {} }, { sell: {} }, new anchor.BN(0), { base: {} }, new anchor.BN(0),
+                true, // firm
+                null,
+            )
+            .accounts({
+                baseMint, quoteMint,
+                maker: maker.publicKey, config: configPda, usdcMint, bondsEscrow, makerPaymentAccount,
+                systemProgram: SystemProgram.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([maker])
+            .rpc();
+
+        // Opening a firm RFQ moves base_amount into the vault
+        await program.methods.openRfq()
+            .accounts({
+                maker: maker.publicKey, rfq: rfqPDA, config: configPda,
+                bondsEscrow, makerPaymentAccount, usdcMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                baseMint, makerBaseAccount, vaultBaseAta,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([maker])
+            .rpc();
+        const [vaultAfterOpen, makerBaseAfterOpen] = await Promise.all([
+            getAndLogBalance("After opening firm RFQ", "RFQ Base Vault", vaultBaseAta),
+            getAndLogBalance("After opening firm RFQ", "Maker Base", makerBaseAccount),
+        ]);
+        assert(vaultAfterOpen.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "vault should hold the base amount");
+        assert(makerBaseAfterOpen.isZero(), "maker base should be in the vault");
+        const rfqOpened = await program.account.rfq.fetch(rfqPDA);
+        assert(rfqOpened.prefundedBaseAmount.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "prefunded amount mismatch");
+
+        const revealDeadline = rfqOpened.openedAt!.toNumber() + rfqOpened.commitTtlSecs + rfqOpened.revealTtlSecs;
+        await waitForChainTime(provider.connection, revealDeadline, "reveal deadline");
+
+        await program.methods.closeExpired()
+            .accounts({
+                maker: maker.publicKey, rfq: rfqPDA, config: configPda, usdcMint, bondsEscrow,
+                keeper: maker.publicKey,
+                treasuryWallet: treasury.publicKey, makerPaymentAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                baseMint, vaultBaseAta, makerBaseAccount,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([maker])
+            .rpc();
+
+        const [vaultAfterClose, makerBaseAfterClose] = await Promise.all([
+            getAndLogBalance("After firm RFQ expiration", "RFQ Base Vault", vaultBaseAta),
+            getAndLogBalance("After firm RFQ expiration", "Maker Base", makerBaseAccount),
+        ]);
+        const rfq = await program.account.rfq.fetch(rfqPDA);
+        assert.ok(rfq.state.expired, "rfq state should be expired");
+        assert(rfq.prefundedBaseAmount.isZero(), "prefunded amount should be cleared");
+        assert(vaultAfterClose.isZero(), "base vault should be empty");
+        assert(makerBaseAfterClose.eq(new anchor.BN(DEFAULT_BASE_AMOUNT)), "maker should get the base back");
     });
 
 
@@ -1,5 +1,4 @@
 import * as anchor from "@coral-xyz/anchor";
-import nacl from "tweetnacl";
 import { Program } from "@coral-xyz/anchor";
 import { SettlementEngine } from "../target/types/settlement_engine";
 import { Ed25519Program, Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
@@ -12,7 +11,7 @@ import {
     getOrCreateAssociatedTokenAccount,
 } from "@solana/spl-token";
 import assert from "assert";
-import { CheckResult, fetchJson, sleep, waitForLiquidityGuardReady } from "./2_quote.spec";
+import { liquidityGuard, provideLiquidityGuardAttestation, waitForLiquidityGuardReady } from "./utils/liquidityGuard";
 import { waitForChainTime } from "./utils/time";
 import { slashedBondsTrackerPda, uuidBytes } from "./1_rfq.spec";
 import { expectedSlashedAmount } from "./utils/slashing";
@@ -21,8 +20,6 @@ anchor.setProvider(anchor.AnchorProvider.env());
 const provider = anchor.getProvider() as anchor.AnchorProvider;
 const program = anchor.workspace.SettlementEngine as Program<SettlementEngine>;
 
-const liquidityGuardURL = "https://liquidity-guard-devnet-skip-c644b6411603.herokuapp.com";
-const liquidityGuard = new PublicKey("5gfPFweV3zJovznZqBra3rv5tWJ5EHVzQY1PqvNA4HGg");
 const DEFAULT_QUOTE_AMOUNT = 1_000_000_001;
 const DEFAULT_BASE_AMOUNT = 1_000_000_000;
 const DEFAULT_BOND_AMOUNT = 1_000_000;
@@ -53,8 +50,8 @@ const quotePda = (rfqPDA: PublicKey, taker: Keypair) => PublicKey.findProgramAdd
     program.programId
 );
 
-const settlementPda = (rfqPDA: PublicKey) => PublicKey.findProgramAddressSync(
-    [Buffer.from("settlement"), rfqPDA.toBuffer()],
+const settlementPda = (rfqPDA: PublicKey, taker: Keypair) => PublicKey.findProgramAddressSync(
+    [Buffer.from("settlement"), rfqPDA.toBuffer(), quotePda(rfqPDA, taker)[0].toBuffer()],
     program.programId
 );
 
@@ -64,47 +61,10 @@ const getAndLogBalance = async (label: string, owner: string, tokenAccount: Publ
     return balance;
 };
 
-const provideLiquidityGuardAttestation = async (taker: anchor.web3.Keypair,
-    rfqPDA: anchor.web3.PublicKey,
-    quoteMint: anchor.web3.PublicKey,
-    quoteAmount = DEFAULT_QUOTE_AMOUNT,
-    bondAmount = DEFAULT_BOND_AMOUNT,
-    feeAmount = DEFAULT_FEE_AMOUNT) => {
-    const rfqAddr = Buffer.from(rfqPDA.toBytes());
-    const salt = nacl.sign.detached(rfqAddr, taker.secretKey);
-    console.log("salt:", Buffer.from(salt).toString("hex"));
-
-    const payload = {
-        rfq: rfqPDA.toBase58(),
-        taker: taker.publicKey.toBase58(),
-        salt: Buffer.from(salt).toString("hex"),
-        quote_mint: quoteMint.toBase58(),
-        quote_amount: new anchor.BN(quoteAmount).toString(),
-        bond_amount_usdc: new anchor.BN(bondAmount).toString(),
-        taker_fee_bps: new anchor.BN(feeAmount).toString(),
-    };
-
-    const response = await fetchJson<CheckResult>(`${liquidityGuardURL}/check`, {
-        method: "POST",
-        headers: {
-            "Content-Type": "application/json",
-        },
-        body: JSON.stringify(payload),
-    });
-    if ("error" in response) {
-        throw new Error(`Liquidity Guard error: ${response.error}`);
-    } else {
-        return [
-            salt,
-            Buffer.from(response.commit_hash, "hex"),
-            Buffer.from(response.liquidity_proof, "hex"),
-        ];
-    }
-};
-
 const commitQuote = async (
     commit_hash: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
     liquidity_proof: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
+    attestation: Uint8Array<ArrayBufferLike> | Buffer<ArrayBuffer>,
     taker: Keypair,
     rfqPDA: PublicKey,
     usdcMint: PublicKey,
@@ -114,11 +74,11 @@ const commitQuote = async (
     // Create Ed25519 verification instruction using the helper
     const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
         publicKey: liquidityGuard.toBytes(),
-        message: commit_hash,
+        message: attestation,
         signature: liquidity_proof,
     });
     const commitQuoteIx1 = await program.methods
-        .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), facilitator)
+        .commitQuote(Array.from(commit_hash), Array.from(liquidity_proof), facilitator, null, null, [])
         .accounts({
             taker: taker.publicKey,
             rfq: rfqPDA,
@@ -126,6 +86,7 @@ const commitQuote = async (
             config: configPda,
             instructionSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
             takerPaymentAccount: takerPaymentAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
         })
         .instruction();
 
@@ -186,7 +147,7 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
         let failed = false;
         try {
             await program.methods
-                .initConfig(usdcMint, treasury.publicKey, liquidityGuard, null)
+                .initConfig(usdcMint, treasury.publicKey, liquidityGuard, null, null, null, null, null)
                 .accounts({ admin: admin.publicKey })
                 .signers([admin])
                 .rpc();
@@ -225,7 +186,7 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
 
         const u = uuidBytes();
         [rfqPDA, rfqBump] = rfqPda(maker.publicKey, u);
-        [settlementPDA, bumpSettlement] = settlementPda(rfqPDA);
+        [settlementPDA, bumpSettlement] = settlementPda(rfqPDA, taker);
         [slashedBondsTrackerPDA, bumpslashedBondsTracker] = slashedBondsTrackerPda(rfqPDA);
 
         // create token accounts & mint usdc, base and quote.
@@ -347,9 +308,20 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                     revealTTL,
                     selectionTTL,
                     fundingTTL,
+                    null,
+                    false,
+                    false,
+                    false,
+                    { firstPrice: {} },
+                    { sell: {} },
+                    new anchor.BN(0),
+                    { base: {} },
+                    new anchor.BN(0),
+                    false,
                     null
                 )
                 .accounts({
+                    baseMint, quoteMint,
                     maker: maker.publicKey,
                     config: configPda,
                     usdcMint,
@@ -381,6 +353,7 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                     bondsEscrow,
                     makerPaymentAccount,
                     usdcMint,
+                    tokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([maker])
                 .rpc();
@@ -397,40 +370,44 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
             getAndLogBalance("After opening RFQ", "RFQ Bonds Vault", bondsEscrow),
         ]);
 
-        const [saltQ1, commit_hashQ1, liquidity_proofQ1] = await provideLiquidityGuardAttestation(taker, rfqPDA, quoteMint);
+        const [saltQ1, commit_hashQ1, liquidity_proofQ1, attestationQ1] = await provideLiquidityGuardAttestation(program, taker, rfqPDA, quoteMint);
         await commitQuote(
             commit_hashQ1,
             liquidity_proofQ1,
+            attestationQ1,
             taker,
             rfqPDA,
             usdcMint,
             configPda,
             takerPaymentAccount);
 
-        const [saltQ2, commit_hashQ2, liquidity_proofQ2] = await provideLiquidityGuardAttestation(taker2, rfqPDA, quoteMint);
+        const [saltQ2, commit_hashQ2, liquidity_proofQ2, attestationQ2] = await provideLiquidityGuardAttestation(program, taker2, rfqPDA, quoteMint);
         await commitQuote(
             commit_hashQ2,
             liquidity_proofQ2,
+            attestationQ2,
             taker2,
             rfqPDA,
             usdcMint,
             configPda,
             taker2PaymentAccount);
 
-        const [saltQ3, commit_hashQ3, liquidity_proofQ3] = await provideLiquidityGuardAttestation(taker3, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
+        const [saltQ3, commit_hashQ3, liquidity_proofQ3, attestationQ3] = await provideLiquidityGuardAttestation(program, taker3, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
         await commitQuote(
             commit_hashQ3,
             liquidity_proofQ3,
+            attestationQ3,
             taker3,
             rfqPDA,
             usdcMint,
             configPda,
             taker3PaymentAccount);
 
-        const [saltQ4, commit_hashQ4, liquidity_proofQ4] = await provideLiquidityGuardAttestation(taker4, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
+        const [saltQ4, commit_hashQ4, liquidity_proofQ4, attestationQ4] = await provideLiquidityGuardAttestation(program, taker4, rfqPDA, quoteMint, DEFAULT_QUOTE_AMOUNT / 10);
         await commitQuote(
             commit_hashQ4,
             liquidity_proofQ4,
+            attestationQ4,
             taker4,
             rfqPDA,
             usdcMint,
@@ -495,12 +472,12 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
         await Promise.all([
             program.methods
                 .revealQuote(Array.from(saltQ1), new anchor.BN(DEFAULT_QUOTE_AMOUNT))
-                .accounts({ rfq: rfqPDA, quote: quotePDA, taker: taker.publicKey, config: configPda })
+                .accounts({ rfq: rfqPDA, quote: quotePDA, taker: taker.publicKey, revealer: taker.publicKey, config: configPda })
                 .signers([taker])
                 .rpc(),
             program.methods
                 .revealQuote(Array.from(saltQ2), new anchor.BN(DEFAULT_QUOTE_AMOUNT))
-                .accounts({ rfq: rfqPDA, quote: quote2PDA, taker: taker2.publicKey, config: configPda })
+                .accounts({ rfq: rfqPDA, quote: quote2PDA, taker: taker2.publicKey, revealer: taker2.publicKey, config: configPda })
                 .signers([taker2])
                 .rpc()
         ]);
@@ -519,6 +496,8 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                 vaultBaseAta: baseVault,
                 makerBaseAccount,
                 config: configPda,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+                quoteTokenProgram: TOKEN_PROGRAM_ID,
             })
             .signers([maker])
             .rpc();
@@ -599,10 +578,12 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
         try {
             await program.methods.closeIncomplete()
                 .accounts({
+                    keeper: maker.publicKey,
                     maker: maker.publicKey,
                     config: configPda,
                     rfq: rfqPDA,
                     settlement: settlementPDA,
+                    settlementRentPayer: maker.publicKey,
                     baseMint: wrongBaseMint,
                     vaultBaseAta: wrongBaseVault.address,
                     makerBaseAccount: wrongMakerBaseAccount.address,
@@ -611,6 +592,8 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                     makerPaymentAccount,
                     treasuryWallet: treasury.publicKey,
                     slashBoundsTracker: slashedBondsTrackerPDA,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    baseTokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([maker])
                 .rpc();
@@ -622,10 +605,12 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
 
         await program.methods.closeIncomplete()
             .accounts({
+                keeper: maker.publicKey,
                 maker: maker.publicKey,
                 config: configPda,
                 rfq: rfqPDA,
                 settlement: settlementPDA,
+                settlementRentPayer: maker.publicKey,
                 baseMint,
                 vaultBaseAta: baseVault,
                 makerBaseAccount,
@@ -634,6 +619,8 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                 makerPaymentAccount,
                 treasuryWallet: treasury.publicKey,
                 slashBoundsTracker: slashedBondsTrackerPDA,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
             })
             .signers([maker])
             .rpc();
@@ -743,6 +730,29 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
         const taker3PaymentAccount = getAssociatedTokenAddressSync(usdcMint, taker3.publicKey);
         const taker4PaymentAccount = getAssociatedTokenAddressSync(usdcMint, taker4.publicKey);
         const treasuryPaymentAccount = getAssociatedTokenAddressSync(usdcMint, treasury.publicKey);
+        const baseVault = getAssociatedTokenAddressSync(baseMint, rfqPDA, true);
+        const quoteVault = getAssociatedTokenAddressSync(quoteMint, rfqPDA, true);
+
+        // incomplete RFQ: close_incomplete already closed the settlement, the maker paid for both vaults
+        const closeRfq = () => program.methods.closeRfq()
+            .accounts({
+                maker: maker.publicKey,
+                config: configPda,
+                rfq: rfqPDA,
+                usdcMint,
+                bondsEscrow,
+                slashedBondsTracker: slashedBondsTrackerPDA,
+                baseMint,
+                vaultBaseAta: baseVault,
+                baseVaultPayer: maker.publicKey,
+                baseTokenProgram: TOKEN_PROGRAM_ID,
+                quoteMint,
+                vaultQuoteAta: quoteVault,
+                quoteVaultPayer: maker.publicKey,
+                quoteTokenProgram: TOKEN_PROGRAM_ID,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
 
         let failed = false;
         try {
@@ -756,6 +766,7 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                     takerPaymentAccount,
                     treasuryWallet: treasury.publicKey,
                     slashBoundsTracker: slashedBondsTrackerPDA,
+                    tokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([taker])
                 .rpc();
@@ -774,6 +785,7 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                 takerPaymentAccount: taker2PaymentAccount,
                 treasuryWallet: treasury.publicKey,
                 slashBoundsTracker: slashedBondsTrackerPDA,
+                tokenProgram: TOKEN_PROGRAM_ID,
             })
             .signers([taker2])
             .rpc();
@@ -790,6 +802,7 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                     takerPaymentAccount: taker3PaymentAccount,
                     treasuryWallet: treasury.publicKey,
                     slashBoundsTracker: slashedBondsTrackerPDA,
+                    tokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([taker3])
                 .rpc();
@@ -810,6 +823,7 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
                     takerPaymentAccount: taker4PaymentAccount,
                     treasuryWallet: treasury.publicKey,
                     slashBoundsTracker: slashedBondsTrackerPDA,
+                    tokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([taker4])
                 .rpc();
@@ -818,11 +832,11 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
         }
         assert(failed, "refundQuoteBonds() should fail for taker4: unrevealed quote not refundable");
 
-        const [rfq, slashedBondsTracker, quote, quote2, quote3, quote4] = await Promise.all([
+        const [rfq, slashedBondsTracker, quote, quote2Info, quote3, quote4] = await Promise.all([
             program.account.rfq.fetch(rfqPDA),
             program.account.slashedBondsTracker.fetch(slashedBondsTrackerPDA),
             program.account.quote.fetch(quotePDA),
-            program.account.quote.fetch(quote2PDA),
+            provider.connection.getAccountInfo(quote2PDA),
             program.account.quote.fetch(quote3PDA),
             program.account.quote.fetch(quote4PDA),
         ]);
@@ -849,7 +863,7 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
         const expectedSlashed2 = expectedSlashedAmount(rfq, true);
         assert(slashedBondsTracker.amount.eq(expectedSlashed2), "amount should be equal to expected slashed amount");
         assert(!quote.bondsRefundedAt, "quote bondsRefundedAt should be None"); // no-show
-        assert(!!quote2.bondsRefundedAt, "quote2 bondsRefundedAt should be set");
+        assert.strictEqual(quote2Info, null, "quote2 should be closed once refunded");
         assert(!quote3.bondsRefundedAt, "quote3 bondsRefundedAt should be None");// invalid quote
         assert(!quote4.bondsRefundedAt, "quote4 bondsRefundedAt should be None");// invalid quote
         assert(takerPaymentAccountBalance.isZero(), "taker balance mismatch");
@@ -858,5 +872,55 @@ describe("CLOSE_INCOMPLETE & REFUND_QUOTE_BONDS", () => {
         assert(taker4PaymentAccountBalance.isZero(), "taker4 balance mismatch");
         assert(bondsEscrowBalance.isZero(), `bonds escrow should be 0`);
         assert(treasuryPaymentAccountBalance.eq(slashedBondsTracker.amount), "treasury payment balance should be equalt to slashed bonds tracker amount");
+
+        // close_rfq is held up until every quote is closed
+        let closeRfqFailed = false;
+        try {
+            await closeRfq();
+        } catch (e) {
+            closeRfqFailed = true;
+            assert.ok(String(e).includes("OpenQuotesRemaining"), String(e));
+        }
+        assert(closeRfqFailed, "closeRfq() should fail while slashed quotes are open");
+
+        // slashed quotes (no-show winner, unrevealed quotes) can be closed by anyone;
+        // the rent goes to their taker
+        for (const [owner, ownerQuotePDA] of [[taker, quotePDA], [taker3, quote3PDA], [taker4, quote4PDA]] as const) {
+            const takerLamportsBefore = await provider.connection.getBalance(owner.publicKey);
+            await program.methods.closeQuote()
+                .accounts({
+                    taker: owner.publicKey,
+                    config: configPda,
+                    rfq: rfqPDA,
+                    slashedBondsTracker: slashedBondsTrackerPDA,
+                })
+                .rpc();
+            assert.strictEqual(
+                await provider.connection.getAccountInfo(ownerQuotePDA),
+                null,
+                "slashed quote should be closed"
+            );
+            assert(
+                (await provider.connection.getBalance(owner.publicKey)) > takerLamportsBefore,
+                "quote rent should go back to the taker"
+            );
+        }
+
+        const makerLamportsBefore = await provider.connection.getBalance(maker.publicKey);
+        await closeRfq();
+
+        for (const [label, address] of [
+            ["rfq", rfqPDA],
+            ["bonds escrow", bondsEscrow],
+            ["slashed bonds tracker", slashedBondsTrackerPDA],
+            ["base vault", baseVault],
+            ["quote vault", quoteVault],
+        ] as const) {
+            assert.strictEqual(await provider.connection.getAccountInfo(address), null, `${label} should be closed`);
+        }
+        assert(
+            (await provider.connection.getBalance(maker.publicKey)) > makerLamportsBefore,
+            "maker should get the rent back"
+        );
     });
 });
@@ -0,0 +1,52 @@
+import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+
+// Mirrors programs/settlement-engine/src/allowlist.rs: sha256 leaves and nodes with
+// distinct prefixes, each pair hashed in sorted order.
+const sha256 = (...parts: Buffer[]) => createHash("sha256").update(Buffer.concat(parts)).digest();
+
+export const allowlistLeaf = (taker: anchor.web3.PublicKey) =>
+    sha256(Buffer.from([0x00]), taker.toBuffer());
+
+const node = (a: Buffer, b: Buffer) =>
+    Buffer.compare(a, b) <= 0
+        ? sha256(Buffer.from([0x01]), a, b)
+        : sha256(Buffer.from([0x01]), b, a);
+
+// Levels from the leaves up to the root; an odd node out is carried up unchanged.
+const levels = (takers: anchor.web3.PublicKey[]) => {
+    if (takers.length === 0) {
+        throw new Error("empty allowlist");
+    }
+    const out = [takers.map(allowlistLeaf)];
+    while (out[out.length - 1].length > 1) {
+        const level = out[out.length - 1];
+        const next: Buffer[] = [];
+        for (let i = 0; i < level.length; i += 2) {
+            next.push(i + 1 < level.length ? node(level[i], level[i + 1]) : level[i]);
+        }
+        out.push(next);
+    }
+    return out;
+};
+
+export const allowlistRoot = (takers: anchor.web3.PublicKey[]) => {
+    const all = levels(takers);
+    return all[all.length - 1][0];
+};
+
+export const allowlistProof = (takers: anchor.web3.PublicKey[], taker: anchor.web3.PublicKey) => {
+    let index = takers.findIndex(t => t.equals(taker));
+    if (index < 0) {
+        throw new Error("taker not in allowlist");
+    }
+    const proof: Buffer[] = [];
+    for (const level of levels(takers).slice(0, -1)) {
+        const sibling = index ^ 1;
+        if (sibling < level.length) {
+            proof.push(level[sibling]);
+        }
+        index = Math.floor(index / 2);
+    }
+    return proof;
+};
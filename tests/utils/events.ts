@@ -0,0 +1,34 @@
+import * as anchor from "@coral-xyz/anchor";
+
+// Decodes the events a transaction emitted through `emit_cpi!`: each one is a
+// self-CPI whose data is EVENT_IX_TAG (8 bytes) followed by the encoded event.
+export const cpiEvents = async (program: anchor.Program<any>, signature: string) => {
+    const connection = program.provider.connection;
+    await connection.confirmTransaction(signature, "confirmed");
+    const tx = await connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+    });
+    if (!tx?.meta?.innerInstructions) {
+        return [];
+    }
+    const keys = tx.transaction.message.getAccountKeys({
+        accountKeysFromLookups: tx.meta.loadedAddresses,
+    });
+    const events = [];
+    for (const inner of tx.meta.innerInstructions) {
+        for (const ix of inner.instructions) {
+            if (!keys.get(ix.programIdIndex)?.equals(program.programId)) {
+                continue;
+            }
+            const data = anchor.utils.bytes.bs58.decode(ix.data);
+            const event = program.coder.events.decode(
+                anchor.utils.bytes.base64.encode(Buffer.from(data.subarray(8))),
+            );
+            if (event) {
+                events.push(event);
+            }
+        }
+    }
+    return events;
+};
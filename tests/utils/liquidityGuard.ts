@@ -0,0 +1,220 @@
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
+import nacl from "tweetnacl";
+import { SettlementEngine } from "../../target/types/settlement_engine";
+
+/**
+ * Liquidity-guard service the specs get commit hashes and attestations from when
+ * LIQUIDITY_GUARD=remote. By default a local test key signs them, so the specs run offline.
+ */
+export const liquidityGuardURL =
+    process.env.LIQUIDITY_GUARD_URL ?? "https://liquidity-guard-devnet-skip-c644b6411603.herokuapp.com";
+export const remoteLiquidityGuard = process.env.LIQUIDITY_GUARD === "remote";
+
+const REMOTE_LIQUIDITY_GUARD = new PublicKey("5gfPFweV3zJovznZqBra3rv5tWJ5EHVzQY1PqvNA4HGg");
+
+const sha256 = (...parts: Buffer[]) => createHash("sha256").update(Buffer.concat(parts)).digest();
+
+// Deterministic test key, so every spec configures the same guard
+const localGuard = nacl.sign.keyPair.fromSeed(sha256(Buffer.from("settlement-engine:test-liquidity-guard")));
+
+/** Guard key the specs put in their Config and verify attestations against */
+export const liquidityGuard = remoteLiquidityGuard ? REMOTE_LIQUIDITY_GUARD : new PublicKey(localGuard.publicKey);
+
+/** Network the guard reports in its /check responses */
+export const liquidityGuardNetwork = remoteLiquidityGuard ? "Devnet" : "Localnet";
+
+export interface CheckRequest {
+    rfq: string;
+    program_id: string;
+    rfq_open_nonce: string;
+    taker: string;
+    salt: string;
+    quote_mint: string;
+    quote_amount: string;
+    bond_amount_usdc: string;
+    taker_fee_bps: string;
+}
+
+export interface CheckResponse {
+    rfq: string;
+    salt: string;
+    taker: string;
+    usdc_mint?: string;
+    quote_mint: string;
+    quote_amount: string;
+    bond_amount_usdc: string;
+    taker_fee_bps: string;
+    service_pubkey: string;
+    commit_hash: string;
+    liquidity_proof: string;
+    attestation: string;
+    network: string;
+    skip_fund_checks: boolean;
+    timestamp: number;
+}
+
+export interface ErrorResponse {
+    error: string;
+}
+
+export type CheckResult = CheckResponse | ErrorResponse;
+
+export async function fetchJson<T>(url: string, init?: RequestInit): Promise<T> {
+    const res = await fetch(url, init);
+
+    if (!res.ok) {
+        const errBody = await res.json().catch(() => ({}));
+        throw new Error(
+            `HTTP ${res.status}: ${JSON.stringify(errBody, null, 2)}`
+        );
+    }
+
+    return res.json() as Promise<T>;
+}
+
+export function sleep(ms: number): Promise<void> {
+    return new Promise(resolve => setTimeout(resolve, ms));
+}
+
+export async function waitForLiquidityGuardReady(maxWaitMs = 10_000, pollMs = 500) {
+    if (!remoteLiquidityGuard) return;
+    const start = Date.now();
+    let lastError: unknown;
+    console.log(`Waiting for Liquidity Guard to be reachable (timeout ${maxWaitMs}ms)...`);
+
+    while (Date.now() - start < maxWaitMs) {
+        const controller = new AbortController();
+        const timer = setTimeout(() => controller.abort(), Math.min(pollMs, 2_000));
+        try {
+            const res = await fetch(`${liquidityGuardURL}/health`, { signal: controller.signal });
+            if (res.ok || res.status === 404) {
+                console.log("Liquidity Guard is reachable");
+                return;
+            }
+            lastError = new Error(`HTTP ${res.status}`);
+        } catch (err) {
+            lastError = err;
+        } finally {
+            clearTimeout(timer);
+        }
+        await sleep(pollMs);
+    }
+
+    const suffix = lastError ? ` (last error: ${String(lastError)})` : "";
+    throw new Error(`Liquidity Guard not ready after ${maxWaitMs}ms${suffix}`);
+}
+
+// Commit hash v1, as reveal_quote recomputes it:
+// sha256(domain || version || salt || rfq || taker || mint || amount || bond_amount || taker_fee_bps || open_nonce)
+const COMMIT_HASH_DOMAIN = Buffer.from("settlement-engine:commit");
+const ATTESTATION_DOMAIN = Buffer.from("settlement-engine:liquidity-guard");
+const ATTESTATION_TTL_SECS = 3_600;
+
+const u64le = (value: string | number) => {
+    const buf = Buffer.alloc(8);
+    buf.writeBigUInt64LE(BigInt(value));
+    return buf;
+};
+
+export const commitHash = (req: CheckRequest) => {
+    const feeBps = Buffer.alloc(2);
+    feeBps.writeUInt16LE(Number(req.taker_fee_bps));
+    return sha256(
+        COMMIT_HASH_DOMAIN,
+        Buffer.from([1]),
+        Buffer.from(req.salt, "hex"),
+        new PublicKey(req.rfq).toBuffer(),
+        new PublicKey(req.taker).toBuffer(),
+        new PublicKey(req.quote_mint).toBuffer(),
+        u64le(req.quote_amount),
+        u64le(req.bond_amount_usdc),
+        feeBps,
+        u64le(req.rfq_open_nonce),
+    );
+};
+
+// Answers /check like the guard does (funds checks skipped), signing with the local test key.
+// The attested max notional is the quoted amount itself.
+const checkLocally = (req: CheckRequest): CheckResponse => {
+    const timestamp = Math.floor(Date.now() / 1000);
+    const hash = commitHash(req);
+    const validUntil = Buffer.alloc(8);
+    validUntil.writeBigInt64LE(BigInt(timestamp + ATTESTATION_TTL_SECS));
+    // domain || version || program_id || rfq || taker || commit_hash || max_notional || valid_until
+    const attestation = Buffer.concat([
+        ATTESTATION_DOMAIN,
+        Buffer.from([1]),
+        new PublicKey(req.program_id).toBuffer(),
+        new PublicKey(req.rfq).toBuffer(),
+        new PublicKey(req.taker).toBuffer(),
+        hash,
+        u64le(req.quote_amount),
+        validUntil,
+    ]);
+    return {
+        rfq: req.rfq,
+        salt: req.salt,
+        taker: req.taker,
+        quote_mint: req.quote_mint,
+        quote_amount: req.quote_amount,
+        bond_amount_usdc: req.bond_amount_usdc,
+        taker_fee_bps: req.taker_fee_bps,
+        service_pubkey: liquidityGuard.toBase58(),
+        commit_hash: hash.toString("hex"),
+        liquidity_proof: Buffer.from(nacl.sign.detached(attestation, localGuard.secretKey)).toString("hex"),
+        attestation: attestation.toString("hex"),
+        network: liquidityGuardNetwork,
+        skip_fund_checks: true,
+        timestamp,
+    };
+};
+
+/** POSTs to the guard's /check, or signs locally unless LIQUIDITY_GUARD=remote */
+export async function checkLiquidity(req: CheckRequest): Promise<CheckResult> {
+    if (!remoteLiquidityGuard) return checkLocally(req);
+    return fetchJson<CheckResult>(`${liquidityGuardURL}/check`, {
+        method: "POST",
+        headers: {
+            "Content-Type": "application/json",
+        },
+        body: JSON.stringify(req),
+    });
+}
+
+/**
+ * Salt, commit hash, liquidity proof and attestation for a taker's quote on an open RFQ.
+ * `quoteMint` / `quoteAmount` are the base mint and amount on quote-size RFQs.
+ */
+export const provideLiquidityGuardAttestation = async (
+    program: anchor.Program<SettlementEngine>,
+    taker: anchor.web3.Keypair,
+    rfqPDA: PublicKey,
+    quoteMint: PublicKey,
+    quoteAmount = 1_000_000_001,
+    bondAmount = 1_000_000,
+    takerFeeBps = 1_000,
+): Promise<[Uint8Array, Buffer, Buffer, Buffer]> => {
+    const salt = nacl.sign.detached(rfqPDA.toBuffer(), taker.secretKey);
+    const response = await checkLiquidity({
+        rfq: rfqPDA.toBase58(),
+        program_id: program.programId.toBase58(),
+        rfq_open_nonce: (await program.account.rfq.fetch(rfqPDA)).openNonce.toString(),
+        taker: taker.publicKey.toBase58(),
+        salt: Buffer.from(salt).toString("hex"),
+        quote_mint: quoteMint.toBase58(),
+        quote_amount: new anchor.BN(quoteAmount).toString(),
+        bond_amount_usdc: new anchor.BN(bondAmount).toString(),
+        taker_fee_bps: new anchor.BN(takerFeeBps).toString(),
+    });
+    if ("error" in response) {
+        throw new Error(`Liquidity Guard error: ${response.error}`);
+    }
+    return [
+        salt,
+        Buffer.from(response.commit_hash, "hex"),
+        Buffer.from(response.liquidity_proof, "hex"),
+        Buffer.from(response.attestation, "hex"),
+    ];
+};
@@ -0,0 +1,59 @@
+import * as anchor from "@coral-xyz/anchor";
+import { generateKeyPairSync, KeyObject, sign } from "crypto";
+
+export const SECP256R1_PROGRAM_ID = new anchor.web3.PublicKey("Secp256r1SigVerify1111111111111111111111111");
+
+// P-256 group order; the precompile only accepts low-S signatures
+const P256_N = BigInt("0xffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551");
+
+export type Secp256r1Keypair = {
+    privateKey: KeyObject;
+    /** 33-byte compressed public key */
+    publicKey: Buffer;
+    /** x-coordinate, as stored on Config / GuardKey */
+    key: anchor.web3.PublicKey;
+};
+
+export const generateSecp256r1Keypair = (): Secp256r1Keypair => {
+    const { privateKey, publicKey } = generateKeyPairSync("ec", { namedCurve: "P-256" });
+    const jwk = publicKey.export({ format: "jwk" });
+    const x = Buffer.from(jwk.x!, "base64url");
+    const y = Buffer.from(jwk.y!, "base64url");
+    const prefix = (y[y.length - 1] & 1) === 0 ? 0x02 : 0x03;
+    return {
+        privateKey,
+        publicKey: Buffer.concat([Buffer.from([prefix]), x]),
+        key: new anchor.web3.PublicKey(x),
+    };
+};
+
+/** 64-byte r || s signature over sha256(message), normalized to low-S */
+export const signSecp256r1 = (kp: Secp256r1Keypair, message: Buffer) => {
+    const sig = sign("sha256", message, { key: kp.privateKey, dsaEncoding: "ieee-p1363" });
+    let s = BigInt("0x" + sig.subarray(32).toString("hex"));
+    if (s > P256_N / BigInt(2)) {
+        s = P256_N - s;
+    }
+    return Buffer.concat([sig.subarray(0, 32), Buffer.from(s.toString(16).padStart(64, "0"), "hex")]);
+};
+
+/** Secp256r1 precompile instruction with a single signature, all data in this instruction */
+export const createSecp256r1Instruction = (publicKey: Buffer, message: Buffer, signature: Buffer) => {
+    const headerLen = 2 + 14;
+    const pubkeyOffset = headerLen;
+    const sigOffset = pubkeyOffset + publicKey.length;
+    const msgOffset = sigOffset + signature.length;
+    const data = Buffer.alloc(msgOffset + message.length);
+    data.writeUInt8(1, 0); // num_signatures
+    data.writeUInt16LE(sigOffset, 2);
+    data.writeUInt16LE(0xffff, 4);
+    data.writeUInt16LE(pubkeyOffset, 6);
+    data.writeUInt16LE(0xffff, 8);
+    data.writeUInt16LE(msgOffset, 10);
+    data.writeUInt16LE(message.length, 12);
+    data.writeUInt16LE(0xffff, 14);
+    publicKey.copy(data, pubkeyOffset);
+    signature.copy(data, sigOffset);
+    message.copy(data, msgOffset);
+    return new anchor.web3.TransactionInstruction({ programId: SECP256R1_PROGRAM_ID, keys: [], data });
+};
@@ -0,0 +1,55 @@
+import * as anchor from "@coral-xyz/anchor";
+import { SettlementEngine } from "../../target/types/settlement_engine";
+
+const PAUSE_SELECT_QUOTE = 1 << 3;
+
+const configGenerationPda = (program: anchor.Program<SettlementEngine>) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("config_generation")], program.programId)[0];
+
+export const selectionPausePda = (program: anchor.Program<SettlementEngine>, index: number) => {
+    const seed = Buffer.alloc(8);
+    seed.writeBigUInt64LE(BigInt(index));
+    return anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("selection_pause"), seed], program.programId)[0];
+};
+
+/** SelectionPause accounts set_pause needs to move from the current flags to `paused`. */
+export const setPauseAccounts = async (
+    program: anchor.Program<SettlementEngine>,
+    config: anchor.web3.PublicKey,
+    paused: number,
+) => {
+    const [cfg, generation] = await Promise.all([
+        program.account.config.fetch(config),
+        program.account.configGeneration.fetch(configGenerationPda(program)),
+    ]);
+    const count = generation.selectPauseCount.toNumber();
+    const wasPaused = (cfg.paused & PAUSE_SELECT_QUOTE) !== 0;
+    const isPaused = (paused & PAUSE_SELECT_QUOTE) !== 0;
+    return {
+        newSelectionPause: !wasPaused && isPaused ? selectionPausePda(program, count) : null,
+        openSelectionPause: wasPaused && !isPaused ? selectionPausePda(program, count - 1) : null,
+    };
+};
+
+/**
+ * refund_quote_bonds' proof for an ignored RFQ: the last selection pause started at or
+ * before its selection deadline, and the pause right after it.
+ */
+export const selectionPauseProof = async (program: anchor.Program<SettlementEngine>, rfqAddr: anchor.web3.PublicKey) => {
+    const [rfq, generation] = await Promise.all([
+        program.account.rfq.fetch(rfqAddr),
+        program.account.configGeneration.fetch(configGenerationPda(program)),
+    ]);
+    const selectionDeadline = rfq.openedAt.toNumber() + rfq.commitTtlSecs + rfq.revealTtlSecs + rfq.selectionTtlSecs;
+    let last = generation.selectPauseCount.toNumber() - 1;
+    while (last >= 0) {
+        const pause = await program.account.selectionPause.fetch(selectionPausePda(program, last));
+        if (pause.startedAt.toNumber() <= selectionDeadline) break;
+        last -= 1;
+    }
+    const next = last + 1;
+    return {
+        selectionPause: last >= 0 ? selectionPausePda(program, last) : null,
+        nextSelectionPause: next < generation.selectPauseCount.toNumber() ? selectionPausePda(program, next) : null,
+    };
+};
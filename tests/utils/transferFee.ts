@@ -0,0 +1,49 @@
+import * as anchor from "@coral-xyz/anchor";
+import {
+    createInitializeMintInstruction,
+    createInitializeTransferFeeConfigInstruction,
+    ExtensionType,
+    getMintLen,
+    TOKEN_2022_PROGRAM_ID,
+} from "@solana/spl-token";
+
+/** Create a Token-2022 mint carrying a TransferFeeConfig extension (payer is both authorities). */
+export const createTransferFeeMint = async (
+    connection: anchor.web3.Connection,
+    payer: anchor.web3.Keypair,
+    feeBps: number,
+    maxFee: bigint,
+    decimals = 9,
+) => {
+    const mint = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports = await connection.getMinimumBalanceForRentExemption(mintLen);
+
+    const tx = new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+            fromPubkey: payer.publicKey,
+            newAccountPubkey: mint.publicKey,
+            space: mintLen,
+            lamports,
+            programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+            mint.publicKey,
+            payer.publicKey,
+            payer.publicKey,
+            feeBps,
+            maxFee,
+            TOKEN_2022_PROGRAM_ID,
+        ),
+        createInitializeMintInstruction(mint.publicKey, decimals, payer.publicKey, null, TOKEN_2022_PROGRAM_ID),
+    );
+    await anchor.web3.sendAndConfirmTransaction(connection, tx, [payer, mint]);
+    return mint.publicKey;
+};
+
+/** Amount to send so the receiver nets `net` under a fee of `feeBps` (mirrors the on-chain gross-up). */
+export const grossUp = (net: number, feeBps: number, maxFee: number) => {
+    if (net === 0 || feeBps === 0) return net;
+    const raw = Math.ceil((net * 10_000) / (10_000 - feeBps));
+    return raw - net >= maxFee ? net + maxFee : raw;
+};
@@ -0,0 +1,53 @@
+import * as anchor from "@coral-xyz/anchor";
+import {
+    createInitializeMintInstruction,
+    createInitializeTransferHookInstruction,
+    ExtensionType,
+    getMintLen,
+    TOKEN_2022_PROGRAM_ID,
+} from "@solana/spl-token";
+import { TestTransferHook } from "../../target/types/test_transfer_hook";
+
+const extraAccountMetasPda = (hook: anchor.Program<TestTransferHook>, mint: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("extra-account-metas"), mint.toBuffer()], hook.programId)[0];
+
+export const hookCounterPda = (hook: anchor.Program<TestTransferHook>, mint: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("counter"), mint.toBuffer()], hook.programId)[0];
+
+/** Create a Token-2022 mint whose transfers run the test transfer hook (payer is the mint authority). */
+export const createTransferHookMint = async (
+    connection: anchor.web3.Connection,
+    payer: anchor.web3.Keypair,
+    hook: anchor.Program<TestTransferHook>,
+    decimals = 9,
+) => {
+    const mint = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferHook]);
+    const lamports = await connection.getMinimumBalanceForRentExemption(mintLen);
+
+    const tx = new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+            fromPubkey: payer.publicKey,
+            newAccountPubkey: mint.publicKey,
+            space: mintLen,
+            lamports,
+            programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferHookInstruction(mint.publicKey, payer.publicKey, hook.programId, TOKEN_2022_PROGRAM_ID),
+        createInitializeMintInstruction(mint.publicKey, decimals, payer.publicKey, null, TOKEN_2022_PROGRAM_ID),
+    );
+    await anchor.web3.sendAndConfirmTransaction(connection, tx, [payer, mint]);
+
+    await hook.methods.initializeExtraAccountMetaList()
+        .accounts({ payer: payer.publicKey, mint: mint.publicKey })
+        .signers([payer])
+        .rpc();
+    return mint.publicKey;
+};
+
+/** remaining_accounts a hooked transfer needs: the hook program, its extra-account-metas PDA and the counter it lists. */
+export const transferHookAccounts = (hook: anchor.Program<TestTransferHook>, mint: anchor.web3.PublicKey): anchor.web3.AccountMeta[] => [
+    { pubkey: hook.programId, isSigner: false, isWritable: false },
+    { pubkey: extraAccountMetasPda(hook, mint), isSigner: false, isWritable: false },
+    { pubkey: hookCounterPda(hook, mint), isSigner: false, isWritable: true },
+];